@@ -0,0 +1,87 @@
+use hub_core::{anyhow::Result, prelude::*, uuid::Uuid};
+use sea_orm::{prelude::*, sea_query::OnConflict, Set};
+
+use crate::{db::Connection, delivery::Delivery, entities::organization_applications};
+
+/// Returns the application ID of an organization, creating its application when it has none yet.
+/// Organizations created before the consumer existed never received one from their `OrganizationCreated` event.
+/// The application is named after the organization ID when `name` is not known.
+///
+/// # Errors
+/// This function fails if the application cannot be created or stored.
+pub async fn get_or_create(
+    db: &Connection,
+    delivery: &Delivery,
+    organization_id: Uuid,
+    name: Option<String>,
+) -> Result<String> {
+    if let Some(organization_application) = find(db, organization_id).await? {
+        return Ok(organization_application.svix_app_id);
+    }
+
+    // the application uid is the organization ID, so an application created by a previous attempt is reused
+    let app_id = delivery
+        .create_application(
+            name.unwrap_or_else(|| organization_id.to_string()),
+            organization_id.to_string(),
+        )
+        .await
+        .context("failed to create application for org")?;
+
+    let organization_application = organization_applications::ActiveModel {
+        svix_app_id: Set(app_id),
+        organization_id: Set(organization_id),
+        ..Default::default()
+    };
+
+    organization_applications::Entity::insert(organization_application)
+        .on_conflict(
+            OnConflict::column(organization_applications::Column::OrganizationId)
+                .do_nothing()
+                .to_owned(),
+        )
+        .exec_without_returning(db.get())
+        .await?;
+
+    // another request may have stored the application first
+    let organization_application = find(db, organization_id)
+        .await?
+        .context("organization application not stored")?;
+
+    Ok(organization_application.svix_app_id)
+}
+
+/// Creates the missing applications of organizations, returning how many were created.
+///
+/// # Errors
+/// This function fails if an application cannot be created or stored.
+pub async fn backfill(
+    db: &Connection,
+    delivery: &Delivery,
+    organization_ids: &[Uuid],
+) -> Result<usize> {
+    let mut created = 0;
+
+    for organization_id in organization_ids {
+        if find(db, *organization_id).await?.is_some() {
+            continue;
+        }
+
+        let app_id = get_or_create(db, delivery, *organization_id, None).await?;
+        info!(%organization_id, app_id, "backfilled organization application");
+
+        created += 1;
+    }
+
+    Ok(created)
+}
+
+async fn find(
+    db: &Connection,
+    organization_id: Uuid,
+) -> Result<Option<organization_applications::Model>> {
+    Ok(organization_applications::Entity::find()
+        .filter(organization_applications::Column::OrganizationId.eq(organization_id))
+        .one(db.get())
+        .await?)
+}
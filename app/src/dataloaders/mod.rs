@@ -1,3 +1,3 @@
 mod webhook;
 
-pub use webhook::{WebhookLoader, WebhooksLoader};
+pub use webhook::WebhookLoader;
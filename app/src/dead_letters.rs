@@ -0,0 +1,235 @@
+use std::sync::Arc;
+
+use hub_core::{
+    anyhow::{Context, Error, Result},
+    chrono::{TimeZone, Utc},
+    prelude::*,
+    producer::Producer,
+    uuid::Uuid,
+};
+use poem::async_trait;
+use sea_orm::{prelude::*, sea_query::OnConflict, QueryOrder, Set};
+
+use crate::{
+    db::Connection, delivery::Delivery, entities::dead_letters, events,
+    validation::PayloadValidation, EventsProducer, Services, Source,
+};
+
+/// The Kafka topic events that could not be processed are published to.
+pub const TOPIC: &str = "hub-webhooks-dlq";
+
+/// An event that could not be processed, published to [`TOPIC`] with its original Kafka message
+/// so other services can inspect or replay it.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct DeadLetter {
+    /// The ID of the dead letter, to re-drive it with the `redrive-dead-letters` command
+    #[prost(string, tag = "1")]
+    pub id: String,
+    #[prost(string, tag = "2")]
+    pub topic: String,
+    #[prost(int32, tag = "3")]
+    pub partition: i32,
+    #[prost(int64, tag = "4")]
+    pub offset: i64,
+    #[prost(bytes = "vec", tag = "5")]
+    pub key: Vec<u8>,
+    #[prost(bytes = "vec", tag = "6")]
+    pub payload: Vec<u8>,
+    #[prost(string, tag = "7")]
+    pub error: String,
+    #[prost(uint32, tag = "8")]
+    pub attempts: u32,
+    /// When processing was given up, in RFC 3339 format
+    #[prost(string, tag = "9")]
+    pub failed_at: String,
+    /// When the original message was produced, in RFC 3339 format
+    #[prost(string, tag = "10")]
+    pub timestamp: String,
+}
+
+/// The key of a [`DeadLetter`], the ID of the dead letter.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct DeadLetterKey {
+    #[prost(string, tag = "1")]
+    pub id: String,
+}
+
+impl hub_core::producer::Message for DeadLetter {
+    type Key = DeadLetterKey;
+}
+
+/// Publishes the events that could not be processed to the dead-letter topic.
+#[async_trait]
+pub trait DeadLetterPublisher: Send + Sync {
+    /// Publishes a dead letter keyed by its ID.
+    ///
+    /// # Errors
+    /// This function fails if the dead letter cannot be published.
+    async fn send(&self, dead_letter: &DeadLetter, key: &DeadLetterKey) -> Result<()>;
+}
+
+#[async_trait]
+impl DeadLetterPublisher for Producer<DeadLetter> {
+    async fn send(&self, dead_letter: &DeadLetter, key: &DeadLetterKey) -> Result<()> {
+        Producer::send(self, Some(dead_letter), Some(key)).await?;
+
+        Ok(())
+    }
+}
+
+/// The producer of the dead-letter topic shared by the pipeline workers.
+pub type DeadLetterQueue = Arc<dyn DeadLetterPublisher>;
+
+/// Publishes an event that could not be processed to the dead-letter topic, and records it so it
+/// can be re-driven once the cause is fixed. The position of its Kafka message is kept so
+/// re-driven messages report the same source.
+/// A message read again because its dead letter failed to be published keeps the dead letter
+/// recorded the first time, which is published again.
+///
+/// # Errors
+/// This function fails if the dead letter cannot be recorded or published.
+pub async fn store(
+    db: &Connection,
+    queue: &DeadLetterQueue,
+    msg: &Services,
+    source: &Source,
+    error: &Error,
+    attempts: u32,
+) -> Result<Uuid> {
+    let (topic, key, payload) = msg.encode();
+    let error = format!("{error:?}");
+
+    let dead_letter = dead_letters::ActiveModel {
+        topic: Set(topic.to_string()),
+        key: Set(key),
+        payload: Set(payload),
+        kafka_partition: Set(source.partition),
+        kafka_offset: Set(source.offset),
+        kafka_timestamp: Set(Some(source.timestamp.naive_utc())),
+        error: Set(error.clone()),
+        attempts: Set(i32::try_from(attempts).unwrap_or(i32::MAX)),
+        ..Default::default()
+    };
+
+    dead_letters::Entity::insert(dead_letter)
+        .on_conflict(
+            OnConflict::columns([
+                dead_letters::Column::Topic,
+                dead_letters::Column::KafkaPartition,
+                dead_letters::Column::KafkaOffset,
+            ])
+            .do_nothing()
+            .to_owned(),
+        )
+        .exec_without_returning(db.get())
+        .await?;
+
+    let dead_letter = dead_letters::Entity::find()
+        .filter(dead_letters::Column::Topic.eq(topic))
+        .filter(dead_letters::Column::KafkaPartition.eq(source.partition))
+        .filter(dead_letters::Column::KafkaOffset.eq(source.offset))
+        .one(db.get())
+        .await?
+        .context("dead letter not found")?;
+
+    let id = dead_letter.id.to_string();
+
+    queue
+        .send(
+            &DeadLetter {
+                id: id.clone(),
+                topic: dead_letter.topic,
+                partition: source.partition,
+                offset: source.offset,
+                key: dead_letter.key,
+                payload: dead_letter.payload,
+                error,
+                attempts,
+                failed_at: Utc.from_utc_datetime(&dead_letter.created_at).to_rfc3339(),
+                timestamp: source.timestamp.to_rfc3339(),
+            },
+            &DeadLetterKey { id },
+        )
+        .await?;
+
+    Ok(dead_letter.id)
+}
+
+/// Processes dead letters again, all of those not yet re-driven when `ids` is empty.
+/// The IDs are those of the messages published to [`TOPIC`].
+/// Dead letters that succeed are marked as re-driven, the others keep their latest error. Those stored before the
+/// time of their Kafka message was recorded report when they were dead-lettered instead.
+/// Returns the number of dead letters processed successfully.
+///
+/// # Errors
+/// This function fails if the dead letters cannot be loaded or updated.
+pub async fn redrive(
+    db: &Connection,
+    delivery: &Delivery,
+    producer: &EventsProducer,
+    validation: PayloadValidation,
+    ids: &[Uuid],
+) -> Result<usize> {
+    let mut query = dead_letters::Entity::find().filter(dead_letters::Column::RedrivenAt.is_null());
+
+    if !ids.is_empty() {
+        query = query.filter(dead_letters::Column::Id.is_in(ids.iter().copied()));
+    }
+
+    // in the order they were read, so the events of a project are processed in order again
+    let dead_letters = query
+        .order_by_asc(dead_letters::Column::CreatedAt)
+        .order_by_asc(dead_letters::Column::Id)
+        .all(db.get())
+        .await?;
+    let mut redriven = 0;
+
+    for dead_letter in dead_letters {
+        let source = Source {
+            topic: dead_letter.topic.clone(),
+            partition: dead_letter.kafka_partition,
+            offset: dead_letter.kafka_offset,
+            timestamp: Utc.from_utc_datetime(
+                &dead_letter
+                    .kafka_timestamp
+                    .unwrap_or(dead_letter.created_at),
+            ),
+        };
+
+        let result =
+            match Services::decode(&dead_letter.topic, &dead_letter.key, &dead_letter.payload) {
+                Ok(msg) => {
+                    events::process(
+                        msg,
+                        &source,
+                        db.clone(),
+                        delivery.clone(),
+                        producer.clone(),
+                        validation,
+                    )
+                    .await
+                },
+                Err(e) => Err(e.into()),
+            };
+
+        let attempts = dead_letter.attempts.saturating_add(1);
+        let mut active_dead_letter: dead_letters::ActiveModel = dead_letter.into();
+        active_dead_letter.attempts = Set(attempts);
+
+        match result {
+            Ok(()) => {
+                active_dead_letter.redriven_at = Set(Some(Utc::now().naive_utc()));
+                redriven += 1;
+            },
+            Err(e) => {
+                warn!(?e, "failed to re-drive dead letter");
+
+                active_dead_letter.error = Set(format!("{e:?}"));
+            },
+        }
+
+        active_dead_letter.update(db.get()).await?;
+    }
+
+    Ok(redriven)
+}
@@ -0,0 +1,324 @@
+//! Delivery backends sending the webhook messages to the endpoints of organizations.
+
+pub mod native;
+mod svix;
+
+use std::{collections::HashMap, sync::Arc};
+
+use hub_core::{
+    anyhow::Result,
+    chrono::{DateTime, Duration, Utc},
+    clap,
+};
+use poem::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+pub use self::svix::SvixDelivery;
+
+/// The number of hours messages keep being signed with the previous secret of an endpoint after a rotation when no
+/// other grace period is requested. This is the only grace period Svix supports.
+pub const DEFAULT_SECRET_ROTATION_GRACE_PERIOD: i64 = 24;
+
+/// Arguments for selecting the backend delivering webhook messages
+#[derive(Debug, clap::Args)]
+pub struct DeliveryArgs {
+    /// The backend delivering webhook messages. `svix` requires a Svix server, `native` sends them from this service
+    #[arg(long, env, value_enum, default_value_t = DeliveryBackend::Svix)]
+    pub delivery_backend: DeliveryBackend,
+
+    #[command(flatten)]
+    pub native: native::NativeArgs,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum DeliveryBackend {
+    /// Deliver messages through a Svix server
+    Svix,
+    /// Deliver messages from this service, queued in Postgres
+    Native,
+}
+
+/// An endpoint to create in an application.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EndpointIn {
+    pub url: String,
+    pub version: i32,
+    pub description: Option<String>,
+    pub disabled: Option<bool>,
+    pub rate_limit: Option<i32>,
+    pub uid: Option<String>,
+    /// The channels of the messages sent to the endpoint, every message when `None`
+    pub channels: Option<Vec<String>>,
+    /// The event types of the messages sent to the endpoint, every message when `None`
+    pub filter_types: Option<Vec<String>>,
+    /// The secret signing the messages, generated by the backend when `None`
+    pub secret: Option<String>,
+}
+
+/// The new configuration of an endpoint, replacing the current one.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EndpointUpdate {
+    pub url: String,
+    pub version: i32,
+    pub description: Option<String>,
+    /// The backend enables the endpoint when `None`
+    pub disabled: Option<bool>,
+    pub rate_limit: Option<i32>,
+    pub uid: Option<String>,
+    pub channels: Option<Vec<String>>,
+    pub filter_types: Option<Vec<String>>,
+}
+
+/// An endpoint of an application.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EndpointOut {
+    pub id: String,
+    pub url: String,
+    pub version: i32,
+    pub description: Option<String>,
+    pub disabled: Option<bool>,
+    pub rate_limit: Option<i32>,
+    pub uid: Option<String>,
+    pub channels: Option<Vec<String>>,
+    pub filter_types: Option<Vec<String>>,
+    /// When the endpoint was created, in RFC 3339 format
+    pub created_at: String,
+    /// When the endpoint was last updated, in RFC 3339 format
+    pub updated_at: String,
+}
+
+/// An event type to register, with the JSON schema of its payload keyed by API version.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EventTypeIn {
+    pub name: String,
+    pub description: String,
+    pub archived: Option<bool>,
+    pub schemas: Option<HashMap<String, Value>>,
+}
+
+/// A registered event type.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EventTypeOut {
+    pub name: String,
+    pub description: String,
+    pub archived: Option<bool>,
+    pub schemas: Option<HashMap<String, Value>>,
+    /// When the event type was registered, in RFC 3339 format
+    pub created_at: String,
+    /// When the event type was last updated, in RFC 3339 format
+    pub updated_at: String,
+}
+
+/// A message to send to the endpoints of an application. Messages are stored in the outbox as JSON before they are
+/// sent, so renaming a field requires migrating the messages still queued.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageIn {
+    pub event_type: String,
+    pub payload: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub event_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channels: Option<Vec<String>>,
+}
+
+/// A message sent to an application.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MessageOut {
+    pub id: String,
+    pub event_type: String,
+    pub payload: Value,
+    pub event_id: Option<String>,
+    pub channels: Option<Vec<String>>,
+    /// When the message was sent, in RFC 3339 format
+    pub timestamp: String,
+}
+
+/// The delivery status of a message attempt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageStatus {
+    Success,
+    Pending,
+    Fail,
+    Sending,
+}
+
+/// An attempt at delivering a message to an endpoint.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MessageAttemptOut {
+    pub id: String,
+    pub msg_id: String,
+    pub endpoint_id: String,
+    pub url: String,
+    pub status: MessageStatus,
+    pub response_status_code: i32,
+    pub response: String,
+    /// When the attempt was made, in RFC 3339 format
+    pub timestamp: String,
+    /// The message, when the backend includes it
+    pub msg: Option<MessageOut>,
+}
+
+/// Filters and pagination of the attempts listed for an endpoint.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MessageAttemptListOptions {
+    /// The cursor returned with the previous page
+    pub iterator: Option<String>,
+    pub limit: Option<i32>,
+    pub status: Option<MessageStatus>,
+    pub event_types: Option<Vec<String>>,
+}
+
+/// A page of message attempts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListResponseMessageAttemptOut {
+    pub data: Vec<MessageAttemptOut>,
+    /// Whether this is the last page
+    pub done: bool,
+    /// The cursor of the next page
+    pub iterator: Option<String>,
+}
+
+/// The delivery backend shared by the GraphQL layer, the event consumer and the outbox worker.
+pub type Delivery = Arc<dyn WebhookDelivery>;
+
+/// Manages the applications, endpoints, secrets, event types and messages of a delivery backend.
+/// Every organization has one application, and every webhook is an endpoint of its organization's application.
+#[async_trait]
+pub trait WebhookDelivery: Send + Sync {
+    /// Creates an application, returning its ID. When an application with the same `uid` already exists,
+    /// it is left untouched and its ID is returned instead.
+    ///
+    /// # Errors
+    /// This function fails if the backend rejects the application.
+    async fn create_application(&self, name: String, uid: String) -> Result<String>;
+
+    /// Deletes an application along with its endpoints. Deleting an application that no longer exists succeeds.
+    ///
+    /// # Errors
+    /// This function fails if the application cannot be deleted.
+    async fn delete_application(&self, app_id: String) -> Result<()>;
+
+    /// Creates an endpoint in an application.
+    ///
+    /// # Errors
+    /// This function fails if the backend rejects the endpoint.
+    async fn create_endpoint(&self, app_id: String, endpoint: EndpointIn) -> Result<EndpointOut>;
+
+    /// Lists every endpoint of an application.
+    ///
+    /// # Errors
+    /// This function fails if the application does not exist or the endpoints cannot be fetched.
+    async fn list_endpoints(&self, app_id: String) -> Result<Vec<EndpointOut>>;
+
+    /// Fetches an endpoint of an application.
+    ///
+    /// # Errors
+    /// This function fails if the endpoint does not exist.
+    async fn get_endpoint(&self, app_id: String, endpoint_id: String) -> Result<EndpointOut>;
+
+    /// Replaces the configuration of an endpoint.
+    ///
+    /// # Errors
+    /// This function fails if the endpoint does not exist or the backend rejects the update.
+    async fn update_endpoint(
+        &self,
+        app_id: String,
+        endpoint_id: String,
+        endpoint: EndpointUpdate,
+    ) -> Result<EndpointOut>;
+
+    /// Deletes an endpoint.
+    ///
+    /// # Errors
+    /// This function fails if the endpoint cannot be deleted.
+    async fn delete_endpoint(&self, app_id: String, endpoint_id: String) -> Result<()>;
+
+    /// Resends every message that failed to be delivered to an endpoint since `since`.
+    ///
+    /// # Errors
+    /// This function fails if the endpoint does not exist.
+    async fn recover_endpoint(
+        &self,
+        app_id: String,
+        endpoint_id: String,
+        since: DateTime<Utc>,
+    ) -> Result<()>;
+
+    /// Fetches the secret used to sign the messages sent to an endpoint.
+    ///
+    /// # Errors
+    /// This function fails if the endpoint does not exist.
+    async fn get_endpoint_secret(&self, app_id: String, endpoint_id: String) -> Result<String>;
+
+    /// Replaces the secret of an endpoint with `key`, or a generated one when `None`. Messages keep being signed
+    /// with the previous secret as well for `grace_period`. Returns when the previous secret expires.
+    ///
+    /// # Errors
+    /// This function fails if the endpoint does not exist, the key is invalid or the backend does not support the
+    /// grace period.
+    async fn rotate_endpoint_secret(
+        &self,
+        app_id: String,
+        endpoint_id: String,
+        key: Option<String>,
+        grace_period: Duration,
+    ) -> Result<DateTime<Utc>>;
+
+    /// Registers an event type, updating its description and schemas when it already exists.
+    ///
+    /// # Errors
+    /// This function fails if the backend rejects the event type.
+    async fn register_event_type(&self, event_type: EventTypeIn) -> Result<()>;
+
+    /// Lists the registered event types.
+    ///
+    /// # Errors
+    /// This function fails if the event types cannot be fetched.
+    async fn list_event_types(&self) -> Result<Vec<EventTypeOut>>;
+
+    /// Sends a message to the endpoints of an application subscribed to its channels and event type.
+    /// A message with an idempotency key that was already accepted is not sent again.
+    ///
+    /// # Errors
+    /// This function fails if the backend rejects the message.
+    async fn create_message(
+        &self,
+        app_id: String,
+        message: MessageIn,
+        idempotency_key: Option<String>,
+    ) -> Result<MessageOut>;
+
+    /// Sends a message to an endpoint again, regardless of its previous delivery status.
+    ///
+    /// # Errors
+    /// This function fails if the message or endpoint does not exist.
+    async fn resend_message(
+        &self,
+        app_id: String,
+        message_id: String,
+        endpoint_id: String,
+    ) -> Result<()>;
+
+    /// Lists the delivery attempts made to an endpoint, most recent first.
+    ///
+    /// # Errors
+    /// This function fails if the attempts cannot be fetched.
+    async fn list_endpoint_attempts(
+        &self,
+        app_id: String,
+        endpoint_id: String,
+        options: MessageAttemptListOptions,
+    ) -> Result<ListResponseMessageAttemptOut>;
+
+    /// Lists every delivery attempt made for a message across all endpoints.
+    ///
+    /// # Errors
+    /// This function fails if the attempts cannot be fetched.
+    async fn list_message_attempts(
+        &self,
+        app_id: String,
+        message_id: String,
+    ) -> Result<Vec<MessageAttemptOut>>;
+}
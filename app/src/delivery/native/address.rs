@@ -0,0 +1,140 @@
+//! Keeps the native delivery backend from sending requests to the network it runs in.
+//! Endpoint URLs are supplied by customers, so every host is resolved and only public addresses
+//! are connected to.
+
+use std::{
+    error::Error,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+};
+
+use hub_core::{
+    anyhow::{anyhow, bail, Result},
+    reqwest::{
+        dns::{Addrs, Name, Resolve, Resolving},
+        Url,
+    },
+    tokio,
+};
+
+/// Resolves the hosts of endpoint URLs to their public addresses only, failing the request when
+/// a host has none.
+pub struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(resolve_public(name))
+    }
+}
+
+async fn resolve_public(name: Name) -> Result<Addrs, Box<dyn Error + Send + Sync>> {
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((name.as_str(), 0))
+        .await?
+        .filter(|addr| is_public(addr.ip()))
+        .collect();
+
+    if addrs.is_empty() {
+        return Err(format!("{} has no public address", name.as_str()).into());
+    }
+
+    Ok(Box::new(addrs.into_iter()))
+}
+
+/// Rejects an endpoint URL whose host is an IP address that is not public.
+/// IP addresses are connected to without being resolved, so [`PublicResolver`] never sees them.
+///
+/// # Errors
+/// This function fails if the URL is invalid, or if its host is a non-public IP address.
+pub fn check_url(url: &str) -> Result<()> {
+    let url = Url::parse(url)?;
+    let host = url.host_str().ok_or_else(|| anyhow!("{url} has no host"))?;
+
+    if let Ok(ip) = host.trim_start_matches('[').trim_end_matches(']').parse() {
+        if !is_public(ip) {
+            bail!("{ip} is not a public address");
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether an address is reachable on the public internet, excluding the loopback, private,
+/// link-local, shared, documentation, multicast and unspecified ranges.
+#[must_use]
+pub fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_v4(ip),
+        IpAddr::V6(ip) => ip
+            .to_ipv4_mapped()
+            .map_or_else(|| is_public_v6(ip), is_public_v4),
+    }
+}
+
+fn is_public_v4(ip: Ipv4Addr) -> bool {
+    let [first, second, ..] = ip.octets();
+
+    // 0.0.0.0/8, 100.64.0.0/10 (carrier-grade NAT) and 240.0.0.0/4 (reserved)
+    let reserved = first == 0 || (first == 100 && (64..128).contains(&second)) || first >= 240;
+
+    !(reserved
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_documentation()
+        || ip.is_multicast()
+        || ip.is_broadcast())
+}
+
+fn is_public_v6(ip: Ipv6Addr) -> bool {
+    let first = ip.segments()[0];
+
+    // fc00::/7 (unique local), fe80::/10 (link-local) and 2001:db8::/32 (documentation)
+    let reserved = (first & 0xfe00) == 0xfc00
+        || (first & 0xffc0) == 0xfe80
+        || (first == 0x2001 && ip.segments()[1] == 0x0db8);
+
+    !(reserved || ip.is_loopback() || ip.is_unspecified() || ip.is_multicast())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn public_addresses_are_allowed() {
+        for ip in ["1.1.1.1", "93.184.216.34", "2606:4700:4700::1111"] {
+            assert!(is_public(ip.parse().unwrap()), "{ip}");
+        }
+    }
+
+    #[test]
+    fn internal_addresses_are_rejected() {
+        for ip in [
+            "127.0.0.1",
+            "10.0.0.1",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "255.255.255.255",
+            "224.0.0.1",
+            "::1",
+            "::",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+            "::ffff:169.254.169.254",
+        ] {
+            assert!(!is_public(ip.parse().unwrap()), "{ip}");
+        }
+    }
+
+    #[test]
+    fn urls_with_internal_ip_hosts_are_rejected() {
+        assert!(check_url("https://example.com/webhooks").is_ok());
+        assert!(check_url("https://1.1.1.1/webhooks").is_ok());
+        assert!(check_url("http://127.0.0.1:8080/webhooks").is_err());
+        assert!(check_url("http://[::1]/webhooks").is_err());
+        assert!(check_url("http://169.254.169.254/latest/meta-data").is_err());
+    }
+}
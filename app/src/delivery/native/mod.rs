@@ -0,0 +1,677 @@
+//! A delivery backend sending webhook messages from this service, without a Svix server.
+//! Messages are queued in Postgres for every subscribed endpoint and sent by [`run`].
+
+mod address;
+pub mod signature;
+mod worker;
+
+use std::collections::HashMap;
+
+use hub_core::{
+    anyhow::{anyhow, Result},
+    chrono::{DateTime, Duration, NaiveDateTime, TimeZone, Utc},
+    clap,
+    uuid::Uuid,
+};
+use poem::async_trait;
+use sea_orm::{
+    prelude::*,
+    sea_query::{Expr, OnConflict},
+    Condition, QueryOrder, QuerySelect, QueryTrait, Set, TransactionTrait,
+};
+use serde_json::Value;
+
+pub use self::worker::run;
+use super::{
+    EndpointIn, EndpointOut, EndpointUpdate, EventTypeIn, EventTypeOut,
+    ListResponseMessageAttemptOut, MessageAttemptListOptions, MessageAttemptOut, MessageIn,
+    MessageOut, MessageStatus, WebhookDelivery,
+};
+use crate::{
+    db::Connection,
+    entities::{
+        native_applications, native_endpoints, native_event_types, native_message_attempts,
+        native_message_destinations, native_messages,
+    },
+};
+
+/// The number of message attempts returned when no limit is requested.
+const DEFAULT_ATTEMPTS_LIMIT: i32 = 50;
+
+/// Arguments for sending webhook messages with the native delivery backend
+#[derive(Debug, Clone, clap::Args)]
+pub struct NativeArgs {
+    /// Seconds to wait before polling the delivery queue again when no message is due
+    #[arg(long, env, default_value_t = 1)]
+    pub native_poll_interval: u64,
+    /// Maximum number of messages sent per poll
+    #[arg(long, env, default_value_t = 50)]
+    pub native_batch_size: u64,
+    /// Seconds to wait for an endpoint to respond before the attempt fails
+    #[arg(long, env, default_value_t = 15)]
+    pub native_request_timeout: u64,
+    /// Number of attempts made to deliver a message to an endpoint before it is marked as failed
+    #[arg(long, env, default_value_t = 8)]
+    pub native_max_attempts: i32,
+    /// Seconds to wait before the first retry of a failed message, doubled on every attempt
+    #[arg(long, env, default_value_t = 5)]
+    pub native_retry_base_delay: u64,
+    /// Maximum seconds to wait between two attempts of a failed message
+    #[arg(long, env, default_value_t = 36000)]
+    pub native_retry_max_delay: u64,
+    /// Number of consecutive failed attempts after which an endpoint is disabled
+    #[arg(long, env, default_value_t = 50)]
+    pub native_disable_after_failures: i32,
+    /// Allow sending to endpoints on loopback, private and link-local addresses, for local development only
+    #[arg(long, env)]
+    pub native_allow_private_addresses: bool,
+}
+
+/// Delivers webhook messages from this service, using Postgres as the message queue.
+#[derive(Clone)]
+pub struct NativeDelivery {
+    db: Connection,
+}
+
+impl NativeDelivery {
+    #[must_use]
+    pub fn new(db: Connection) -> Self {
+        Self { db }
+    }
+
+    async fn find_endpoint(
+        &self,
+        app_id: &str,
+        endpoint_id: &str,
+    ) -> Result<native_endpoints::Model> {
+        native_endpoints::Entity::find_by_id(endpoint_id.to_string())
+            .filter(native_endpoints::Column::ApplicationId.eq(app_id))
+            .one(self.db.get())
+            .await?
+            .ok_or_else(|| anyhow!("endpoint {endpoint_id} not found"))
+    }
+
+    async fn find_message(&self, app_id: &str, message_id: &str) -> Result<native_messages::Model> {
+        native_messages::Entity::find_by_id(message_id.to_string())
+            .filter(native_messages::Column::ApplicationId.eq(app_id))
+            .one(self.db.get())
+            .await?
+            .ok_or_else(|| anyhow!("message {message_id} not found"))
+    }
+}
+
+#[async_trait]
+impl WebhookDelivery for NativeDelivery {
+    async fn create_application(&self, name: String, uid: String) -> Result<String> {
+        if let Some(application) = native_applications::Entity::find()
+            .filter(native_applications::Column::Uid.eq(uid.as_str()))
+            .one(self.db.get())
+            .await?
+        {
+            return Ok(application.id);
+        }
+
+        let application = native_applications::ActiveModel {
+            id: Set(new_id("app")),
+            name: Set(name),
+            uid: Set(uid),
+            ..Default::default()
+        };
+
+        let application = application.insert(self.db.get()).await?;
+
+        Ok(application.id)
+    }
+
+    async fn delete_application(&self, app_id: String) -> Result<()> {
+        // endpoints, messages and attempts are removed by the cascading foreign keys
+        native_applications::Entity::delete_by_id(app_id)
+            .exec(self.db.get())
+            .await?;
+
+        Ok(())
+    }
+
+    async fn create_endpoint(&self, app_id: String, endpoint: EndpointIn) -> Result<EndpointOut> {
+        let EndpointIn {
+            channels,
+            filter_types,
+            version,
+            description,
+            disabled,
+            rate_limit,
+            secret,
+            url,
+            uid,
+        } = endpoint;
+
+        native_applications::Entity::find_by_id(app_id.clone())
+            .one(self.db.get())
+            .await?
+            .ok_or_else(|| anyhow!("application {app_id} not found"))?;
+
+        let secret = match secret {
+            Some(secret) => {
+                signature::validate_secret(&secret)?;
+
+                secret
+            },
+            None => signature::generate_secret(),
+        };
+
+        let endpoint = native_endpoints::ActiveModel {
+            id: Set(new_id("ep")),
+            application_id: Set(app_id),
+            url: Set(url),
+            description: Set(description),
+            version: Set(version),
+            disabled: Set(disabled.unwrap_or_default()),
+            rate_limit: Set(rate_limit),
+            uid: Set(uid),
+            channels: Set(channels.map(Into::into)),
+            filter_types: Set(filter_types.map(Into::into)),
+            secret: Set(secret),
+            ..Default::default()
+        };
+
+        let endpoint = endpoint.insert(self.db.get()).await?;
+
+        Ok(endpoint_out(endpoint))
+    }
+
+    async fn list_endpoints(&self, app_id: String) -> Result<Vec<EndpointOut>> {
+        native_applications::Entity::find_by_id(app_id.clone())
+            .one(self.db.get())
+            .await?
+            .ok_or_else(|| anyhow!("application {app_id} not found"))?;
+
+        let endpoints = native_endpoints::Entity::find()
+            .filter(native_endpoints::Column::ApplicationId.eq(app_id))
+            .all(self.db.get())
+            .await?;
+
+        Ok(endpoints.into_iter().map(endpoint_out).collect())
+    }
+
+    async fn get_endpoint(&self, app_id: String, endpoint_id: String) -> Result<EndpointOut> {
+        let endpoint = self.find_endpoint(&app_id, &endpoint_id).await?;
+
+        Ok(endpoint_out(endpoint))
+    }
+
+    async fn update_endpoint(
+        &self,
+        app_id: String,
+        endpoint_id: String,
+        endpoint: EndpointUpdate,
+    ) -> Result<EndpointOut> {
+        let EndpointUpdate {
+            channels,
+            filter_types,
+            version,
+            description,
+            disabled,
+            rate_limit,
+            url,
+            uid,
+        } = endpoint;
+
+        let current = self.find_endpoint(&app_id, &endpoint_id).await?;
+        let was_disabled = current.disabled;
+
+        let mut endpoint: native_endpoints::ActiveModel = current.into();
+        endpoint.channels = Set(channels.map(Into::into));
+        endpoint.filter_types = Set(filter_types.map(Into::into));
+        endpoint.version = Set(version);
+        endpoint.description = Set(description);
+        endpoint.rate_limit = Set(rate_limit);
+        endpoint.url = Set(url);
+        endpoint.uid = Set(uid);
+        endpoint.updated_at = Set(Utc::now().naive_utc());
+
+        if let Some(disabled) = disabled {
+            endpoint.disabled = Set(disabled);
+
+            // an endpoint enabled again starts over with a clean record
+            if was_disabled && !disabled {
+                endpoint.consecutive_failures = Set(0);
+            }
+        }
+
+        let endpoint = endpoint.update(self.db.get()).await?;
+
+        Ok(endpoint_out(endpoint))
+    }
+
+    async fn delete_endpoint(&self, app_id: String, endpoint_id: String) -> Result<()> {
+        native_endpoints::Entity::delete_many()
+            .filter(native_endpoints::Column::Id.eq(endpoint_id))
+            .filter(native_endpoints::Column::ApplicationId.eq(app_id))
+            .exec(self.db.get())
+            .await?;
+
+        Ok(())
+    }
+
+    async fn recover_endpoint(
+        &self,
+        app_id: String,
+        endpoint_id: String,
+        since: DateTime<Utc>,
+    ) -> Result<()> {
+        let endpoint = self.find_endpoint(&app_id, &endpoint_id).await?;
+
+        native_message_destinations::Entity::update_many()
+            .col_expr(
+                native_message_destinations::Column::Status,
+                Expr::value(status_name(MessageStatus::Pending)),
+            )
+            .col_expr(
+                native_message_destinations::Column::Attempts,
+                Expr::value(0),
+            )
+            .col_expr(
+                native_message_destinations::Column::NextAttemptAt,
+                Expr::value(Utc::now().naive_utc()),
+            )
+            .filter(native_message_destinations::Column::EndpointId.eq(endpoint.id))
+            .filter(
+                native_message_destinations::Column::Status.eq(status_name(MessageStatus::Fail)),
+            )
+            .filter(native_message_destinations::Column::CreatedAt.gte(since.naive_utc()))
+            .exec(self.db.get())
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_endpoint_secret(&self, app_id: String, endpoint_id: String) -> Result<String> {
+        let endpoint = self.find_endpoint(&app_id, &endpoint_id).await?;
+
+        Ok(endpoint.secret)
+    }
+
+    async fn rotate_endpoint_secret(
+        &self,
+        app_id: String,
+        endpoint_id: String,
+        key: Option<String>,
+        grace_period: Duration,
+    ) -> Result<DateTime<Utc>> {
+        let current = self.find_endpoint(&app_id, &endpoint_id).await?;
+
+        let secret = match key {
+            Some(key) => {
+                signature::validate_secret(&key)?;
+
+                key
+            },
+            None => signature::generate_secret(),
+        };
+
+        let previous_secret_expires_at = Utc::now() + grace_period;
+        let previous_secret = current.secret.clone();
+
+        let mut endpoint: native_endpoints::ActiveModel = current.into();
+        endpoint.secret = Set(secret);
+        endpoint.previous_secret = Set(Some(previous_secret));
+        endpoint.previous_secret_expires_at = Set(Some(previous_secret_expires_at.naive_utc()));
+        endpoint.updated_at = Set(Utc::now().naive_utc());
+
+        endpoint.update(self.db.get()).await?;
+
+        Ok(previous_secret_expires_at)
+    }
+
+    async fn register_event_type(&self, event_type: EventTypeIn) -> Result<()> {
+        let EventTypeIn {
+            description,
+            schemas,
+            archived,
+            name,
+        } = event_type;
+
+        let event_type = native_event_types::ActiveModel {
+            name: Set(name),
+            description: Set(description),
+            schemas: Set(schemas.map(serde_json::to_value).transpose()?),
+            archived: Set(archived.unwrap_or_default()),
+            ..Default::default()
+        };
+
+        native_event_types::Entity::insert(event_type)
+            .on_conflict(
+                OnConflict::column(native_event_types::Column::Name)
+                    .update_columns([
+                        native_event_types::Column::Description,
+                        native_event_types::Column::Schemas,
+                        native_event_types::Column::Archived,
+                        native_event_types::Column::UpdatedAt,
+                    ])
+                    .to_owned(),
+            )
+            .exec_without_returning(self.db.get())
+            .await?;
+
+        Ok(())
+    }
+
+    async fn list_event_types(&self) -> Result<Vec<EventTypeOut>> {
+        let event_types = native_event_types::Entity::find()
+            .order_by_asc(native_event_types::Column::Name)
+            .all(self.db.get())
+            .await?;
+
+        event_types
+            .into_iter()
+            .map(|event_type| {
+                Ok(EventTypeOut {
+                    archived: Some(event_type.archived),
+                    created_at: rfc3339(event_type.created_at),
+                    description: event_type.description,
+                    name: event_type.name,
+                    schemas: event_type.schemas.map(serde_json::from_value).transpose()?,
+                    updated_at: rfc3339(event_type.updated_at),
+                })
+            })
+            .collect()
+    }
+
+    async fn create_message(
+        &self,
+        app_id: String,
+        message: MessageIn,
+        idempotency_key: Option<String>,
+    ) -> Result<MessageOut> {
+        let MessageIn {
+            channels,
+            event_id,
+            event_type,
+            payload,
+        } = message;
+
+        let tx = self.db.get().begin().await?;
+
+        if let Some(idempotency_key) = &idempotency_key {
+            let existing = native_messages::Entity::find()
+                .filter(native_messages::Column::ApplicationId.eq(app_id.clone()))
+                .filter(native_messages::Column::IdempotencyKey.eq(idempotency_key.clone()))
+                .one(&tx)
+                .await?;
+
+            if let Some(existing) = existing {
+                return Ok(message_out(existing));
+            }
+        }
+
+        let endpoints = native_endpoints::Entity::find()
+            .filter(native_endpoints::Column::ApplicationId.eq(app_id.clone()))
+            .filter(native_endpoints::Column::Disabled.eq(false))
+            .all(&tx)
+            .await?;
+
+        let message = native_messages::ActiveModel {
+            id: Set(new_id("msg")),
+            application_id: Set(app_id),
+            event_type: Set(event_type),
+            event_id: Set(event_id),
+            channels: Set(channels.map(Into::into)),
+            payload: Set(payload),
+            idempotency_key: Set(idempotency_key),
+            ..Default::default()
+        };
+
+        let message = message.insert(&tx).await?;
+
+        let destinations: Vec<_> = endpoints
+            .into_iter()
+            .filter(|endpoint| subscribes_to(endpoint, &message))
+            .map(|endpoint| native_message_destinations::ActiveModel {
+                message_id: Set(message.id.clone()),
+                endpoint_id: Set(endpoint.id),
+                status: Set(status_name(MessageStatus::Pending).to_string()),
+                ..Default::default()
+            })
+            .collect();
+
+        if !destinations.is_empty() {
+            native_message_destinations::Entity::insert_many(destinations)
+                .exec_without_returning(&tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(message_out(message))
+    }
+
+    async fn resend_message(
+        &self,
+        app_id: String,
+        message_id: String,
+        endpoint_id: String,
+    ) -> Result<()> {
+        let message = self.find_message(&app_id, &message_id).await?;
+        let endpoint = self.find_endpoint(&app_id, &endpoint_id).await?;
+
+        let destination = native_message_destinations::ActiveModel {
+            message_id: Set(message.id),
+            endpoint_id: Set(endpoint.id),
+            status: Set(status_name(MessageStatus::Pending).to_string()),
+            ..Default::default()
+        };
+
+        destination.insert(self.db.get()).await?;
+
+        Ok(())
+    }
+
+    async fn list_endpoint_attempts(
+        &self,
+        app_id: String,
+        endpoint_id: String,
+        options: MessageAttemptListOptions,
+    ) -> Result<ListResponseMessageAttemptOut> {
+        let MessageAttemptListOptions {
+            iterator,
+            limit,
+            status,
+            event_types,
+        } = options;
+
+        let endpoint = self.find_endpoint(&app_id, &endpoint_id).await?;
+        let limit = u64::try_from(limit.unwrap_or(DEFAULT_ATTEMPTS_LIMIT).max(1))?;
+
+        let mut query = native_message_attempts::Entity::find()
+            .filter(native_message_attempts::Column::EndpointId.eq(endpoint.id.clone()));
+
+        if let Some(status) = status {
+            query = query.filter(native_message_attempts::Column::Status.eq(status_name(status)));
+        }
+
+        if let Some(event_types) = event_types {
+            let message_ids = native_messages::Entity::find()
+                .select_only()
+                .column(native_messages::Column::Id)
+                .filter(native_messages::Column::ApplicationId.eq(app_id.clone()))
+                .filter(native_messages::Column::EventType.is_in(event_types))
+                .into_query();
+
+            query =
+                query.filter(native_message_attempts::Column::MessageId.in_subquery(message_ids));
+        }
+
+        if let Some(iterator) = iterator {
+            let cursor = native_message_attempts::Entity::find_by_id(iterator.clone())
+                .filter(native_message_attempts::Column::EndpointId.eq(endpoint.id))
+                .one(self.db.get())
+                .await?
+                .ok_or_else(|| anyhow!("invalid iterator {iterator}"))?;
+
+            // attempts sharing a timestamp are told apart by their ID, so none is skipped
+            query = query.filter(
+                Condition::any()
+                    .add(native_message_attempts::Column::CreatedAt.lt(cursor.created_at))
+                    .add(
+                        native_message_attempts::Column::CreatedAt
+                            .eq(cursor.created_at)
+                            .and(native_message_attempts::Column::Id.lt(cursor.id)),
+                    ),
+            );
+        }
+
+        let mut attempts = query
+            .order_by_desc(native_message_attempts::Column::CreatedAt)
+            .order_by_desc(native_message_attempts::Column::Id)
+            .limit(limit + 1)
+            .all(self.db.get())
+            .await?;
+
+        let done = attempts.len() as u64 <= limit;
+        attempts.truncate(usize::try_from(limit)?);
+
+        let messages: HashMap<String, MessageOut> = native_messages::Entity::find()
+            .filter(
+                native_messages::Column::Id
+                    .is_in(attempts.iter().map(|attempt| attempt.message_id.clone())),
+            )
+            .all(self.db.get())
+            .await?
+            .into_iter()
+            .map(|message| (message.id.clone(), message_out(message)))
+            .collect();
+
+        let iterator = attempts.last().map(|attempt| attempt.id.clone());
+
+        let data = attempts
+            .into_iter()
+            .map(|attempt| {
+                let message = messages.get(&attempt.message_id).cloned();
+
+                attempt_out(attempt, message)
+            })
+            .collect();
+
+        Ok(ListResponseMessageAttemptOut {
+            data,
+            done,
+            iterator,
+        })
+    }
+
+    async fn list_message_attempts(
+        &self,
+        app_id: String,
+        message_id: String,
+    ) -> Result<Vec<MessageAttemptOut>> {
+        let message = self.find_message(&app_id, &message_id).await?;
+
+        let attempts = native_message_attempts::Entity::find()
+            .filter(native_message_attempts::Column::MessageId.eq(message.id.clone()))
+            .order_by_desc(native_message_attempts::Column::CreatedAt)
+            .order_by_desc(native_message_attempts::Column::Id)
+            .all(self.db.get())
+            .await?;
+
+        let message = message_out(message);
+
+        Ok(attempts
+            .into_iter()
+            .map(|attempt| attempt_out(attempt, Some(message.clone())))
+            .collect())
+    }
+}
+
+/// Whether a message is sent to an endpoint, following the filtering rules of Svix.
+/// An endpoint without channels or filter types receives every message.
+fn subscribes_to(endpoint: &native_endpoints::Model, message: &native_messages::Model) -> bool {
+    let filter_types = strings(endpoint.filter_types.clone());
+    let endpoint_channels = strings(endpoint.channels.clone());
+    let message_channels = strings(message.channels.clone()).unwrap_or_default();
+
+    let subscribes_to_event_type = filter_types.map_or(true, |filter_types| {
+        filter_types.contains(&message.event_type)
+    });
+
+    let subscribes_to_channels = endpoint_channels.map_or(true, |endpoint_channels| {
+        message_channels
+            .iter()
+            .any(|channel| endpoint_channels.contains(channel))
+    });
+
+    subscribes_to_event_type && subscribes_to_channels
+}
+
+fn new_id(prefix: &str) -> String {
+    format!("{prefix}_{}", Uuid::new_v4().simple())
+}
+
+fn strings(value: Option<Value>) -> Option<Vec<String>> {
+    value.and_then(|value| serde_json::from_value(value).ok())
+}
+
+fn rfc3339(datetime: NaiveDateTime) -> String {
+    Utc.from_utc_datetime(&datetime).to_rfc3339()
+}
+
+fn status_name(status: MessageStatus) -> &'static str {
+    match status {
+        MessageStatus::Success => "success",
+        MessageStatus::Pending => "pending",
+        MessageStatus::Fail => "fail",
+        MessageStatus::Sending => "sending",
+    }
+}
+
+fn parse_status(status: &str) -> MessageStatus {
+    match status {
+        "success" => MessageStatus::Success,
+        "fail" => MessageStatus::Fail,
+        "sending" => MessageStatus::Sending,
+        _ => MessageStatus::Pending,
+    }
+}
+
+fn endpoint_out(endpoint: native_endpoints::Model) -> EndpointOut {
+    EndpointOut {
+        channels: strings(endpoint.channels),
+        created_at: rfc3339(endpoint.created_at),
+        description: endpoint.description,
+        disabled: Some(endpoint.disabled),
+        filter_types: strings(endpoint.filter_types),
+        id: endpoint.id,
+        rate_limit: endpoint.rate_limit,
+        uid: endpoint.uid,
+        updated_at: rfc3339(endpoint.updated_at),
+        url: endpoint.url,
+        version: endpoint.version,
+    }
+}
+
+fn message_out(message: native_messages::Model) -> MessageOut {
+    MessageOut {
+        channels: strings(message.channels),
+        event_id: message.event_id,
+        event_type: message.event_type,
+        id: message.id,
+        payload: message.payload,
+        timestamp: rfc3339(message.created_at),
+    }
+}
+
+fn attempt_out(
+    attempt: native_message_attempts::Model,
+    message: Option<MessageOut>,
+) -> MessageAttemptOut {
+    MessageAttemptOut {
+        endpoint_id: attempt.endpoint_id,
+        id: attempt.id,
+        msg: message,
+        msg_id: attempt.message_id,
+        response: attempt.response,
+        response_status_code: attempt.response_status_code,
+        status: parse_status(&attempt.status),
+        timestamp: rfc3339(attempt.created_at),
+        url: attempt.url,
+    }
+}
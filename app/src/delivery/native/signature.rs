@@ -0,0 +1,141 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use hmac::{Hmac, Mac};
+use hub_core::anyhow::{anyhow, Result};
+use rand::Rng;
+use sha2::Sha256;
+
+/// The prefix of endpoint secrets, as used by Svix and the Standard Webhooks libraries.
+const SECRET_PREFIX: &str = "whsec_";
+/// The number of random bytes in a generated secret.
+const SECRET_LENGTH: usize = 24;
+/// The version prefix of the signatures, the only scheme of the Standard Webhooks specification.
+const SIGNATURE_VERSION: &str = "v1";
+
+/// Generates a new endpoint secret.
+#[must_use]
+pub fn generate_secret() -> String {
+    let key: [u8; SECRET_LENGTH] = rand::thread_rng().gen();
+
+    format!("{SECRET_PREFIX}{}", STANDARD.encode(key))
+}
+
+/// Checks that a secret provided by a user can be used to sign messages.
+///
+/// # Errors
+/// This function fails if the secret is not prefixed with `whsec_` or is not valid base64.
+pub fn validate_secret(secret: &str) -> Result<()> {
+    decode_secret(secret).map(|_| ())
+}
+
+/// Signs a message following the Standard Webhooks specification, returning the value of one
+/// `webhook-signature` entry.
+///
+/// # Errors
+/// This function fails if the secret is invalid.
+pub fn sign(secret: &str, message_id: &str, timestamp: i64, payload: &str) -> Result<String> {
+    let mac = mac(secret, message_id, timestamp, payload)?;
+
+    Ok(format!(
+        "{SIGNATURE_VERSION},{}",
+        STANDARD.encode(mac.finalize().into_bytes())
+    ))
+}
+
+/// Checks the value of a `webhook-signature` header against a message, accepting it when any of its
+/// space-separated signatures matches.
+///
+/// # Errors
+/// This function fails if the secret is invalid or no signature matches.
+pub fn verify(
+    secret: &str,
+    message_id: &str,
+    timestamp: i64,
+    payload: &str,
+    signatures: &str,
+) -> Result<()> {
+    let mac = mac(secret, message_id, timestamp, payload)?;
+
+    let matched = signatures
+        .split(' ')
+        .filter_map(|signature| signature.split_once(','))
+        .filter(|(version, _)| *version == SIGNATURE_VERSION)
+        .filter_map(|(_, signature)| STANDARD.decode(signature).ok())
+        .any(|signature| mac.clone().verify_slice(&signature).is_ok());
+
+    if matched {
+        Ok(())
+    } else {
+        Err(anyhow!("no signature matches the message"))
+    }
+}
+
+fn mac(secret: &str, message_id: &str, timestamp: i64, payload: &str) -> Result<Hmac<Sha256>> {
+    let key = decode_secret(secret)?;
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(&key)?;
+    mac.update(format!("{message_id}.{timestamp}.{payload}").as_bytes());
+
+    Ok(mac)
+}
+
+fn decode_secret(secret: &str) -> Result<Vec<u8>> {
+    let key = secret
+        .strip_prefix(SECRET_PREFIX)
+        .ok_or_else(|| anyhow!("secret must be prefixed with {SECRET_PREFIX}"))?;
+
+    Ok(STANDARD.decode(key)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the reference vector of the Svix and Standard Webhooks libraries
+    const SECRET: &str = "whsec_MfKQ9r8GKYqrTwjUPD8ILPZIo2LaLaSw";
+    const MESSAGE_ID: &str = "msg_p5jXN8AQM9LWM0D4loKWxJek";
+    const TIMESTAMP: i64 = 1_614_265_330;
+    const PAYLOAD: &str = r#"{"test": 2432232314}"#;
+    const SIGNATURE: &str = "v1,g0hM9SsE+OTPJTGt/tmIKtSyZlE3uFJELVlNIOLJ1OE=";
+
+    #[test]
+    fn signature_matches_the_reference_vector() {
+        assert_eq!(
+            sign(SECRET, MESSAGE_ID, TIMESTAMP, PAYLOAD).unwrap(),
+            SIGNATURE
+        );
+    }
+
+    #[test]
+    fn any_matching_signature_is_accepted() {
+        let signatures = format!("v1,Ceo5qEr07ixe2NLpvHk3FH9bwy/WavXrAFQ/9tdO6mc= {SIGNATURE}");
+
+        assert!(verify(SECRET, MESSAGE_ID, TIMESTAMP, PAYLOAD, SIGNATURE).is_ok());
+        assert!(verify(SECRET, MESSAGE_ID, TIMESTAMP, PAYLOAD, &signatures).is_ok());
+    }
+
+    #[test]
+    fn tampered_messages_are_rejected() {
+        let v2_signature = SIGNATURE.replacen("v1", "v2", 1);
+
+        assert!(verify(SECRET, MESSAGE_ID, TIMESTAMP, r#"{"test": 0}"#, SIGNATURE).is_err());
+        assert!(verify(SECRET, MESSAGE_ID, TIMESTAMP + 1, PAYLOAD, SIGNATURE).is_err());
+        assert!(verify(SECRET, "msg_other", TIMESTAMP, PAYLOAD, SIGNATURE).is_err());
+        assert!(verify(SECRET, MESSAGE_ID, TIMESTAMP, PAYLOAD, &v2_signature).is_err());
+    }
+
+    #[test]
+    fn generated_secrets_are_valid() {
+        let secret = generate_secret();
+        let signature = sign(&secret, MESSAGE_ID, TIMESTAMP, PAYLOAD).unwrap();
+
+        assert!(validate_secret(&secret).is_ok());
+        assert!(verify(&secret, MESSAGE_ID, TIMESTAMP, PAYLOAD, &signature).is_ok());
+        assert!(verify(SECRET, MESSAGE_ID, TIMESTAMP, PAYLOAD, &signature).is_err());
+    }
+
+    #[test]
+    fn secrets_must_be_prefixed_base64() {
+        assert!(validate_secret("MfKQ9r8GKYqrTwjUPD8ILPZIo2LaLaSw").is_err());
+        assert!(validate_secret("whsec_not base64!").is_err());
+    }
+}
@@ -0,0 +1,523 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use hub_core::{
+    anyhow::Result,
+    chrono::{self, DateTime, NaiveDateTime, Utc},
+    prelude::*,
+    reqwest::{redirect, Client},
+    tokio,
+};
+use sea_orm::{prelude::*, sea_query::Expr, DbBackend, Set, Statement, TransactionTrait};
+
+use super::{address, new_id, signature, status_name, MessageStatus, NativeArgs};
+use crate::{
+    db::Connection,
+    endpoint_health::{self, HealthChange},
+    entities::{
+        native_endpoints, native_message_attempts, native_message_destinations, native_messages,
+    },
+    outbox::Backoff,
+    EventsProducer,
+};
+
+/// The maximum number of characters of a response body logged on a message attempt.
+/// Kept short so an endpoint cannot be used to read back the responses of arbitrary servers.
+const MAX_RESPONSE_LENGTH: usize = 256;
+
+/// Sends the queued messages to their endpoints until the process exits.
+/// Failed messages are retried with an exponential backoff, and endpoints failing repeatedly are disabled.
+/// The health changes of the endpoints are recorded on their webhooks and published as webhook events.
+pub async fn run(db: Connection, args: NativeArgs, producer: EventsProducer) {
+    let NativeArgs {
+        native_poll_interval,
+        native_batch_size,
+        native_request_timeout,
+        native_max_attempts,
+        native_retry_base_delay,
+        native_retry_max_delay,
+        native_disable_after_failures,
+        native_allow_private_addresses,
+    } = args;
+
+    // redirects are not followed, as they could lead to an address the endpoint URL was checked against
+    let mut client = Client::builder()
+        .timeout(Duration::from_secs(native_request_timeout))
+        .redirect(redirect::Policy::none());
+
+    if !native_allow_private_addresses {
+        client = client.dns_resolver(Arc::new(address::PublicResolver));
+    }
+
+    let client = match client.build() {
+        Ok(client) => client,
+        Err(e) => {
+            error!("failed to build the native delivery http client: {:?}", e);
+
+            return;
+        },
+    };
+
+    let sender = Sender {
+        db,
+        client,
+        producer,
+        request_timeout: Duration::from_secs(native_request_timeout),
+        retry: RetryPolicy {
+            max_attempts: native_max_attempts,
+            backoff: Backoff {
+                base: Duration::from_secs(native_retry_base_delay),
+                max: Duration::from_secs(native_retry_max_delay),
+            },
+        },
+        disable_after_failures: native_disable_after_failures,
+        allow_private_addresses: native_allow_private_addresses,
+    };
+
+    loop {
+        match sender.drain(native_batch_size).await {
+            Ok(count) if count > 0 => continue,
+            Ok(_) => (),
+            Err(e) => error!("failed to drain the native delivery queue: {:?}", e),
+        }
+
+        tokio::time::sleep(Duration::from_secs(native_poll_interval)).await;
+    }
+}
+
+struct Sender {
+    db: Connection,
+    client: Client,
+    producer: EventsProducer,
+    request_timeout: Duration,
+    retry: RetryPolicy,
+    disable_after_failures: i32,
+    allow_private_addresses: bool,
+}
+
+/// How often and when a message is sent again to an endpoint after a failed attempt.
+struct RetryPolicy {
+    max_attempts: i32,
+    backoff: Backoff,
+}
+
+/// What becomes of a destination after an attempt to send its message.
+#[derive(Debug, PartialEq, Eq)]
+enum Schedule {
+    Delivered,
+    Failed,
+    RetryAt(DateTime<Utc>),
+}
+
+impl RetryPolicy {
+    /// Schedules a destination after its given attempt succeeded or failed.
+    fn schedule(&self, attempts: i32, success: bool, now: DateTime<Utc>) -> Schedule {
+        if success {
+            Schedule::Delivered
+        } else if attempts >= self.max_attempts {
+            Schedule::Failed
+        } else {
+            Schedule::RetryAt(now + self.backoff.delay(attempts))
+        }
+    }
+}
+
+/// The outcome of sending a message to an endpoint.
+struct Response {
+    status_code: i32,
+    body: String,
+    success: bool,
+}
+
+/// Leases the next due destinations, skipping those claimed by another worker.
+const CLAIM_SQL: &str = r#"
+UPDATE native_message_destinations SET locked_until = $1
+WHERE id IN (
+    SELECT id FROM native_message_destinations
+    WHERE status = $2
+        AND next_attempt_at <= $3
+        AND (locked_until IS NULL OR locked_until <= $3)
+    ORDER BY next_attempt_at
+    LIMIT $4
+    FOR UPDATE SKIP LOCKED
+)
+RETURNING *
+"#;
+
+impl Sender {
+    /// Sends a batch of due messages, returning how many were claimed.
+    /// The destinations are leased in a short transaction, so no lock is held while sending, and
+    /// the result of each attempt is recorded as soon as it is known.
+    async fn drain(&self, batch_size: u64) -> Result<usize> {
+        let mut destinations = self.claim(batch_size).await?;
+        destinations.sort_by_key(|destination| destination.next_attempt_at);
+
+        let count = destinations.len();
+
+        let messages: HashMap<String, native_messages::Model> = native_messages::Entity::find()
+            .filter(
+                native_messages::Column::Id.is_in(
+                    destinations
+                        .iter()
+                        .map(|destination| destination.message_id.clone()),
+                ),
+            )
+            .all(self.db.get())
+            .await?
+            .into_iter()
+            .map(|message| (message.id.clone(), message))
+            .collect();
+
+        let mut endpoints: HashMap<String, native_endpoints::Model> =
+            native_endpoints::Entity::find()
+                .filter(
+                    native_endpoints::Column::Id.is_in(
+                        destinations
+                            .iter()
+                            .map(|destination| destination.endpoint_id.clone()),
+                    ),
+                )
+                .all(self.db.get())
+                .await?
+                .into_iter()
+                .map(|endpoint| (endpoint.id.clone(), endpoint))
+                .collect();
+
+        for destination in destinations {
+            let (Some(message), Some(endpoint)) = (
+                messages.get(&destination.message_id),
+                endpoints.get_mut(&destination.endpoint_id),
+            ) else {
+                continue;
+            };
+
+            if endpoint.disabled {
+                let mut active_destination: native_message_destinations::ActiveModel =
+                    destination.into();
+                active_destination.status = Set(status_name(MessageStatus::Fail).to_string());
+                active_destination.locked_until = Set(None);
+                active_destination.update(self.db.get()).await?;
+
+                continue;
+            }
+
+            let response = self.send(message, endpoint).await;
+
+            let status = if response.success {
+                MessageStatus::Success
+            } else {
+                MessageStatus::Fail
+            };
+
+            let attempt = native_message_attempts::ActiveModel {
+                id: Set(new_id("atmpt")),
+                message_id: Set(message.id.clone()),
+                endpoint_id: Set(endpoint.id.clone()),
+                url: Set(endpoint.url.clone()),
+                status: Set(status_name(status).to_string()),
+                response_status_code: Set(response.status_code),
+                response: Set(response.body.chars().take(MAX_RESPONSE_LENGTH).collect()),
+                ..Default::default()
+            };
+
+            attempt.insert(self.db.get()).await?;
+
+            let destination = self
+                .record_destination(destination, response.success)
+                .await?;
+
+            if destination.status == status_name(MessageStatus::Fail) {
+                self.record_health(&endpoint.id, HealthChange::Failing {
+                    message_id: message.id.clone(),
+                    attempts: destination.attempts,
+                    response_status_code: response.status_code,
+                })
+                .await;
+            } else if response.success && destination.attempts > 1 {
+                self.record_health(&endpoint.id, HealthChange::Recovered)
+                    .await;
+            }
+
+            if self.record_endpoint(&endpoint.id, response.success).await? {
+                endpoint.disabled = true;
+
+                self.record_health(&endpoint.id, HealthChange::Disabled)
+                    .await;
+            }
+        }
+
+        Ok(count)
+    }
+
+    /// Leases a batch of due destinations until every request of the batch could have timed out.
+    async fn claim(&self, batch_size: u64) -> Result<Vec<native_message_destinations::Model>> {
+        let now = Utc::now();
+        let lease = self.request_timeout.saturating_mul(
+            u32::try_from(batch_size)
+                .unwrap_or(u32::MAX)
+                .saturating_add(1),
+        );
+        let locked_until = now + chrono::Duration::from_std(lease)?;
+
+        let tx = self.db.get().begin().await?;
+
+        let destinations = native_message_destinations::Entity::find()
+            .from_raw_sql(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                CLAIM_SQL,
+                [
+                    locked_until.naive_utc().into(),
+                    status_name(MessageStatus::Pending).into(),
+                    now.naive_utc().into(),
+                    i64::try_from(batch_size).unwrap_or(i64::MAX).into(),
+                ],
+            ))
+            .all(&tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(destinations)
+    }
+
+    /// Records a health change on the webhook of an endpoint, publishing it as a webhook event.
+    async fn record_health(&self, endpoint_id: &str, change: HealthChange) {
+        if let Err(e) = endpoint_health::record(&self.db, &self.producer, endpoint_id, change).await
+        {
+            warn!(
+                endpoint_id,
+                "failed to record endpoint health change: {:?}", e
+            );
+        }
+    }
+
+    /// Posts the payload of a message to an endpoint, signed with its secrets as described by the
+    /// Standard Webhooks specification.
+    async fn send(
+        &self,
+        message: &native_messages::Model,
+        endpoint: &native_endpoints::Model,
+    ) -> Response {
+        match self.try_send(message, endpoint).await {
+            Ok(response) => response,
+            Err(e) => Response {
+                status_code: 0,
+                body: format!("{e:#}"),
+                success: false,
+            },
+        }
+    }
+
+    async fn try_send(
+        &self,
+        message: &native_messages::Model,
+        endpoint: &native_endpoints::Model,
+    ) -> Result<Response> {
+        if !self.allow_private_addresses {
+            address::check_url(&endpoint.url)?;
+        }
+
+        let payload = serde_json::to_string(&message.payload)?;
+        let timestamp = Utc::now().timestamp();
+
+        let signatures = secrets(endpoint, Utc::now().naive_utc())
+            .map(|secret| signature::sign(secret, &message.id, timestamp, &payload))
+            .collect::<Result<Vec<_>>>()?
+            .join(" ");
+
+        let response = self
+            .client
+            .post(&endpoint.url)
+            .header("content-type", "application/json")
+            .header("webhook-id", &message.id)
+            .header("webhook-timestamp", timestamp.to_string())
+            .header("webhook-signature", signatures)
+            .body(payload)
+            .send()
+            .await?;
+
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+
+        Ok(Response {
+            status_code: i32::from(status.as_u16()),
+            body,
+            success: status.is_success(),
+        })
+    }
+
+    /// Marks a message as delivered to an endpoint, or schedules its next attempt.
+    async fn record_destination(
+        &self,
+        destination: native_message_destinations::Model,
+        success: bool,
+    ) -> Result<native_message_destinations::Model> {
+        let attempts = destination.attempts + 1;
+
+        let mut active_destination: native_message_destinations::ActiveModel = destination.into();
+        active_destination.attempts = Set(attempts);
+        active_destination.locked_until = Set(None);
+
+        match self.retry.schedule(attempts, success, Utc::now()) {
+            Schedule::Delivered => {
+                active_destination.status = Set(status_name(MessageStatus::Success).to_string());
+            },
+            Schedule::Failed => {
+                active_destination.status = Set(status_name(MessageStatus::Fail).to_string());
+            },
+            Schedule::RetryAt(next_attempt_at) => {
+                active_destination.next_attempt_at = Set(next_attempt_at.naive_utc());
+            },
+        }
+
+        Ok(active_destination.update(self.db.get()).await?)
+    }
+
+    /// Tracks the consecutive failures of an endpoint, disabling it when they reach the threshold.
+    /// The counter is updated in place, as other workers may be sending to the same endpoint.
+    /// Returns whether this attempt disabled the endpoint.
+    async fn record_endpoint(&self, endpoint_id: &str, success: bool) -> Result<bool> {
+        if success {
+            native_endpoints::Entity::update_many()
+                .col_expr(
+                    native_endpoints::Column::ConsecutiveFailures,
+                    Expr::value(0),
+                )
+                .filter(native_endpoints::Column::Id.eq(endpoint_id))
+                .filter(native_endpoints::Column::ConsecutiveFailures.ne(0))
+                .exec(self.db.get())
+                .await?;
+
+            return Ok(false);
+        }
+
+        native_endpoints::Entity::update_many()
+            .col_expr(
+                native_endpoints::Column::ConsecutiveFailures,
+                Expr::col(native_endpoints::Column::ConsecutiveFailures).add(1),
+            )
+            .filter(native_endpoints::Column::Id.eq(endpoint_id))
+            .exec(self.db.get())
+            .await?;
+
+        let disabled = native_endpoints::Entity::update_many()
+            .col_expr(native_endpoints::Column::Disabled, Expr::value(true))
+            .col_expr(
+                native_endpoints::Column::UpdatedAt,
+                Expr::value(Utc::now().naive_utc()),
+            )
+            .filter(native_endpoints::Column::Id.eq(endpoint_id))
+            .filter(native_endpoints::Column::Disabled.eq(false))
+            .filter(native_endpoints::Column::ConsecutiveFailures.gte(self.disable_after_failures))
+            .exec(self.db.get())
+            .await?
+            .rows_affected
+            > 0;
+
+        if disabled {
+            warn!(endpoint_id, "disabling failing endpoint");
+        }
+
+        Ok(disabled)
+    }
+}
+
+/// The secrets a message is signed with: the current secret of the endpoint, and its previous
+/// secret until the end of the rotation grace period.
+fn secrets(endpoint: &native_endpoints::Model, now: NaiveDateTime) -> impl Iterator<Item = &str> {
+    let previous_secret = endpoint.previous_secret.as_deref().filter(|_| {
+        endpoint
+            .previous_secret_expires_at
+            .map_or(false, |expires_at| expires_at > now)
+    });
+
+    std::iter::once(endpoint.secret.as_str()).chain(previous_secret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The retry policy of the default native delivery arguments.
+    fn retry() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 8,
+            backoff: Backoff {
+                base: Duration::from_secs(5),
+                max: Duration::from_secs(300),
+            },
+        }
+    }
+
+    fn endpoint(previous_secret_expires_at: Option<NaiveDateTime>) -> native_endpoints::Model {
+        let now = Utc::now().naive_utc();
+
+        native_endpoints::Model {
+            id: "ep_1".to_string(),
+            application_id: "app_1".to_string(),
+            url: "https://example.com/webhooks".to_string(),
+            description: None,
+            version: 1,
+            disabled: false,
+            rate_limit: None,
+            uid: None,
+            channels: None,
+            filter_types: None,
+            secret: "whsec_current".to_string(),
+            previous_secret: Some("whsec_previous".to_string()),
+            previous_secret_expires_at,
+            consecutive_failures: 0,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn failed_attempts_are_retried_with_an_exponential_backoff() {
+        let now = Utc::now();
+        let retry = retry();
+
+        let delays: Vec<i64> = (1..8)
+            .map(|attempts| match retry.schedule(attempts, false, now) {
+                Schedule::RetryAt(at) => (at - now).num_seconds(),
+                schedule => panic!("attempt {attempts} was not retried: {schedule:?}"),
+            })
+            .collect();
+
+        assert_eq!(delays, [5, 10, 20, 40, 80, 160, 300]);
+    }
+
+    #[test]
+    fn messages_fail_after_the_last_attempt() {
+        let now = Utc::now();
+        let retry = retry();
+
+        assert_eq!(retry.schedule(8, false, now), Schedule::Failed);
+        assert_eq!(retry.schedule(9, false, now), Schedule::Failed);
+    }
+
+    #[test]
+    fn successful_attempts_are_never_retried() {
+        let now = Utc::now();
+        let retry = retry();
+
+        assert_eq!(retry.schedule(1, true, now), Schedule::Delivered);
+        assert_eq!(retry.schedule(8, true, now), Schedule::Delivered);
+    }
+
+    #[test]
+    fn previous_secret_signs_until_it_expires() {
+        let now = Utc::now().naive_utc();
+
+        let rotating_endpoint = endpoint(Some(now + chrono::Duration::hours(1)));
+        let rotated_endpoint = endpoint(Some(now - chrono::Duration::hours(1)));
+        let never_rotated_endpoint = endpoint(None);
+
+        let rotating: Vec<_> = secrets(&rotating_endpoint, now).collect();
+        let rotated: Vec<_> = secrets(&rotated_endpoint, now).collect();
+        let never_rotated: Vec<_> = secrets(&never_rotated_endpoint, now).collect();
+
+        assert_eq!(rotating, ["whsec_current", "whsec_previous"]);
+        assert_eq!(rotated, ["whsec_current"]);
+        assert_eq!(never_rotated, ["whsec_current"]);
+    }
+}
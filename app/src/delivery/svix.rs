@@ -0,0 +1,449 @@
+use hub_core::{
+    anyhow::{bail, Result},
+    chrono::{DateTime, Duration, Utc},
+    reqwest::StatusCode,
+};
+use poem::async_trait;
+use svix::{
+    api::{
+        self, ApplicationIn, EndpointSecretRotateIn, EventTypeUpdate, ListOptions, PostOptions,
+        RecoverIn, Svix,
+    },
+    error::Error,
+};
+
+use super::{
+    EndpointIn, EndpointOut, EndpointUpdate, EventTypeIn, EventTypeOut,
+    ListResponseMessageAttemptOut, MessageAttemptListOptions, MessageAttemptOut, MessageIn,
+    MessageOut, MessageStatus, WebhookDelivery, DEFAULT_SECRET_ROTATION_GRACE_PERIOD,
+};
+
+/// The number of endpoints fetched per request when listing the endpoints of an application.
+const ENDPOINTS_PAGE_SIZE: i32 = 250;
+/// The number of attempts fetched per request when listing the attempts of a message.
+const ATTEMPTS_PAGE_SIZE: i32 = 250;
+
+/// Delivers webhook messages through a Svix server.
+#[derive(Clone)]
+pub struct SvixDelivery {
+    svix: Svix,
+}
+
+impl SvixDelivery {
+    #[must_use]
+    pub fn new(svix: Svix) -> Self {
+        Self { svix }
+    }
+}
+
+#[async_trait]
+impl WebhookDelivery for SvixDelivery {
+    async fn create_application(&self, name: String, uid: String) -> Result<String> {
+        let created = self
+            .svix
+            .application()
+            .create(
+                ApplicationIn {
+                    name,
+                    rate_limit: None,
+                    uid: Some(uid.clone()),
+                },
+                None,
+            )
+            .await;
+
+        match created {
+            Ok(app) => Ok(app.id),
+            // svix accepts the uid of an application wherever its ID is expected
+            Err(Error::Http(e)) if e.status == StatusCode::CONFLICT => {
+                let app = self.svix.application().get(uid).await?;
+
+                Ok(app.id)
+            },
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn delete_application(&self, app_id: String) -> Result<()> {
+        match self.svix.application().delete(app_id).await {
+            Ok(()) => Ok(()),
+            Err(Error::Http(e)) if e.status == StatusCode::NOT_FOUND => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn create_endpoint(&self, app_id: String, endpoint: EndpointIn) -> Result<EndpointOut> {
+        let endpoint = self
+            .svix
+            .endpoint()
+            .create(app_id, endpoint_in(endpoint), None)
+            .await?;
+
+        Ok(endpoint_out(endpoint))
+    }
+
+    async fn list_endpoints(&self, app_id: String) -> Result<Vec<EndpointOut>> {
+        let mut endpoints = Vec::new();
+        let mut iterator = None;
+
+        loop {
+            let page = self
+                .svix
+                .endpoint()
+                .list(
+                    app_id.clone(),
+                    Some(ListOptions {
+                        iterator,
+                        limit: Some(ENDPOINTS_PAGE_SIZE),
+                    }),
+                )
+                .await?;
+
+            endpoints.extend(page.data.into_iter().map(endpoint_out));
+
+            if page.done {
+                return Ok(endpoints);
+            }
+
+            iterator = page.iterator;
+        }
+    }
+
+    async fn get_endpoint(&self, app_id: String, endpoint_id: String) -> Result<EndpointOut> {
+        let endpoint = self.svix.endpoint().get(app_id, endpoint_id).await?;
+
+        Ok(endpoint_out(endpoint))
+    }
+
+    async fn update_endpoint(
+        &self,
+        app_id: String,
+        endpoint_id: String,
+        endpoint: EndpointUpdate,
+    ) -> Result<EndpointOut> {
+        let endpoint = self
+            .svix
+            .endpoint()
+            .update(app_id, endpoint_id, endpoint_update(endpoint), None)
+            .await?;
+
+        Ok(endpoint_out(endpoint))
+    }
+
+    async fn delete_endpoint(&self, app_id: String, endpoint_id: String) -> Result<()> {
+        self.svix.endpoint().delete(app_id, endpoint_id).await?;
+
+        Ok(())
+    }
+
+    async fn recover_endpoint(
+        &self,
+        app_id: String,
+        endpoint_id: String,
+        since: DateTime<Utc>,
+    ) -> Result<()> {
+        self.svix
+            .endpoint()
+            .recover(app_id, endpoint_id, RecoverIn {
+                since: since.to_rfc3339(),
+            })
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_endpoint_secret(&self, app_id: String, endpoint_id: String) -> Result<String> {
+        let secret = self.svix.endpoint().get_secret(app_id, endpoint_id).await?;
+
+        Ok(secret.key)
+    }
+
+    async fn rotate_endpoint_secret(
+        &self,
+        app_id: String,
+        endpoint_id: String,
+        key: Option<String>,
+        grace_period: Duration,
+    ) -> Result<DateTime<Utc>> {
+        if grace_period != Duration::hours(DEFAULT_SECRET_ROTATION_GRACE_PERIOD) {
+            bail!(
+                "Svix keeps the previous secret for {DEFAULT_SECRET_ROTATION_GRACE_PERIOD} hours, other grace \
+                 periods require the native delivery backend"
+            );
+        }
+
+        self.svix
+            .endpoint()
+            .rotate_secret(app_id, endpoint_id, EndpointSecretRotateIn { key })
+            .await?;
+
+        Ok(Utc::now() + grace_period)
+    }
+
+    async fn register_event_type(&self, event_type: EventTypeIn) -> Result<()> {
+        let EventTypeIn {
+            name,
+            description,
+            archived,
+            schemas,
+        } = event_type;
+
+        let mut update = EventTypeUpdate::new(description.clone());
+        update.archived = archived;
+        update.schemas = schemas.clone();
+
+        let event_type = api::EventTypeIn {
+            name: name.clone(),
+            description,
+            archived,
+            schemas,
+        };
+
+        match self.svix.event_type().create(event_type, None).await {
+            Ok(_) => Ok(()),
+            Err(Error::Http(e)) if e.status == StatusCode::CONFLICT => {
+                self.svix.event_type().update(name, update, None).await?;
+
+                Ok(())
+            },
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn list_event_types(&self) -> Result<Vec<EventTypeOut>> {
+        let event_types = self.svix.event_type().list(None).await?;
+
+        Ok(event_types.data.into_iter().map(event_type_out).collect())
+    }
+
+    async fn create_message(
+        &self,
+        app_id: String,
+        message: MessageIn,
+        idempotency_key: Option<String>,
+    ) -> Result<MessageOut> {
+        let options = idempotency_key.map(|idempotency_key| PostOptions {
+            idempotency_key: Some(idempotency_key),
+        });
+
+        let MessageIn {
+            event_type,
+            payload,
+            event_id,
+            channels,
+        } = message;
+
+        let message = api::MessageIn {
+            event_type,
+            payload,
+            event_id,
+            channels,
+            payload_retention_period: None,
+        };
+
+        let message = self.svix.message().create(app_id, message, options).await?;
+
+        Ok(message_out(message))
+    }
+
+    async fn resend_message(
+        &self,
+        app_id: String,
+        message_id: String,
+        endpoint_id: String,
+    ) -> Result<()> {
+        self.svix
+            .message_attempt()
+            .resend(app_id, message_id, endpoint_id)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn list_endpoint_attempts(
+        &self,
+        app_id: String,
+        endpoint_id: String,
+        options: MessageAttemptListOptions,
+    ) -> Result<ListResponseMessageAttemptOut> {
+        let MessageAttemptListOptions {
+            iterator,
+            limit,
+            status,
+            event_types,
+        } = options;
+
+        let options = api::MessageAttemptListOptions {
+            iterator,
+            limit,
+            status: status.map(message_status_in),
+            event_types,
+            ..Default::default()
+        };
+
+        let page = self
+            .svix
+            .message_attempt()
+            .list_by_endpoint(app_id, endpoint_id, Some(options))
+            .await?;
+
+        Ok(ListResponseMessageAttemptOut {
+            data: page.data.into_iter().map(attempt_out).collect(),
+            done: page.done,
+            iterator: page.iterator,
+        })
+    }
+
+    async fn list_message_attempts(
+        &self,
+        app_id: String,
+        message_id: String,
+    ) -> Result<Vec<MessageAttemptOut>> {
+        let mut attempts = Vec::new();
+        let mut iterator = None;
+
+        loop {
+            let page = self
+                .svix
+                .message_attempt()
+                .list_by_msg(
+                    app_id.clone(),
+                    message_id.clone(),
+                    Some(api::MessageAttemptListOptions {
+                        iterator,
+                        limit: Some(ATTEMPTS_PAGE_SIZE),
+                        ..Default::default()
+                    }),
+                )
+                .await?;
+
+            attempts.extend(page.data.into_iter().map(attempt_out));
+
+            if page.done {
+                return Ok(attempts);
+            }
+
+            iterator = page.iterator;
+        }
+    }
+}
+
+fn endpoint_in(endpoint: EndpointIn) -> api::EndpointIn {
+    let EndpointIn {
+        url,
+        version,
+        description,
+        disabled,
+        rate_limit,
+        uid,
+        channels,
+        filter_types,
+        secret,
+    } = endpoint;
+
+    api::EndpointIn {
+        channels,
+        filter_types,
+        version,
+        description,
+        disabled,
+        rate_limit,
+        secret,
+        url,
+        uid,
+    }
+}
+
+fn endpoint_update(endpoint: EndpointUpdate) -> api::EndpointUpdate {
+    let EndpointUpdate {
+        url,
+        version,
+        description,
+        disabled,
+        rate_limit,
+        uid,
+        channels,
+        filter_types,
+    } = endpoint;
+
+    api::EndpointUpdate {
+        channels,
+        filter_types,
+        version,
+        description,
+        disabled,
+        rate_limit,
+        url,
+        uid,
+    }
+}
+
+fn endpoint_out(endpoint: api::EndpointOut) -> EndpointOut {
+    EndpointOut {
+        id: endpoint.id,
+        url: endpoint.url,
+        version: endpoint.version,
+        description: endpoint.description,
+        disabled: endpoint.disabled,
+        rate_limit: endpoint.rate_limit,
+        uid: endpoint.uid,
+        channels: endpoint.channels,
+        filter_types: endpoint.filter_types,
+        created_at: endpoint.created_at,
+        updated_at: endpoint.updated_at,
+    }
+}
+
+fn event_type_out(event_type: api::EventTypeOut) -> EventTypeOut {
+    EventTypeOut {
+        name: event_type.name,
+        description: event_type.description,
+        archived: event_type.archived,
+        schemas: event_type.schemas,
+        created_at: event_type.created_at,
+        updated_at: event_type.updated_at,
+    }
+}
+
+fn message_out(message: api::MessageOut) -> MessageOut {
+    MessageOut {
+        id: message.id,
+        event_type: message.event_type,
+        payload: message.payload,
+        event_id: message.event_id,
+        channels: message.channels,
+        timestamp: message.timestamp,
+    }
+}
+
+fn message_status_in(status: MessageStatus) -> api::MessageStatus {
+    match status {
+        MessageStatus::Success => api::MessageStatus::Success,
+        MessageStatus::Pending => api::MessageStatus::Pending,
+        MessageStatus::Fail => api::MessageStatus::Fail,
+        MessageStatus::Sending => api::MessageStatus::Sending,
+    }
+}
+
+fn message_status_out(status: api::MessageStatus) -> MessageStatus {
+    match status {
+        api::MessageStatus::Success => MessageStatus::Success,
+        api::MessageStatus::Pending => MessageStatus::Pending,
+        api::MessageStatus::Fail => MessageStatus::Fail,
+        api::MessageStatus::Sending => MessageStatus::Sending,
+    }
+}
+
+fn attempt_out(attempt: api::MessageAttemptOut) -> MessageAttemptOut {
+    MessageAttemptOut {
+        id: attempt.id,
+        msg_id: attempt.msg_id,
+        endpoint_id: attempt.endpoint_id,
+        url: attempt.url,
+        status: message_status_out(attempt.status),
+        response_status_code: attempt.response_status_code,
+        response: attempt.response,
+        timestamp: attempt.timestamp,
+        msg: attempt.msg.map(message_out),
+    }
+}
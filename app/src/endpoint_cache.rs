@@ -0,0 +1,113 @@
+use std::time::Duration;
+
+use hub_core::{anyhow::Result, chrono::Utc, clap, prelude::*, tokio};
+use sea_orm::{prelude::*, JoinType, QueryOrder, QuerySelect, Set};
+use serde_json::Value;
+
+use crate::{
+    db::Connection,
+    delivery::{Delivery, EndpointOut},
+    entities::{organization_applications, webhooks},
+};
+
+/// Arguments for keeping the endpoint metadata stored on webhooks in sync with the delivery backend
+#[derive(Debug, clap::Args)]
+pub struct EndpointCacheArgs {
+    /// Seconds between two syncs of the stored endpoint metadata with the delivery backend. Disabled when 0
+    #[arg(long, env, default_value_t = 3600)]
+    pub endpoint_cache_sync_interval: u64,
+    /// Maximum number of webhooks synced per batch
+    #[arg(long, env, default_value_t = 100)]
+    pub endpoint_cache_batch_size: u64,
+}
+
+/// Copies the metadata of the endpoint onto the webhook, so reads are served from Postgres.
+pub fn store(webhook: &mut webhooks::ActiveModel, endpoint: &EndpointOut) {
+    webhook.url = Set(Some(endpoint.url.clone()));
+    webhook.description = Set(endpoint.description.clone());
+    webhook.filter_types = Set(endpoint.filter_types.clone().map(Value::from));
+    webhook.channels = Set(endpoint.channels.clone().map(Value::from));
+    webhook.disabled = Set(endpoint.disabled.unwrap_or_default());
+    webhook.synced_at = Set(Some(Utc::now().naive_utc()));
+}
+
+/// Syncs the stored endpoint metadata with the delivery backend every `endpoint_cache_sync_interval` seconds,
+/// picking up changes made outside of this service. The first sync runs on startup, which fills the metadata of
+/// webhooks created before it was stored. Returns immediately when the interval is 0.
+pub async fn run(db: Connection, delivery: Delivery, args: EndpointCacheArgs) {
+    let EndpointCacheArgs {
+        endpoint_cache_sync_interval,
+        endpoint_cache_batch_size,
+    } = args;
+
+    if endpoint_cache_sync_interval == 0 {
+        return;
+    }
+
+    loop {
+        match sync(&db, &delivery, endpoint_cache_batch_size).await {
+            Ok(synced) => debug!(synced, "synced endpoint metadata"),
+            Err(e) => error!("failed to sync endpoint metadata: {:?}", e),
+        }
+
+        tokio::time::sleep(Duration::from_secs(endpoint_cache_sync_interval)).await;
+    }
+}
+
+/// Refreshes the metadata of every webhook, least recently synced first, returning how many were synced.
+/// Webhooks whose endpoint cannot be fetched are skipped until the next sync.
+///
+/// # Errors
+/// This function fails if the webhooks cannot be read or updated.
+pub async fn sync(db: &Connection, delivery: &Delivery, batch_size: u64) -> Result<usize> {
+    let started_at = Utc::now().naive_utc();
+    let mut synced = 0;
+
+    loop {
+        let batch = webhooks::Entity::find()
+            .select_also(organization_applications::Entity)
+            .join(
+                JoinType::InnerJoin,
+                webhooks::Relation::OrganizationApplications.def(),
+            )
+            .filter(
+                webhooks::Column::SyncedAt
+                    .is_null()
+                    .or(webhooks::Column::SyncedAt.lt(started_at)),
+            )
+            .order_by_asc(webhooks::Column::SyncedAt)
+            .limit(batch_size.max(1))
+            .all(db.get())
+            .await?;
+
+        if batch.is_empty() {
+            return Ok(synced);
+        }
+
+        for (webhook, organization_application) in batch {
+            let Some(organization_application) = organization_application else {
+                continue;
+            };
+
+            let mut active_webhook: webhooks::ActiveModel = webhook.clone().into();
+
+            match delivery
+                .get_endpoint(organization_application.svix_app_id, webhook.endpoint_id)
+                .await
+            {
+                Ok(endpoint) => {
+                    store(&mut active_webhook, &endpoint);
+                    synced += 1;
+                },
+                Err(e) => {
+                    warn!(id = ?webhook.id, "failed to fetch endpoint of webhook {:?}", e);
+
+                    // retried on the next sync rather than within this one
+                    active_webhook.synced_at = Set(Some(Utc::now().naive_utc()));
+                },
+            }
+
+            active_webhook.update(db.get()).await?;
+        }
+    }
+}
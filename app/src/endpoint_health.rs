@@ -0,0 +1,145 @@
+use hub_core::{anyhow::Result, chrono::Utc, prelude::*};
+use sea_orm::{prelude::*, Set};
+
+use crate::{
+    db::Connection,
+    entities::webhooks,
+    proto::{self, webhook_events::Event, WebhookEventKey, WebhookEvents},
+    EventsProducer,
+};
+
+/// Whether the messages sent to an endpoint are being delivered, as stored on its webhook.
+/// Disabled endpoints are tracked by the `disabled` column of the webhook instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Health {
+    /// The last message that finished its attempts was delivered.
+    Healthy,
+    /// The last message that finished its attempts was not delivered.
+    Failing,
+}
+
+impl Health {
+    /// The name of the health as stored in the `health` column.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Healthy => "healthy",
+            Self::Failing => "failing",
+        }
+    }
+
+    /// Reads the health stored in the `health` column, treating unknown values as healthy.
+    #[must_use]
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "failing" => Self::Failing,
+            _ => Self::Healthy,
+        }
+    }
+}
+
+/// A change in the health of an endpoint reported by the delivery backend.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HealthChange {
+    /// A message was not delivered after its last attempt.
+    Failing {
+        message_id: String,
+        attempts: i32,
+        response_status_code: i32,
+    },
+    /// A message was delivered after previous attempts failed.
+    Recovered,
+    /// The endpoint was disabled after failing repeatedly.
+    Disabled,
+    /// The endpoint was enabled again.
+    Enabled,
+}
+
+/// Records a change in the health of an endpoint on its webhook, then publishes it as an event of the webhook
+/// without an acting user. Changes leaving the webhook as it was, such as a notification delivered twice, are
+/// not published again. Endpoints no webhook refers to are skipped.
+///
+/// # Errors
+/// This function fails if the webhook cannot be read or updated, or the event cannot be published.
+pub async fn record(
+    db: &Connection,
+    producer: &EventsProducer,
+    endpoint_id: &str,
+    change: HealthChange,
+) -> Result<()> {
+    let Some(webhook) = webhooks::Entity::find()
+        .filter(webhooks::Column::EndpointId.eq(endpoint_id))
+        .one(db.get())
+        .await?
+    else {
+        debug!(
+            endpoint_id,
+            ?change,
+            "skipping health change of unknown endpoint"
+        );
+
+        return Ok(());
+    };
+
+    let previous_health = Health::from_name(&webhook.health);
+    let webhook_proto = proto::Webhook {
+        organization_id: webhook.organization_id.to_string(),
+        endpoint_id: webhook.endpoint_id.clone(),
+    };
+
+    let (health, disabled, event) = match change {
+        HealthChange::Failing {
+            message_id,
+            attempts,
+            response_status_code,
+        } => (
+            Health::Failing,
+            webhook.disabled,
+            Some(Event::EndpointFailing(proto::WebhookFailure {
+                organization_id: webhook.organization_id.to_string(),
+                endpoint_id: webhook.endpoint_id.clone(),
+                message_id,
+                attempts,
+                response_status_code,
+            })),
+        ),
+        HealthChange::Recovered => (
+            Health::Healthy,
+            webhook.disabled,
+            (previous_health == Health::Failing).then_some(Event::EndpointRecovered(webhook_proto)),
+        ),
+        HealthChange::Disabled => (
+            previous_health,
+            true,
+            (!webhook.disabled).then_some(Event::Disabled(webhook_proto)),
+        ),
+        // failures from before the endpoint was disabled no longer apply
+        HealthChange::Enabled => (
+            Health::Healthy,
+            false,
+            webhook.disabled.then_some(Event::Enabled(webhook_proto)),
+        ),
+    };
+
+    if health != previous_health || disabled != webhook.disabled {
+        let mut active_webhook: webhooks::ActiveModel = webhook.clone().into();
+        active_webhook.health = Set(health.name().to_string());
+        active_webhook.health_changed_at = Set(Some(Utc::now().naive_utc()));
+        active_webhook.disabled = Set(disabled);
+
+        active_webhook.update(db.get()).await?;
+    }
+
+    let Some(event) = event else {
+        return Ok(());
+    };
+
+    let key = WebhookEventKey {
+        id: webhook.id.to_string(),
+        user_id: String::new(),
+    };
+
+    producer
+        .send(&WebhookEvents { event: Some(event) }, &key)
+        .await
+}
@@ -1,5 +1,15 @@
 #![allow(clippy::wildcard_imports)] // for generated code
 
+pub mod dead_letters;
+pub mod message_idempotency_keys;
+pub mod native_applications;
+pub mod native_endpoints;
+pub mod native_event_types;
+pub mod native_message_attempts;
+pub mod native_message_destinations;
+pub mod native_messages;
 pub mod organization_applications;
+pub mod organization_members;
+pub mod outbox_messages;
 pub mod webhook_projects;
 pub mod webhooks;
@@ -1,6 +1,16 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.11.0
 
 pub use super::{
-    organization_applications::Entity as OrganizationApplications, webhooks::Entity as Webhooks,
+    dead_letters::Entity as DeadLetters,
+    message_idempotency_keys::Entity as MessageIdempotencyKeys,
+    native_applications::Entity as NativeApplications,
+    native_endpoints::Entity as NativeEndpoints,
+    native_event_types::Entity as NativeEventTypes,
+    native_message_attempts::Entity as NativeMessageAttempts,
+    native_message_destinations::Entity as NativeMessageDestinations,
+    native_messages::Entity as NativeMessages,
+    organization_applications::Entity as OrganizationApplications,
+    organization_members::Entity as OrganizationMembers,
+    outbox_messages::Entity as OutboxMessages, webhooks::Entity as Webhooks,
     webhook_projects::Entity as WebhookProjects,
 };
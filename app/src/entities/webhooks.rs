@@ -12,6 +12,20 @@ pub struct Model {
     pub created_at: DateTime,
     pub updated_at: Option<DateTime>,
     pub created_by: Uuid,
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub filter_types: Option<Json>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub url: Option<String>,
+    pub disabled: bool,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub description: Option<String>,
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub channels: Option<Json>,
+    pub synced_at: Option<DateTime>,
+    #[sea_orm(column_type = "Text")]
+    pub health: String,
+    pub health_changed_at: Option<DateTime>,
+    pub api_version: i32,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -0,0 +1,558 @@
+//! The event types webhooks can subscribe to, declared once in [`event_types!`].
+//! Each declaration generates a [`FilterType`] variant with its name and description in the delivery backend,
+//! a payload struct with its JSON schema, an [`EventPayload`] variant and an example payload.
+//! Payloads whose shape changed in a later [`ApiVersion`] have an additional struct for that version.
+
+use std::fmt;
+
+use async_graphql::Enum;
+use hub_core::uuid::Uuid;
+use once_cell::sync::Lazy;
+use serde::{Serialize, Serializer};
+use serde_json::{json, Map, Value};
+
+use crate::{delivery::EventTypeIn, entities::webhooks, proto::CreationStatus};
+
+/// Placeholder wallet address used in example payloads.
+const EXAMPLE_WALLET_ADDRESS: &str = "11111111111111111111111111111111";
+/// Placeholder project name used in example payloads.
+const EXAMPLE_PROJECT_NAME: &str = "Example Project";
+
+/// The schemas published in the event type catalog, keyed by event type name and API version number.
+/// Receivers code against these, so they are committed in `schemas/event_types.json` rather than derived at startup:
+/// a change to a payload struct or an upstream proto only reaches the catalog through a reviewed edit of the snapshot.
+static REGISTERED_SCHEMAS: Lazy<Value> = Lazy::new(|| {
+    serde_json::from_str(include_str!("../schemas/event_types.json")).unwrap_or_default()
+});
+
+/// Declares a payload struct along with its JSON schema, built from the doc comments and types of its fields.
+macro_rules! payload {
+    (
+        $(#[$attr:meta])*
+        $payload:ident {
+            $(
+                $(#[doc = $field_doc:literal])*
+                $field:ident: $ty:ty
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone, Serialize)]
+        pub struct $payload {
+            $(
+                $(#[doc = $field_doc])*
+                pub $field: $ty,
+            )*
+        }
+
+        impl JsonSchema for $payload {
+            fn json_schema() -> Value {
+                object_schema(&[$((
+                    stringify!($field),
+                    concat!($($field_doc),*),
+                    <$ty as JsonSchema>::json_schema(),
+                ),)*])
+            }
+        }
+    };
+}
+
+/// Declares the event types, see the module documentation for what is generated.
+/// The doc comments of the event types and payload fields are used in the GraphQL schema and the registered
+/// JSON schemas, and the types of the fields must implement [`JsonSchema`]. Each field is followed by the value
+/// it takes in example payloads.
+macro_rules! event_types {
+    ($(
+        $(#[doc = $doc:literal])*
+        $variant:ident {
+            name: $name:literal,
+            description: $description:literal,
+            payload: $payload:ident {
+                $(
+                    $(#[doc = $field_doc:literal])*
+                    $field:ident: $ty:ty = $example:expr
+                ),* $(,)?
+            } $(,)?
+        }
+    ),* $(,)?) => {
+        /// An enumeration of event types that can be subscribed to by a webhook.
+        #[derive(Debug, Clone, PartialEq, Eq, Copy, Enum)]
+        pub enum FilterType {
+            $(
+                $(#[doc = $doc])*
+                $variant,
+            )*
+        }
+
+        impl FilterType {
+            /// Every event type, in the order they are declared.
+            pub const ALL: &'static [Self] = &[$(Self::$variant),*];
+
+            /// The name of the event type in the delivery backend.
+            #[must_use]
+            pub fn name(self) -> &'static str {
+                match self {
+                    $(Self::$variant => $name,)*
+                }
+            }
+
+            /// The description of the event type in the delivery backend.
+            #[must_use]
+            pub fn description(self) -> &'static str {
+                match self {
+                    $(Self::$variant => $description,)*
+                }
+            }
+
+            /// The JSON schema of the payload of the event type in its first version.
+            fn payload_schema_v1(self) -> Value {
+                match self {
+                    $(Self::$variant => $payload::json_schema(),)*
+                }
+            }
+
+            /// A payload of the event type with placeholder IDs, used to test webhook endpoints.
+            #[must_use]
+            pub fn example(self) -> EventPayload {
+                match self {
+                    $(Self::$variant => EventPayload::$variant($payload {
+                        $($field: $example,)*
+                    }),)*
+                }
+            }
+        }
+
+        impl std::str::FromStr for FilterType {
+            type Err = String;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s {
+                    $($name => Ok(Self::$variant),)*
+                    _ => Err(format!("'{s}' is not a valid value for FilterType")),
+                }
+            }
+        }
+
+        $(
+            payload! {
+                #[doc = concat!("The payload of `", $name, "` events.")]
+                $payload {
+                    $(
+                        $(#[doc = $field_doc])*
+                        $field: $ty,
+                    )*
+                }
+            }
+
+            impl From<$payload> for EventPayload {
+                fn from(payload: $payload) -> Self {
+                    Self::$variant(payload)
+                }
+            }
+        )*
+
+        /// The payload of an event, serialized as the payload struct of its type.
+        #[derive(Debug, Clone, Serialize)]
+        #[serde(untagged)]
+        pub enum EventPayload {
+            $($variant($payload),)*
+        }
+
+        impl EventPayload {
+            /// The type of the event the payload belongs to.
+            #[must_use]
+            pub fn event_type(&self) -> FilterType {
+                match self {
+                    $(Self::$variant(_) => FilterType::$variant,)*
+                }
+            }
+        }
+    };
+}
+
+event_types! {
+    /// Event triggered when a new project is created
+    ProjectCreated {
+        name: "project.created",
+        description: "A project was created",
+        payload: ProjectCreatedPayload {
+            /// Project id
+            project_id: String = example_id(),
+            /// Organization id
+            organization_id: String = example_id(),
+            /// Project name
+            name: String = EXAMPLE_PROJECT_NAME.to_string(),
+        },
+    },
+    /// Event triggered when a new customer is created
+    CustomerCreated {
+        name: "customer.created",
+        description: "A customer was created",
+        payload: CustomerCreatedPayload {
+            /// Customer id
+            customer_id: String = example_id(),
+            /// Project id
+            project_id: String = example_id(),
+        },
+    },
+    /// Event triggered when a new customer treasury is created
+    CustomerTreasuryCreated {
+        name: "customer_treasury.created",
+        description: "A customer treasury was created",
+        payload: CustomerTreasuryCreatedPayload {
+            /// Treasury id
+            treasury_id: String = example_id(),
+            /// Project id
+            project_id: String = example_id(),
+            /// Customer id
+            customer_id: String = example_id(),
+        },
+    },
+    /// Event triggered when a new wallet is created for a project
+    ProjectWalletCreated {
+        name: "project_wallet.created",
+        description: "A project treasury wallet was created",
+        payload: ProjectWalletCreatedPayload {
+            /// Wallet id
+            treasury_id: String = example_id(),
+            /// Project id
+            project_id: String = example_id(),
+        },
+    },
+    /// Event triggered when a new wallet is created for a customer
+    CustomerWalletCreated {
+        name: "customer_wallet.created",
+        description: "A customer treasury wallet was created",
+        payload: CustomerWalletCreatedPayload {
+            /// Wallet id
+            treasury_id: String = example_id(),
+            /// Project id
+            project_id: String = example_id(),
+            /// Customer id
+            customer_id: String = example_id(),
+        },
+    },
+    /// Event triggered when a new drop is created
+    DropCreated {
+        name: "drop.created",
+        description: "A drop was created",
+        payload: DropCreatedPayload {
+            /// Drop id
+            drop_id: String = example_id(),
+            /// Project id
+            project_id: String = example_id(),
+            /// The status of the drop's creation
+            creation_status: CreationStatus = CreationStatus::Created,
+        },
+    },
+    /// Event triggered when a new drop is minted
+    DropMinted {
+        name: "drop.minted",
+        description: "A drop was minted",
+        payload: DropMintedPayload {
+            /// Mint id
+            mint_id: String = example_id(),
+            /// Project id
+            project_id: String = example_id(),
+            /// Drop id
+            drop_id: String = example_id(),
+            /// The status of the mint creation
+            creation_status: CreationStatus = CreationStatus::Created,
+        },
+    },
+    /// Event triggered when a mint has been successfully transfered
+    MintTransfered {
+        name: "mint.transfered",
+        description: "A mint was transfered",
+        payload: MintTransferedPayload {
+            /// Project id
+            project_id: String = example_id(),
+            /// Sender wallet address
+            sender: String = EXAMPLE_WALLET_ADDRESS.to_string(),
+            /// Recipient wallet address
+            recipient: String = EXAMPLE_WALLET_ADDRESS.to_string(),
+            /// Mint id
+            mint_id: String = example_id(),
+        },
+    },
+    /// Event triggered when a new collection is created
+    CollectionCreated {
+        name: "collection.created",
+        description: "A collection was created",
+        payload: CollectionCreatedPayload {
+            /// Collection id
+            collection_id: String = example_id(),
+            /// Project id
+            project_id: String = example_id(),
+            /// Collection status
+            status: CreationStatus = CreationStatus::Created,
+        },
+    },
+    /// Event triggered when an NFT is minted to a collection
+    MintedToCollection {
+        name: "collection.minted",
+        description: "An NFT was minted to a collection",
+        payload: MintedToCollectionPayload {
+            /// Collection Mint id
+            mint_id: String = example_id(),
+            /// Collection id
+            collection_id: String = example_id(),
+            /// Project id
+            project_id: String = example_id(),
+            /// Collection mint status
+            status: CreationStatus = CreationStatus::Created,
+        },
+    },
+}
+
+impl FilterType {
+    /// The name of the event type in the delivery backend, as an owned string.
+    #[must_use]
+    pub fn format(self) -> String {
+        self.name().to_string()
+    }
+
+    /// The JSON schema of the payload of the event type in an API version.
+    fn payload_schema(self, api_version: ApiVersion) -> Value {
+        match (api_version, self) {
+            (ApiVersion::V2, Self::DropCreated) => DropCreatedPayloadV2::json_schema(),
+            (ApiVersion::V2, Self::DropMinted) => DropMintedPayloadV2::json_schema(),
+            _ => self.payload_schema_v1(),
+        }
+    }
+
+    /// The JSON schema of the messages of the event type in an API version, derived from its payload struct.
+    /// The tests check it against [`FilterType::registered_schema`], which is what messages are validated against.
+    #[must_use]
+    pub fn schema(self, api_version: ApiVersion) -> Value {
+        let string = json!({ "type": "string" });
+        let integer = json!({ "type": "integer" });
+
+        let mut source = object_schema(&[
+            ("topic", "Kafka topic", string.clone()),
+            ("partition", "Kafka partition", integer.clone()),
+            ("offset", "Offset of the message in its partition", integer),
+        ]);
+        // test messages and events re-driven without a recorded position have no source
+        source["type"] = json!(["object", "null"]);
+
+        let event_type = json!({ "type": "string", "enum": [self.name()] });
+        let version = json!({ "type": "integer", "enum": [api_version.number()] });
+        let created_at = json!({ "type": "string", "format": "date-time" });
+
+        let mut schema = object_schema(&[
+            ("id", "Event ID", string.clone()),
+            ("event_type", "Event Type", event_type),
+            ("api_version", "API Version", version),
+            ("organization_id", "Organization ID", string),
+            ("created_at", "When the event occurred", created_at),
+            ("source", "Kafka message of the event", source),
+            ("payload", "Event Payload", self.payload_schema(api_version)),
+        ]);
+        schema["title"] = self.name().into();
+        schema["description"] = self.description().into();
+
+        schema
+    }
+
+    /// The JSON schema of the messages of the event type in an API version, as published in the event type catalog.
+    /// Returns `None` when the snapshot has no schema for the version.
+    #[must_use]
+    pub fn registered_schema(self, api_version: ApiVersion) -> Option<&'static Value> {
+        REGISTERED_SCHEMAS
+            .get(self.name())
+            .and_then(|schemas| schemas.get(api_version.number().to_string()))
+    }
+
+    /// The event type as registered with the delivery backend, with its published schema for every API version keyed
+    /// by its number.
+    #[must_use]
+    pub fn event_type_in(self) -> EventTypeIn {
+        let schemas = ApiVersion::ALL
+            .iter()
+            .filter_map(|api_version| {
+                self.registered_schema(*api_version)
+                    .map(|schema| (api_version.number().to_string(), schema.clone()))
+            })
+            .collect();
+
+        EventTypeIn {
+            description: self.description().to_string(),
+            schemas: Some(schemas),
+            archived: Some(false),
+            name: self.format(),
+        }
+    }
+}
+
+/// The versions of the shape of the messages sent to webhooks. A webhook keeps receiving the messages of its
+/// version until it is edited to another one, so that payloads can change without breaking existing integrations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Enum)]
+pub enum ApiVersion {
+    /// The original messages
+    V1,
+    /// The `drop.created` and `drop.minted` payloads report the creation status as `status`, like the collection
+    /// payloads
+    V2,
+}
+
+impl ApiVersion {
+    /// Every API version, from the oldest.
+    pub const ALL: &'static [Self] = &[Self::V1, Self::V2];
+    /// The version of the webhooks created without one.
+    pub const LATEST: Self = Self::V2;
+
+    /// The number of the version, as stored on the webhooks and used as the key of the registered schemas.
+    #[must_use]
+    pub fn number(self) -> i32 {
+        match self {
+            Self::V1 => 1,
+            Self::V2 => 2,
+        }
+    }
+
+    /// The version stored on a webhook. Numbers of no known version are read as the first version.
+    #[must_use]
+    pub fn of(webhook: &webhooks::Model) -> Self {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|api_version| api_version.number() == webhook.api_version)
+            .unwrap_or(Self::V1)
+    }
+
+    /// The channel carrying the messages about a project or organization to the endpoints of the version.
+    /// Endpoints of the first version listen on the bare IDs, as they did before API versions existed.
+    #[must_use]
+    pub fn channel(self, id: impl fmt::Display) -> String {
+        match self {
+            Self::V1 => id.to_string(),
+            _ => format!("{id}.v{}", self.number()),
+        }
+    }
+
+    /// The ID of the project or organization whose messages a channel carries, whatever its version.
+    #[must_use]
+    pub fn channel_id(channel: &str) -> &str {
+        channel.split_once('.').map_or(channel, |(id, _)| id)
+    }
+}
+
+payload! {
+    /// The payload of `drop.created` events from API version 2.
+    DropCreatedPayloadV2 {
+        /// Drop id
+        drop_id: String,
+        /// Project id
+        project_id: String,
+        /// The status of the drop's creation
+        status: CreationStatus,
+    }
+}
+
+impl From<DropCreatedPayload> for DropCreatedPayloadV2 {
+    fn from(payload: DropCreatedPayload) -> Self {
+        let DropCreatedPayload {
+            drop_id,
+            project_id,
+            creation_status,
+        } = payload;
+
+        Self {
+            drop_id,
+            project_id,
+            status: creation_status,
+        }
+    }
+}
+
+payload! {
+    /// The payload of `drop.minted` events from API version 2.
+    DropMintedPayloadV2 {
+        /// Mint id
+        mint_id: String,
+        /// Project id
+        project_id: String,
+        /// Drop id
+        drop_id: String,
+        /// The status of the mint creation
+        status: CreationStatus,
+    }
+}
+
+impl From<DropMintedPayload> for DropMintedPayloadV2 {
+    fn from(payload: DropMintedPayload) -> Self {
+        let DropMintedPayload {
+            mint_id,
+            project_id,
+            drop_id,
+            creation_status,
+        } = payload;
+
+        Self {
+            mint_id,
+            project_id,
+            drop_id,
+            status: creation_status,
+        }
+    }
+}
+
+fn example_id() -> String {
+    Uuid::nil().to_string()
+}
+
+/// A type of payload field that describes itself as a JSON schema.
+pub trait JsonSchema {
+    /// The JSON schema of the values of the type.
+    fn json_schema() -> Value;
+}
+
+impl JsonSchema for String {
+    fn json_schema() -> Value {
+        json!({ "type": "string" })
+    }
+}
+
+impl JsonSchema for CreationStatus {
+    fn json_schema() -> Value {
+        // the statuses are numbered from 0 without gaps
+        let statuses: Vec<&str> = (0..)
+            .map_while(Self::from_i32)
+            .map(|status| status.as_str_name())
+            .collect();
+
+        json!({
+            "type": "string",
+            "enum": statuses,
+        })
+    }
+}
+
+/// Creation statuses are sent by name, as in the `CreationStatus` enum of the hub-nfts proto.
+impl Serialize for CreationStatus {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str_name())
+    }
+}
+
+/// Builds the schema of an object whose fields are all required, from their names, doc comments and schemas.
+fn object_schema(fields: &[(&str, &str, Value)]) -> Value {
+    let properties: Map<String, Value> = fields
+        .iter()
+        .map(|(field, description, schema)| {
+            let mut schema = schema.clone();
+            schema["description"] = description.trim().into();
+
+            ((*field).to_string(), schema)
+        })
+        .collect();
+
+    let required: Vec<&str> = fields.iter().map(|(field, ..)| *field).collect();
+
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+    })
+}
@@ -1,18 +1,38 @@
-use hub_core::{prelude::*, uuid::Uuid};
-use sea_orm::{prelude::*, JoinType, QuerySelect, Set};
+use std::{collections::BTreeSet, ops::Add};
+
+use hub_core::{
+    chrono::{DateTime, SecondsFormat, Utc},
+    prelude::*,
+    uuid::Uuid,
+};
+use sea_orm::{
+    prelude::*,
+    sea_query::{Expr, OnConflict},
+    JoinType, QuerySelect, Set, TransactionTrait,
+};
 use serde::Serialize;
 use serde_json::Value;
-use svix::api::{ApplicationIn, MessageIn, Svix};
 
 use crate::{
+    applications,
     db::Connection,
-    entities::{organization_applications, webhook_projects, webhooks},
-    mutations::webhook::FilterType,
+    delivery::{Delivery, EndpointUpdate, MessageIn},
+    endpoint_cache,
+    entities::{organization_applications, organization_members, webhook_projects, webhooks},
+    event_types::{
+        ApiVersion, CollectionCreatedPayload, CustomerCreatedPayload,
+        CustomerTreasuryCreatedPayload, CustomerWalletCreatedPayload, DropCreatedPayload,
+        DropCreatedPayloadV2, DropMintedPayload, DropMintedPayloadV2, EventPayload, FilterType,
+        MintTransferedPayload, MintedToCollectionPayload, ProjectCreatedPayload,
+        ProjectWalletCreatedPayload,
+    },
+    metrics, outbox,
     proto::{
-        customer_events, nft_events, organization_events, treasury_events, CreationStatus,
-        Organization, OrganizationEventKey,
+        self, customer_events, nft_events, organization_events, treasury_events, webhook_events,
+        CreationStatus, WebhookEventKey, WebhookEvents,
     },
-    Services,
+    validation::PayloadValidation,
+    EventsProducer, Services, Source,
 };
 
 /// Res
@@ -20,31 +40,76 @@ use crate::{
 /// # Errors
 /// This function fails if ...
 #[allow(clippy::too_many_lines)]
-pub async fn process(msg: Services, db: Connection, svix: Svix) -> Result<()> {
+pub async fn process(
+    msg: Services,
+    source: &Source,
+    db: Connection,
+    delivery: Delivery,
+    producer: EventsProducer,
+    validation: PayloadValidation,
+) -> Result<()> {
     // match topics
     match msg {
         Services::Organizations(k, e) => match e.event {
             Some(organization_events::Event::OrganizationCreated(org)) => {
-                create_svix_application(db, svix, k, org).await
+                add_organization_member(&db, &org.id, &k.user_id).await?;
+
+                let organization_id = Uuid::parse_str(&org.id)?;
+
+                applications::get_or_create(&db, &delivery, organization_id, Some(org.name))
+                    .await?;
+
+                Ok(())
+            },
+            Some(organization_events::Event::MemberAdded(member)) => {
+                add_organization_member(&db, &member.organization_id, &member.user_id).await
+            },
+            Some(organization_events::Event::MemberDeactivated(member)) => {
+                deactivate_organization_member(&db, &member.organization_id, &member.user_id).await
+            },
+            Some(organization_events::Event::ProjectCreated(project)) => {
+                let event_id = event_id(FilterType::ProjectCreated, &[&project.id]);
+
+                let payload = ProjectCreatedPayload {
+                    project_id: project.id,
+                    organization_id: project.organization_id.clone(),
+                    name: project.name,
+                };
+
+                broadcast_organization(
+                    db,
+                    validation,
+                    source,
+                    project.organization_id,
+                    event_id,
+                    payload.into(),
+                )
+                .await
+            },
+            Some(organization_events::Event::ProjectDeactivated(project)) => {
+                remove_project(db, delivery, project.id).await
+            },
+            Some(organization_events::Event::OrganizationDeactivated(org)) => {
+                remove_organization(db, delivery, producer, org.id, k.user_id).await
             },
             Some(_) | None => Ok(()),
         },
         Services::Customers(k, e) => match e.event {
             Some(customer_events::Event::Created(customer)) => {
-                let payload = serde_json::to_value(Event {
-                    event_type: FilterType::CustomerCreated.format(),
-                    payload: EventPayload::CustomerCreated(CustomerCreatedPayload {
-                        project_id: customer.project_id.clone(),
-                        customer_id: k.id.clone(),
-                    }),
-                })?;
+                let event_id = event_id(FilterType::CustomerCreated, &[&k.id]);
+
+                let payload = CustomerCreatedPayload {
+                    project_id: customer.project_id.clone(),
+                    customer_id: k.id,
+                };
 
                 broadcast(
                     db,
-                    svix,
+                    validation,
+                    source,
                     customer.project_id,
-                    FilterType::CustomerCreated,
-                    payload,
+                    event_id,
+                    payload.into(),
                 )
                 .await
             },
@@ -54,77 +119,102 @@ pub async fn process(msg: Services, db: Connection, svix: Svix) -> Result<()> {
             Some(nft_events::Event::DropCreated(drop_creation)) => {
                 let creation_status = CreationStatus::from_i32(drop_creation.status)
                     .context("no creation status on the message")?;
+                let event_id = event_id(FilterType::DropCreated, &[
+                    &k.id,
+                    creation_status.as_str_name(),
+                    &occurrence(source),
+                ]);
+
+                let payload = DropCreatedPayload {
+                    project_id: k.project_id.clone(),
+                    drop_id: k.id,
+                    creation_status,
+                };
 
-                let payload = serde_json::to_value(Event {
-                    event_type: FilterType::DropCreated.format(),
-                    payload: EventPayload::DropCreated(DropCreatedPayload {
-                        project_id: k.project_id.clone(),
-                        drop_id: k.id,
-                        creation_status: creation_status.as_str_name().to_string(),
-                    }),
-                })?;
-
-                broadcast(db, svix, k.project_id, FilterType::DropCreated, payload).await
+                broadcast(
+                    db,
+                    validation,
+                    source,
+                    k.project_id,
+                    event_id,
+                    payload.into(),
+                )
+                .await
             },
             Some(nft_events::Event::DropMinted(mint_creation)) => {
                 let creation_status = CreationStatus::from_i32(mint_creation.status)
                     .context("no creation status on the message")?;
+                let event_id = event_id(FilterType::DropMinted, &[
+                    &k.id,
+                    creation_status.as_str_name(),
+                    &occurrence(source),
+                ]);
+
+                let payload = DropMintedPayload {
+                    project_id: k.project_id.clone(),
+                    drop_id: mint_creation.drop_id,
+                    mint_id: k.id,
+                    creation_status,
+                };
 
-                let payload = serde_json::to_value(Event {
-                    event_type: FilterType::DropMinted.format(),
-                    payload: EventPayload::DropMinted(DropMintedPayload {
-                        project_id: k.project_id.clone(),
-                        drop_id: mint_creation.drop_id,
-                        mint_id: k.id,
-                        creation_status: creation_status.as_str_name().to_string(),
-                    }),
-                })?;
-
-                broadcast(db, svix, k.project_id, FilterType::DropMinted, payload).await
+                broadcast(
+                    db,
+                    validation,
+                    source,
+                    k.project_id,
+                    event_id,
+                    payload.into(),
+                )
+                .await
             },
             Some(nft_events::Event::MintedToCollection(payload)) => {
                 let creation_status = CreationStatus::from_i32(payload.status)
                     .context("no creation status on the message")?;
-
-                let payload = serde_json::to_value(Event {
-                    event_type: FilterType::MintedToCollection.format(),
-                    payload: EventPayload::MintedToCollection(MintedToCollectionPayload {
-                        mint_id: k.id,
-                        collection_id: payload.collection_id,
-                        project_id: k.project_id.clone(),
-                        status: creation_status.as_str_name().to_string(),
-                    }),
-                })?;
+                let event_id = event_id(FilterType::MintedToCollection, &[
+                    &k.id,
+                    creation_status.as_str_name(),
+                    &occurrence(source),
+                ]);
+
+                let payload = MintedToCollectionPayload {
+                    mint_id: k.id,
+                    collection_id: payload.collection_id,
+                    project_id: k.project_id.clone(),
+                    status: creation_status,
+                };
 
                 broadcast(
                     db,
-                    svix,
+                    validation,
+                    source,
                     k.project_id,
-                    FilterType::MintedToCollection,
-                    payload,
+                    event_id,
+                    payload.into(),
                 )
                 .await
             },
-
             Some(nft_events::Event::CollectionCreated(payload)) => {
                 let creation_status = CreationStatus::from_i32(payload.status)
                     .context("no creation status on the message")?;
-
-                let payload = serde_json::to_value(Event {
-                    event_type: FilterType::CollectionCreated.format(),
-                    payload: EventPayload::CollectionCreated(CollectionCreatedPayload {
-                        collection_id: k.id,
-                        project_id: k.project_id.clone(),
-                        status: creation_status.as_str_name().to_string(),
-                    }),
-                })?;
+                let event_id = event_id(FilterType::CollectionCreated, &[
+                    &k.id,
+                    creation_status.as_str_name(),
+                    &occurrence(source),
+                ]);
+
+                let payload = CollectionCreatedPayload {
+                    collection_id: k.id,
+                    project_id: k.project_id.clone(),
+                    status: creation_status,
+                };
 
                 broadcast(
                     db,
-                    svix,
+                    validation,
+                    source,
                     k.project_id,
-                    FilterType::CollectionCreated,
-                    payload,
+                    event_id,
+                    payload.into(),
                 )
                 .await
             },
@@ -132,80 +222,83 @@ pub async fn process(msg: Services, db: Connection, svix: Svix) -> Result<()> {
         },
         Services::Treasuries(k, e) => match e.event {
             Some(treasury_events::Event::CustomerTreasuryCreated(customer)) => {
-                let payload = serde_json::to_value(Event {
-                    event_type: FilterType::CustomerTreasuryCreated.format(),
-                    payload: EventPayload::CustomerTreasuryCreated(
-                        CustomerTreasuryCreatedPayload {
-                            project_id: customer.project_id.clone(),
-                            customer_id: customer.customer_id,
-                            treasury_id: k.id,
-                        },
-                    ),
-                })?;
+                let event_id = event_id(FilterType::CustomerTreasuryCreated, &[&k.id]);
+
+                let payload = CustomerTreasuryCreatedPayload {
+                    project_id: customer.project_id.clone(),
+                    customer_id: customer.customer_id,
+                    treasury_id: k.id,
+                };
 
                 broadcast(
                     db,
-                    svix,
+                    validation,
+                    source,
                     customer.project_id,
-                    FilterType::CustomerTreasuryCreated,
-                    payload,
+                    event_id,
+                    payload.into(),
                 )
                 .await
             },
             Some(treasury_events::Event::CustomerWalletCreated(customer)) => {
-                let payload = serde_json::to_value(Event {
-                    event_type: FilterType::CustomerWalletCreated.format(),
-                    payload: EventPayload::CustomerWalletCreated(CustomerWalletCreatedPayload {
-                        project_id: customer.project_id.clone(),
-                        customer_id: customer.customer_id,
-                        treasury_id: k.id,
-                    }),
-                })?;
+                let event_id = event_id(FilterType::CustomerWalletCreated, &[&k.id]);
+
+                let payload = CustomerWalletCreatedPayload {
+                    project_id: customer.project_id.clone(),
+                    customer_id: customer.customer_id,
+                    treasury_id: k.id,
+                };
 
                 broadcast(
                     db,
-                    svix,
+                    validation,
+                    source,
                     customer.project_id,
-                    FilterType::CustomerWalletCreated,
-                    payload,
+                    event_id,
+                    payload.into(),
                 )
                 .await
             },
             Some(treasury_events::Event::ProjectWalletCreated(p)) => {
-                let payload = serde_json::to_value(Event {
-                    event_type: FilterType::ProjectWalletCreated.format(),
-                    payload: EventPayload::ProjectWalletCreated(ProjectWalletCreatedPayload {
-                        treasury_id: k.id,
-                        project_id: p.project_id.clone(),
-                    }),
-                })?;
+                let event_id = event_id(FilterType::ProjectWalletCreated, &[&k.id]);
+
+                let payload = ProjectWalletCreatedPayload {
+                    treasury_id: k.id,
+                    project_id: p.project_id.clone(),
+                };
 
                 broadcast(
                     db,
-                    svix,
+                    validation,
+                    source,
                     p.project_id,
-                    FilterType::ProjectWalletCreated,
-                    payload,
+                    event_id,
+                    payload.into(),
                 )
                 .await
             },
             Some(treasury_events::Event::MintTransfered(payload)) => {
-                let event_payload = serde_json::to_value(Event {
-                    event_type: FilterType::MintTransfered.format(),
-                    payload: EventPayload::MintTransfered(MintTransferedPayload {
-                        project_id: payload.project_id.clone(),
-                        sender: payload.sender,
-                        recipient: payload.recipient,
-                        mint_id: k.id,
-                    }),
-                })?;
+                let event_id = event_id(FilterType::MintTransfered, &[
+                    &k.id,
+                    &payload.sender,
+                    &payload.recipient,
+                    &occurrence(source),
+                ]);
+
+                let event_payload = MintTransferedPayload {
+                    project_id: payload.project_id.clone(),
+                    sender: payload.sender,
+                    recipient: payload.recipient,
+                    mint_id: k.id,
+                };
 
                 broadcast(
                     db,
-                    svix,
+                    validation,
+                    source,
                     payload.project_id,
-                    FilterType::MintTransfered,
-                    event_payload,
+                    event_id,
+                    event_payload.into(),
                 )
                 .await
             },
@@ -214,157 +307,485 @@ pub async fn process(msg: Services, db: Connection, svix: Svix) -> Result<()> {
     }
 }
 
-async fn create_svix_application(
-    db: Connection,
-    svix: Svix,
-    k: OrganizationEventKey,
-    org: Organization,
-) -> Result<()> {
-    let app = svix
-        .application()
-        .create(
-            ApplicationIn {
-                name: org.name,
-                rate_limit: None,
-                uid: Some(k.id),
-            },
-            None,
-        )
-        .await
-        .context("failed to create svix application for org")?;
-
-    let org_id = Uuid::parse_str(&org.id)?;
-
-    let org_app = organization_applications::ActiveModel {
-        svix_app_id: Set(app.id),
-        organization_id: Set(org_id),
+async fn add_organization_member(db: &Connection, org_id: &str, user_id: &str) -> Result<()> {
+    let member = organization_members::ActiveModel {
+        organization_id: Set(Uuid::parse_str(org_id)?),
+        user_id: Set(Uuid::parse_str(user_id)?),
+        deactivated_at: Set(None),
         ..Default::default()
     };
 
-    org_app.insert(db.get()).await?;
+    organization_members::Entity::insert(member)
+        .on_conflict(
+            OnConflict::columns([
+                organization_members::Column::OrganizationId,
+                organization_members::Column::UserId,
+            ])
+            .update_column(organization_members::Column::DeactivatedAt)
+            .to_owned(),
+        )
+        .exec(db.get())
+        .await?;
 
     Ok(())
 }
 
-async fn broadcast(
-    db: Connection,
-    svix: Svix,
-    project_id: String,
-    event_type: FilterType,
-    payload: Value,
+async fn deactivate_organization_member(
+    db: &Connection,
+    org_id: &str,
+    user_id: &str,
 ) -> Result<()> {
-    let message = MessageIn {
-        channels: Some(vec![project_id.clone()]),
-        event_id: None,
-        event_type: event_type.format(),
-        payload,
-        payload_retention_period: None,
-    };
+    organization_members::Entity::update_many()
+        .col_expr(
+            organization_members::Column::DeactivatedAt,
+            Expr::value(Utc::now().naive_utc()),
+        )
+        .filter(organization_members::Column::OrganizationId.eq(Uuid::parse_str(org_id)?))
+        .filter(organization_members::Column::UserId.eq(Uuid::parse_str(user_id)?))
+        .exec(db.get())
+        .await?;
+
+    Ok(())
+}
 
+/// Unsubscribes every webhook from a project that no longer exists. Endpoints left without any project are disabled.
+/// A webhook only leaves the project once its endpoint is updated, so a redelivered event retries the endpoints that
+/// failed to be updated.
+async fn remove_project(db: Connection, delivery: Delivery, project_id: String) -> Result<()> {
     let project_id = Uuid::parse_str(&project_id)?;
 
-    let app = organization_applications::Entity::find()
+    let webhooks = webhooks::Entity::find()
         .join(
             JoinType::InnerJoin,
-            organization_applications::Relation::Webhooks.def(),
+            webhooks::Relation::WebhookProjects.def(),
         )
+        .select_also(organization_applications::Entity)
         .join(
             JoinType::InnerJoin,
-            webhooks::Relation::WebhookProjects.def(),
+            webhooks::Relation::OrganizationApplications.def(),
         )
         .filter(webhook_projects::Column::ProjectId.eq(project_id))
+        .all(db.get())
+        .await?;
+
+    for (webhook, organization_application) in webhooks {
+        let organization_application =
+            organization_application.context("organization_application not found")?;
+
+        let endpoint = delivery
+            .get_endpoint(
+                organization_application.svix_app_id.clone(),
+                webhook.endpoint_id.clone(),
+            )
+            .await?;
+
+        let api_version = ApiVersion::of(&webhook);
+        let project_channel = api_version.channel(project_id);
+        let organization_channel = api_version.channel(webhook.organization_id);
+
+        let channels: Vec<String> = endpoint
+            .channels
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|channel| *channel != project_channel)
+            .collect();
+
+        let disabled = !channels
+            .iter()
+            .any(|channel| *channel != organization_channel);
+
+        let update_endpoint = EndpointUpdate {
+            channels: Some(channels),
+            filter_types: endpoint.filter_types,
+            version: endpoint.version.add(1),
+            description: endpoint.description,
+            disabled: Some(disabled || endpoint.disabled.unwrap_or_default()),
+            rate_limit: endpoint.rate_limit,
+            url: endpoint.url,
+            uid: endpoint.uid,
+        };
+
+        let endpoint = delivery
+            .update_endpoint(
+                organization_application.svix_app_id,
+                webhook.endpoint_id.clone(),
+                update_endpoint,
+            )
+            .await
+            .context("failed to unsubscribe endpoint from project")?;
+
+        let webhook_id = webhook.id;
+        let tx = db.get().begin().await?;
+
+        let mut active_webhook: webhooks::ActiveModel = webhook.into();
+        endpoint_cache::store(&mut active_webhook, &endpoint);
+
+        active_webhook.update(&tx).await?;
+
+        webhook_projects::Entity::delete_many()
+            .filter(webhook_projects::Column::WebhookId.eq(webhook_id))
+            .filter(webhook_projects::Column::ProjectId.eq(project_id))
+            .exec(&tx)
+            .await?;
+
+        tx.commit().await?;
+    }
+
+    Ok(())
+}
+
+/// Deletes the application of an organization that no longer exists, along with its endpoints and webhooks.
+/// The deletion of every webhook is published on behalf of `user_id`, as when the webhooks are deleted one by one.
+async fn remove_organization(
+    db: Connection,
+    delivery: Delivery,
+    producer: EventsProducer,
+    organization_id: String,
+    user_id: String,
+) -> Result<()> {
+    let organization_id = Uuid::parse_str(&organization_id)?;
+
+    let Some(organization_application) = organization_applications::Entity::find()
+        .filter(organization_applications::Column::OrganizationId.eq(organization_id))
         .one(db.get())
         .await?
-        .context("failed to get svix app_id")?;
+    else {
+        return Ok(());
+    };
+
+    let webhooks = webhooks::Entity::find()
+        .filter(webhooks::Column::OrganizationId.eq(organization_id))
+        .all(db.get())
+        .await?;
 
-    svix.message()
-        .create(app.svix_app_id, message, None)
+    // the application is already gone when the event is redelivered after a failure to publish the deletions
+    delivery
+        .delete_application(organization_application.svix_app_id.clone())
         .await
-        .context("failed to broadcast message")?;
+        .context("failed to delete application for org")?;
+
+    for webhook in webhooks {
+        let event = WebhookEvents {
+            event: Some(webhook_events::Event::Deleted(proto::Webhook {
+                organization_id: webhook.organization_id.to_string(),
+                endpoint_id: webhook.endpoint_id,
+            })),
+        };
+
+        let key = WebhookEventKey {
+            id: webhook.id.to_string(),
+            user_id: user_id.clone(),
+        };
+
+        producer
+            .send(&event, &key)
+            .await
+            .context("failed to publish webhook deletion")?;
+    }
+
+    // webhooks and their projects are removed by the cascading foreign keys, once their deletions are published
+    // so that a redelivered event publishes them again
+    organization_application.delete(db.get()).await?;
 
     Ok(())
 }
 
-#[derive(Serialize)]
-pub struct Event {
-    event_type: String,
-    payload: EventPayload,
+/// Builds the event ID of a message from the event type and the parts of the Kafka event identifying it,
+/// so that a redelivered Kafka event produces the same ID and is not sent twice.
+fn event_id(event_type: FilterType, parts: &[&str]) -> String {
+    [event_type.format().as_str()]
+        .iter()
+        .chain(parts)
+        .copied()
+        .collect::<Vec<_>>()
+        .join(".")
 }
 
-#[derive(Serialize)]
-#[serde(untagged)]
-pub enum EventPayload {
-    CustomerCreated(CustomerCreatedPayload),
-    CustomerTreasuryCreated(CustomerTreasuryCreatedPayload),
-    CustomerWalletCreated(CustomerWalletCreatedPayload),
-    ProjectWalletCreated(ProjectWalletCreatedPayload),
-    DropCreated(DropCreatedPayload),
-    DropMinted(DropMintedPayload),
-    MintTransfered(MintTransferedPayload),
-    CollectionCreated(CollectionCreatedPayload),
-    MintedToCollection(MintedToCollectionPayload),
+/// Identifies the Kafka message an event was read from, for the events whose key and status can recur, e.g. a drop
+/// failing to be created twice. A redelivered message keeps its partition and offset.
+fn occurrence(source: &Source) -> String {
+    format!("{}-{}", source.partition, source.offset)
 }
 
-#[derive(Serialize)]
-pub struct CollectionCreatedPayload {
-    collection_id: String,
-    project_id: String,
-    status: String,
+/// Sends a message to every webhook of an organization subscribed to the event type of the payload, regardless of
+/// their projects.
+async fn broadcast_organization(
+    db: Connection,
+    validation: PayloadValidation,
+    source: &Source,
+    organization_id: String,
+    event_id: String,
+    payload: EventPayload,
+) -> Result<()> {
+    let organization_id = Uuid::parse_str(&organization_id)?;
+
+    let subscribers = webhooks::Entity::find()
+        .select_also(organization_applications::Entity)
+        .join(
+            JoinType::InnerJoin,
+            webhooks::Relation::OrganizationApplications.def(),
+        )
+        .filter(webhooks::Column::OrganizationId.eq(organization_id))
+        .all(db.get())
+        .await?;
+
+    publish(
+        db,
+        validation,
+        source,
+        organization_id,
+        event_id,
+        payload,
+        subscribers,
+    )
+    .await
 }
 
-#[derive(Serialize)]
-pub struct MintedToCollectionPayload {
-    mint_id: String,
-    collection_id: String,
+/// Sends a message to the webhooks subscribed to the project.
+async fn broadcast(
+    db: Connection,
+    validation: PayloadValidation,
+    source: &Source,
     project_id: String,
-    status: String,
+    event_id: String,
+    payload: EventPayload,
+) -> Result<()> {
+    let project_id = Uuid::parse_str(&project_id)?;
+
+    let subscribers = webhooks::Entity::find()
+        .join(
+            JoinType::InnerJoin,
+            webhooks::Relation::WebhookProjects.def(),
+        )
+        .select_also(organization_applications::Entity)
+        .join(
+            JoinType::InnerJoin,
+            webhooks::Relation::OrganizationApplications.def(),
+        )
+        .filter(webhook_projects::Column::ProjectId.eq(project_id))
+        .all(db.get())
+        .await?;
+
+    publish(
+        db,
+        validation,
+        source,
+        project_id,
+        event_id,
+        payload,
+        subscribers,
+    )
+    .await
 }
 
-#[derive(Serialize)]
-pub struct CustomerCreatedPayload {
-    customer_id: String,
-    project_id: String,
+/// Enqueues a message for the application of the subscribed webhooks on the channel of `channel_id`, rendered once
+/// for every API version of the webhooks listening to the event type of the payload.
+/// The message is skipped when there are no webhooks or none of them listens to the event type.
+async fn publish(
+    db: Connection,
+    validation: PayloadValidation,
+    source: &Source,
+    channel_id: Uuid,
+    event_id: String,
+    payload: EventPayload,
+    subscribers: Vec<(webhooks::Model, Option<organization_applications::Model>)>,
+) -> Result<()> {
+    let event_type = payload.event_type();
+
+    // the webhooks of a project or organization all belong to the same organization application
+    let Some((organization_id, svix_app_id)) = subscribers
+        .iter()
+        .find_map(|(webhook, app)| app.as_ref().map(|app| (webhook.organization_id, app)))
+        .map(|(organization_id, app)| (organization_id, app.svix_app_id.clone()))
+    else {
+        debug!(
+            event_type = event_type.format(),
+            "no webhooks subscribed, skipping event"
+        );
+        skip(event_type, "no_webhooks");
+
+        return Ok(());
+    };
+
+    let api_versions: BTreeSet<ApiVersion> = subscribers
+        .iter()
+        .filter(|(webhook, _)| subscribes_to(webhook, event_type))
+        .map(|(webhook, _)| ApiVersion::of(webhook))
+        .collect();
+
+    if api_versions.is_empty() {
+        debug!(
+            event_type = event_type.format(),
+            "no webhooks subscribed to the event type, skipping event"
+        );
+        skip(event_type, "no_event_type_subscribers");
+
+        return Ok(());
+    }
+
+    // every version is rendered and validated before any is enqueued, so a rejected event is sent to no webhook
+    let mut messages = Vec::with_capacity(api_versions.len());
+
+    for api_version in api_versions {
+        // the first version keeps the event IDs messages had before API versions existed
+        let event_id = match api_version {
+            ApiVersion::V1 => event_id.clone(),
+            _ => format!("{event_id}.v{}", api_version.number()),
+        };
+
+        let envelope = Envelope {
+            id: event_id.clone(),
+            organization_id,
+            // the time of the Kafka message stays the same when the event is re-driven or consumed again
+            created_at: source.timestamp,
+            source: Some(source.clone()),
+        };
+
+        let payload = render(payload.clone(), api_version, envelope)?;
+
+        validation.check(event_type, api_version, &payload)?;
+
+        messages.push(MessageIn {
+            channels: Some(vec![api_version.channel(channel_id)]),
+            event_id: Some(event_id),
+            event_type: event_type.format(),
+            payload,
+        });
+    }
+
+    let tx = db.get().begin().await?;
+
+    for message in messages {
+        let enqueued = outbox::enqueue(&tx, svix_app_id.clone(), &message)
+            .await
+            .context("failed to broadcast message")?;
+
+        if !enqueued {
+            debug!(event_id = ?message.event_id, "skipping already broadcast event");
+            skip(event_type, "duplicate");
+        }
+    }
+
+    tx.commit().await?;
+
+    Ok(())
 }
 
-#[derive(Serialize)]
-pub struct CustomerTreasuryCreatedPayload {
-    treasury_id: String,
-    project_id: String,
-    customer_id: String,
+/// Whether the webhook listens to `event_type`. Webhooks created before their filter types were stored are assumed to.
+fn subscribes_to(webhook: &webhooks::Model, event_type: FilterType) -> bool {
+    let Some(filter_types) = webhook.filter_types.as_ref().and_then(Value::as_array) else {
+        return true;
+    };
+
+    let event_type = event_type.format();
+
+    filter_types
+        .iter()
+        .any(|filter_type| filter_type.as_str() == Some(event_type.as_str()))
 }
 
-#[derive(Serialize)]
-pub struct CustomerWalletCreatedPayload {
-    treasury_id: String,
-    project_id: String,
-    customer_id: String,
+fn skip(event_type: FilterType, reason: &str) {
+    metrics::BROADCASTS_SKIPPED
+        .with_label_values(&[&event_type.format(), reason])
+        .inc();
 }
 
-#[derive(Serialize)]
-pub struct ProjectWalletCreatedPayload {
-    treasury_id: String,
-    project_id: String,
+/// Builds a sample message body for `event_type` in an API version with placeholder IDs, used to test the webhook
+/// endpoints of an organization. Test messages have a random ID and no source.
+///
+/// # Errors
+/// This function fails if the payload cannot be serialized.
+pub fn example_payload(
+    event_type: FilterType,
+    api_version: ApiVersion,
+    organization_id: Uuid,
+) -> Result<Value> {
+    let envelope = Envelope {
+        id: Uuid::new_v4().to_string(),
+        organization_id,
+        created_at: Utc::now(),
+        source: None,
+    };
+
+    render(event_type.example(), api_version, envelope)
 }
 
-#[derive(Serialize)]
-pub struct DropCreatedPayload {
-    drop_id: String,
-    project_id: String,
-    creation_status: String,
+/// Serializes the message body of a payload in the shape of an API version. Payloads left unchanged by a version
+/// are serialized as in the previous one.
+///
+/// # Errors
+/// This function fails if the payload cannot be serialized.
+pub fn render(payload: EventPayload, api_version: ApiVersion, envelope: Envelope) -> Result<Value> {
+    let event_type = payload.event_type();
+
+    let body = match (api_version, payload) {
+        (ApiVersion::V2, EventPayload::DropCreated(payload)) => serde_json::to_value(Event::new(
+            event_type,
+            api_version,
+            envelope,
+            DropCreatedPayloadV2::from(payload),
+        )),
+        (ApiVersion::V2, EventPayload::DropMinted(payload)) => serde_json::to_value(Event::new(
+            event_type,
+            api_version,
+            envelope,
+            DropMintedPayloadV2::from(payload),
+        )),
+        (_, payload) => {
+            serde_json::to_value(Event::new(event_type, api_version, envelope, payload))
+        },
+    }?;
+
+    Ok(body)
 }
 
-#[derive(Serialize)]
-pub struct DropMintedPayload {
-    mint_id: String,
-    project_id: String,
-    drop_id: String,
-    creation_status: String,
+/// What the body of a message reports about the event it was built from, besides its payload.
+#[derive(Debug, Clone)]
+pub struct Envelope {
+    /// ID of the message, the same for every delivery of the event in an API version
+    pub id: String,
+    pub organization_id: Uuid,
+    /// When the event occurred, the time of its Kafka message. Only test messages use the time they are sent
+    pub created_at: DateTime<Utc>,
+    /// The Kafka message of the event, if it was consumed from one
+    pub source: Option<Source>,
 }
 
+/// The body of the messages sent to webhooks.
 #[derive(Serialize)]
-pub struct MintTransferedPayload {
-    project_id: String,
-    sender: String,
-    recipient: String,
-    mint_id: String,
+pub struct Event<P> {
+    id: String,
+    event_type: String,
+    api_version: i32,
+    organization_id: String,
+    created_at: String,
+    source: Option<Source>,
+    payload: P,
+}
+
+impl<P: Serialize> Event<P> {
+    #[must_use]
+    pub fn new(
+        event_type: FilterType,
+        api_version: ApiVersion,
+        envelope: Envelope,
+        payload: P,
+    ) -> Self {
+        let Envelope {
+            id,
+            organization_id,
+            created_at,
+            source,
+        } = envelope;
+
+        Self {
+            id,
+            event_type: event_type.format(),
+            api_version: api_version.number(),
+            organization_id: organization_id.to_string(),
+            created_at: created_at.to_rfc3339_opts(SecondsFormat::Millis, true),
+            source,
+            payload,
+        }
+    }
 }
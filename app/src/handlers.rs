@@ -1,16 +1,26 @@
 use async_graphql::http::{playground_source, GraphQLPlaygroundConfig};
 use async_graphql_poem::{GraphQLRequest, GraphQLResponse};
+use hub_core::prelude::*;
 use poem::{
     handler,
+    http::{HeaderMap, StatusCode},
     web::{Data, Html},
-    IntoResponse, Result,
+    Error, IntoResponse, Result,
 };
 
-use crate::{AppContext, AppState, UserID};
+use crate::{
+    operational::{OperationalEvent, OperationalWebhooks},
+    AppContext, AppState, UserID,
+};
 
 #[handler]
 pub fn health() {}
 
+#[handler]
+pub fn metrics() -> Result<String> {
+    Ok(crate::metrics::render()?)
+}
+
 #[handler]
 pub fn playground() -> impl IntoResponse {
     Html(playground_source(GraphQLPlaygroundConfig::new("/graphql")))
@@ -24,16 +34,44 @@ pub async fn graphql_handler(
 ) -> Result<GraphQLResponse> {
     let UserID(user_id) = user_id;
 
-    let context = AppContext::new(state.connection.clone(), user_id, state.svix_client.clone());
+    let context = AppContext::new(state.connection.clone(), user_id);
 
     Ok(state
         .schema
         .execute(
             req.0
                 .data(context)
-                .data(state.svix_client.clone())
+                .data(state.delivery.clone())
                 .data(state.producer.clone()),
         )
         .await
         .into())
 }
+
+/// Receives the operational webhooks Svix sends when the health of an endpoint changes.
+/// Failures to record a change are answered with a server error, so Svix retries the webhook.
+#[handler]
+pub async fn svix_operational(
+    Data(operational): Data<&OperationalWebhooks>,
+    headers: &HeaderMap,
+    body: String,
+) -> Result<()> {
+    operational.verify(headers, &body).map_err(|e| {
+        warn!("rejected operational webhook: {:?}", e);
+
+        Error::from_status(StatusCode::UNAUTHORIZED)
+    })?;
+
+    let event = OperationalEvent::parse(&body)
+        .map_err(|e| Error::from_string(e.to_string(), StatusCode::BAD_REQUEST))?;
+
+    let Some(event) = event else {
+        return Ok(());
+    };
+
+    operational.process(event).await.map_err(|e| {
+        error!("failed to process operational webhook: {:?}", e);
+
+        Error::from_status(StatusCode::INTERNAL_SERVER_ERROR)
+    })
+}
@@ -2,28 +2,46 @@
 #![warn(clippy::pedantic, clippy::cargo)]
 #![allow(clippy::module_name_repetitions)]
 
+pub mod applications;
 pub mod dataloaders;
 pub mod db;
+pub mod dead_letters;
+pub mod delivery;
+pub mod endpoint_cache;
+pub mod endpoint_health;
 #[allow(clippy::pedantic)]
 pub mod entities;
+pub mod event_types;
 pub mod events;
 pub mod handlers;
+pub mod members;
+pub mod metrics;
 pub mod mutations;
 pub mod objects;
+pub mod operational;
+pub mod outbox;
+pub mod pipeline;
 pub mod queries;
+pub mod reconcile;
 pub mod svix_client;
+pub mod validation;
+
+use std::{path::PathBuf, sync::Arc};
 
 use async_graphql::{
     dataloader::DataLoader,
     extensions::{ApolloTracing, Logger},
     EmptySubscription, Schema,
 };
-use dataloaders::{WebhookLoader, WebhooksLoader};
+use dataloaders::WebhookLoader;
 use db::Connection;
+use delivery::Delivery;
+use entities::organization_members;
 use hub_core::{
     anyhow::{Error, Result},
+    chrono::{DateTime, TimeZone, Utc},
     clap,
-    consumer::RecvError,
+    consumer::{MessageGroup, RecvError},
     prelude::*,
     producer::Producer,
     tokio,
@@ -32,7 +50,8 @@ use hub_core::{
 use mutations::Mutation;
 use poem::{async_trait, FromRequest, Request, RequestBody};
 use queries::Query;
-use svix::api::Svix;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use serde::Serialize;
 
 #[allow(clippy::pedantic)]
 pub mod proto {
@@ -49,7 +68,29 @@ impl hub_core::producer::Message for proto::WebhookEvents {
     type Key = proto::WebhookEventKey;
 }
 
-#[derive(Debug)]
+/// Publishes the events of the webhooks managed by this service.
+#[async_trait]
+pub trait WebhookEventsProducer: Send + Sync {
+    /// Publishes an event keyed by the webhook it is about.
+    ///
+    /// # Errors
+    /// This function fails if the event cannot be published.
+    async fn send(&self, event: &WebhookEvents, key: &proto::WebhookEventKey) -> Result<()>;
+}
+
+#[async_trait]
+impl WebhookEventsProducer for Producer<WebhookEvents> {
+    async fn send(&self, event: &WebhookEvents, key: &proto::WebhookEventKey) -> Result<()> {
+        Producer::send(self, Some(event), Some(key)).await?;
+
+        Ok(())
+    }
+}
+
+/// The producer of webhook events shared by the GraphQL mutations and the endpoint health tracking.
+pub type EventsProducer = Arc<dyn WebhookEventsProducer>;
+
+#[derive(Debug, Clone)]
 pub enum Services {
     Organizations(proto::OrganizationEventKey, proto::OrganizationEvents),
     Customers(proto::CustomerEventKey, proto::CustomerEvents),
@@ -57,15 +98,12 @@ pub enum Services {
     Nfts(proto::NftEventKey, proto::NftEvents),
 }
 
-impl hub_core::consumer::MessageGroup for Services {
-    const REQUESTED_TOPICS: &'static [&'static str] = &["hub-orgs", "hub-customers", "hub-nfts"];
-
-    fn from_message<M: hub_core::consumer::Message>(msg: &M) -> Result<Self, RecvError> {
-        let topic = msg.topic();
-        let key = msg.key().ok_or(RecvError::MissingKey)?;
-        let val = msg.payload().ok_or(RecvError::MissingPayload)?;
-        info!(topic, ?key, ?val);
-
+impl Services {
+    /// Decodes the key and payload of a message consumed from `topic`.
+    ///
+    /// # Errors
+    /// This function fails if the topic is unknown or the key or payload cannot be decoded.
+    pub fn decode(topic: &str, key: &[u8], val: &[u8]) -> Result<Self, RecvError> {
         match topic {
             "hub-orgs" => {
                 let key = proto::OrganizationEventKey::decode(key)?;
@@ -94,6 +132,99 @@ impl hub_core::consumer::MessageGroup for Services {
             t => Err(RecvError::BadTopic(t.into())),
         }
     }
+
+    /// Encodes the event back into the topic, key and payload it was consumed from.
+    #[must_use]
+    pub fn encode(&self) -> (&'static str, Vec<u8>, Vec<u8>) {
+        match self {
+            Services::Organizations(k, e) => ("hub-orgs", k.encode_to_vec(), e.encode_to_vec()),
+            Services::Customers(k, e) => ("hub-customers", k.encode_to_vec(), e.encode_to_vec()),
+            Services::Treasuries(k, e) => ("hub-treasuries", k.encode_to_vec(), e.encode_to_vec()),
+            Services::Nfts(k, e) => ("hub-nfts", k.encode_to_vec(), e.encode_to_vec()),
+        }
+    }
+
+    /// Events sharing an ordering key are processed one at a time, in the order they were consumed.
+    /// This is the project ID of the event when it has one.
+    #[must_use]
+    pub fn ordering_key(&self) -> &str {
+        match self {
+            Services::Organizations(k, _) => &k.id,
+            Services::Customers(k, e) => match &e.event {
+                Some(proto::customer_events::Event::Created(customer)) => &customer.project_id,
+                _ => &k.id,
+            },
+            Services::Treasuries(k, e) => match &e.event {
+                Some(proto::treasury_events::Event::CustomerTreasuryCreated(customer)) => {
+                    &customer.project_id
+                },
+                Some(proto::treasury_events::Event::CustomerWalletCreated(customer)) => {
+                    &customer.project_id
+                },
+                Some(proto::treasury_events::Event::ProjectWalletCreated(project)) => {
+                    &project.project_id
+                },
+                Some(proto::treasury_events::Event::MintTransfered(transfer)) => {
+                    &transfer.project_id
+                },
+                _ => &k.id,
+            },
+            Services::Nfts(k, _) => &k.project_id,
+        }
+    }
+}
+
+impl MessageGroup for Services {
+    const REQUESTED_TOPICS: &'static [&'static str] =
+        &["hub-orgs", "hub-customers", "hub-treasuries", "hub-nfts"];
+
+    fn from_message<M: hub_core::consumer::Message>(msg: &M) -> Result<Self, RecvError> {
+        let topic = msg.topic();
+        let key = msg.key().ok_or(RecvError::MissingKey)?;
+        let val = msg.payload().ok_or(RecvError::MissingPayload)?;
+        info!(topic, ?key, ?val);
+
+        Self::decode(topic, key, val)
+    }
+}
+
+/// The Kafka message an event was consumed from, reported in the envelope of the messages it produces.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Source {
+    pub topic: String,
+    pub partition: i32,
+    pub offset: i64,
+    /// When the message was produced, reported as the `created_at` of the envelope
+    #[serde(skip)]
+    pub timestamp: DateTime<Utc>,
+}
+
+/// An event consumed from Kafka along with the message it was read from.
+#[derive(Debug)]
+pub struct Consumed {
+    pub msg: Services,
+    pub source: Source,
+}
+
+impl MessageGroup for Consumed {
+    const REQUESTED_TOPICS: &'static [&'static str] = Services::REQUESTED_TOPICS;
+
+    fn from_message<M: hub_core::consumer::Message>(msg: &M) -> Result<Self, RecvError> {
+        Ok(Self {
+            msg: Services::from_message(msg)?,
+            source: Source {
+                topic: msg.topic().to_string(),
+                partition: msg.partition(),
+                offset: msg.offset(),
+                // brokers set the timestamp unless the topic is configured otherwise
+                timestamp: msg
+                    .timestamp()
+                    .to_millis()
+                    .and_then(|millis| Utc.timestamp_millis_opt(millis).single())
+                    .unwrap_or_else(Utc::now),
+            },
+        })
+    }
 }
 
 #[derive(Debug, clap::Args)]
@@ -107,6 +238,59 @@ pub struct Args {
 
     #[command(flatten)]
     pub svix: svix_client::SvixArgs,
+
+    #[command(flatten)]
+    pub delivery: delivery::DeliveryArgs,
+
+    #[command(flatten)]
+    pub outbox: outbox::OutboxArgs,
+
+    #[command(flatten)]
+    pub pipeline: pipeline::PipelineArgs,
+
+    #[command(flatten)]
+    pub endpoint_cache: endpoint_cache::EndpointCacheArgs,
+
+    #[command(flatten)]
+    pub reconcile: reconcile::ReconcileArgs,
+
+    #[command(flatten)]
+    pub operational: operational::OperationalArgs,
+
+    #[command(flatten)]
+    pub validation: validation::ValidationArgs,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum Command {
+    /// Processes events stored as dead letters again, then exits
+    RedriveDeadLetters {
+        /// IDs of the dead letters to re-drive, all of those not yet re-driven when omitted
+        ids: Vec<Uuid>,
+    },
+    /// Creates the missing Svix applications of organizations created before the consumer existed, then exits
+    BackfillApplications {
+        /// IDs of the organizations to backfill
+        #[arg(required = true)]
+        organization_ids: Vec<Uuid>,
+    },
+    /// Subscribes the endpoints created before organization-wide events to the channel of their organization,
+    /// then exits
+    BackfillChannels,
+    /// Adds the members of organizations that existed before memberships were tracked, then exits
+    BackfillMembers {
+        /// CSV file of `organization_id,user_id` lines listing the active members of hub-orgs
+        file: PathBuf,
+    },
+    /// Reports the drift between the webhooks in Postgres and the delivery backend, then exits
+    Reconcile {
+        /// Repair the drift found instead of only reporting it
+        #[arg(long)]
+        repair: bool,
+    },
 }
 
 pub type AppSchema = Schema<Query, Mutation, EmptySubscription>;
@@ -141,8 +325,8 @@ impl<'a> FromRequest<'a> for UserID {
 pub struct AppState {
     pub schema: AppSchema,
     pub connection: Connection,
-    pub svix_client: Svix,
-    pub producer: Producer<WebhookEvents>,
+    pub delivery: Delivery,
+    pub producer: EventsProducer,
 }
 
 impl AppState {
@@ -150,13 +334,13 @@ impl AppState {
     pub fn new(
         schema: AppSchema,
         connection: Connection,
-        svix_client: Svix,
-        producer: Producer<WebhookEvents>,
+        delivery: Delivery,
+        producer: EventsProducer,
     ) -> Self {
         Self {
             schema,
             connection,
-            svix_client,
+            delivery,
             producer,
         }
     }
@@ -165,24 +349,43 @@ impl AppState {
 pub struct AppContext {
     pub db: Connection,
     pub user_id: Option<Uuid>,
-    pub organization_webhooks_loader: DataLoader<WebhooksLoader>,
     pub webhook_loader: DataLoader<WebhookLoader>,
 }
 
 impl AppContext {
     #[must_use]
-    pub fn new(db: Connection, user_id: Option<Uuid>, svix: Svix) -> Self {
-        let organization_webhooks_loader =
-            DataLoader::new(WebhooksLoader::new(db.clone(), svix.clone()), tokio::spawn);
-        let webhook_loader = DataLoader::new(WebhookLoader::new(db.clone(), svix), tokio::spawn);
+    pub fn new(db: Connection, user_id: Option<Uuid>) -> Self {
+        let webhook_loader = DataLoader::new(WebhookLoader::new(db.clone()), tokio::spawn);
 
         Self {
             db,
             user_id,
-            organization_webhooks_loader,
             webhook_loader,
         }
     }
+
+    /// Checks that the user making the request is an active member of the organization.
+    ///
+    /// # Errors
+    /// This function fails if the X-USER-ID header is missing or the user is not a member of the organization.
+    pub async fn authorize(&self, organization_id: Uuid) -> async_graphql::Result<Uuid> {
+        let user_id = self
+            .user_id
+            .ok_or_else(|| async_graphql::Error::new("X-USER-ID header not found"))?;
+
+        let member = organization_members::Entity::find_by_id((organization_id, user_id))
+            .filter(organization_members::Column::DeactivatedAt.is_null())
+            .one(self.db.get())
+            .await?;
+
+        if member.is_none() {
+            return Err(async_graphql::Error::new(format!(
+                "User {user_id} is not a member of organization {organization_id}"
+            )));
+        }
+
+        Ok(user_id)
+    }
 }
 
 /// Builds the GraphQL Schema, attaching the Database to the context
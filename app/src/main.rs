@@ -1,17 +1,30 @@
 //!
 
+use std::{sync::Arc, time::Duration};
+
 use async_std::stream::StreamExt;
 use holaplex_hub_webhooks::{
-    build_schema,
+    applications, build_schema,
     db::Connection,
-    events,
-    handlers::{graphql_handler, health, playground},
-    proto, AppState, Args, Services,
+    dead_letters,
+    delivery::{
+        native::{self, NativeDelivery},
+        Delivery, DeliveryArgs, DeliveryBackend,
+    },
+    endpoint_cache,
+    handlers::{graphql_handler, health, metrics, playground, svix_operational},
+    members,
+    operational::{OperationalArgs, OperationalWebhooks},
+    outbox,
+    pipeline::Pipeline,
+    proto, reconcile, svix_client,
+    validation::ValidationArgs,
+    AppState, Args, Command, Consumed, EventsProducer,
 };
 use hub_core::{
     anyhow::Context as AnyhowContext,
-    tokio::{self, task},
-    tracing::{info, warn},
+    tokio,
+    tracing::{error, info, warn},
 };
 use poem::{get, listener::TcpListener, middleware::AddData, post, EndpointExt, Route, Server};
 
@@ -21,53 +34,212 @@ pub fn main() {
     };
 
     hub_core::run(opts, |common, args| {
-        let Args { port, db, svix } = args;
+        let Args {
+            port,
+            db,
+            svix,
+            delivery: delivery_args,
+            outbox: outbox_args,
+            pipeline: pipeline_args,
+            endpoint_cache: endpoint_cache_args,
+            reconcile: reconcile_args,
+            operational: operational_args,
+            validation: ValidationArgs { payload_validation },
+            command,
+        } = args;
 
         common.rt.block_on(async move {
             let connection = Connection::new(db)
                 .await
                 .context("failed to get database connection")?;
 
+            let DeliveryArgs {
+                delivery_backend,
+                native: native_args,
+            } = delivery_args;
+
+            let delivery: Delivery = match delivery_backend {
+                DeliveryBackend::Svix => svix.build_delivery()?,
+                DeliveryBackend::Native => Arc::new(NativeDelivery::new(connection.clone())),
+            };
+
+            svix_client::create_event_types(delivery.as_ref()).await?;
+
+            let producer: EventsProducer = Arc::new(
+                common
+                    .producer_cfg
+                    .clone()
+                    .build::<proto::WebhookEvents>()
+                    .await?,
+            );
+
+            match command {
+                Some(Command::RedriveDeadLetters { ids }) => {
+                    let redriven = dead_letters::redrive(
+                        &connection,
+                        &delivery,
+                        &producer,
+                        payload_validation,
+                        &ids,
+                    )
+                    .await?;
+                    info!(redriven, "re-drove dead letters");
+
+                    return Ok(());
+                },
+                Some(Command::BackfillApplications { organization_ids }) => {
+                    let created =
+                        applications::backfill(&connection, &delivery, &organization_ids).await?;
+                    info!(created, "backfilled organization applications");
+
+                    return Ok(());
+                },
+                Some(Command::BackfillChannels) => {
+                    let subscribed =
+                        reconcile::backfill_organization_channels(&connection, &delivery).await?;
+                    info!(subscribed, "backfilled organization channels");
+
+                    return Ok(());
+                },
+                Some(Command::BackfillMembers { file }) => {
+                    let added = members::backfill(&connection, &file).await?;
+                    info!(added, "backfilled organization members");
+
+                    return Ok(());
+                },
+                Some(Command::Reconcile { repair }) => {
+                    let drifts = reconcile::reconcile(
+                        &connection,
+                        &delivery,
+                        Duration::from_secs(reconcile_args.reconcile_grace_period),
+                        repair,
+                    )
+                    .await?;
+                    info!(drifts = drifts.len(), repair, "reconciled webhooks");
+
+                    return Ok(());
+                },
+                None => (),
+            }
+
             let schema = build_schema();
-            let svix_client = svix.build_client().await?;
-            let producer = common.producer_cfg.build::<proto::WebhookEvents>().await?;
+            let dead_letter_queue = Arc::new(
+                common
+                    .producer_cfg
+                    .topic(dead_letters::TOPIC)
+                    .build::<dead_letters::DeadLetter>()
+                    .await?,
+            );
+
+            let state = AppState::new(
+                schema,
+                connection.clone(),
+                delivery.clone(),
+                producer.clone(),
+            );
 
-            let state = AppState::new(schema, connection.clone(), svix_client.clone(), producer);
+            let OperationalArgs {
+                svix_operational_secret,
+            } = operational_args;
 
-            let cons = common.consumer_cfg.build::<Services>().await?;
+            let operational = svix_operational_secret.map(|secret| {
+                OperationalWebhooks::new(connection.clone(), producer.clone(), secret)
+            });
+
+            // offsets are stored by the consumer loop once their events are processed, rather than
+            // as soon as they are read
+            let cons = common
+                .consumer_cfg
+                .set("enable.auto.offset.store", "false")
+                .build::<Consumed>()
+                .await?;
+
+            if delivery_backend == DeliveryBackend::Native {
+                tokio::spawn(native::run(
+                    connection.clone(),
+                    native_args,
+                    producer.clone(),
+                ));
+            }
+
+            tokio::spawn(outbox::run(
+                connection.clone(),
+                delivery.clone(),
+                outbox_args,
+            ));
+
+            tokio::spawn(endpoint_cache::run(
+                connection.clone(),
+                delivery.clone(),
+                endpoint_cache_args,
+            ));
+
+            tokio::spawn(reconcile::run(
+                connection.clone(),
+                delivery.clone(),
+                reconcile_args,
+            ));
+
+            let pipeline = Pipeline::new(
+                pipeline_args,
+                connection,
+                delivery,
+                producer,
+                dead_letter_queue,
+                payload_validation,
+            );
 
             tokio::spawn(async move {
-                {
-                    let mut stream = cons.stream();
-                    loop {
-                        let connection = connection.clone();
-                        let svix_client = svix_client.clone();
+                let mut stream = cons.stream();
+                let mut commit_interval = tokio::time::interval(Duration::from_secs(1));
 
-                        match stream.next().await {
+                loop {
+                    tokio::select! {
+                        msg = stream.next() => match msg {
                             Some(Ok(msg)) => {
                                 info!(?msg, "message received");
 
-                                tokio::spawn(async move {
-                                    events::process(msg, connection.clone(), svix_client).await
-                                });
-                                task::yield_now().await;
+                                if let Err(e) = pipeline.dispatch(msg).await {
+                                    error!("failed to dispatch message {:?}", e);
+                                }
                             },
                             None => (),
                             Some(Err(e)) => {
                                 warn!("failed to get message {:?}", e);
                             },
+                        },
+                        _ = commit_interval.tick() => (),
+                    }
+
+                    // offsets are only stored once the workers processed every event before them,
+                    // so the events still queued are read again after a crash
+                    for offset in pipeline.committable() {
+                        if let Err(e) =
+                            cons.store_offset(&offset.topic, offset.partition, offset.offset)
+                        {
+                            warn!(?offset, "failed to store consumer offset {:?}", e);
+
+                            pipeline.revoke(&offset.topic, offset.partition);
                         }
                     }
                 }
             });
 
+            let mut routes = Route::new()
+                .at("/graphql", post(graphql_handler).with(AddData::new(state)))
+                .at("/playground", get(playground))
+                .at("/health", get(health))
+                .at("/metrics", get(metrics));
+
+            if let Some(operational) = operational {
+                routes = routes.at(
+                    "/svix/operational",
+                    post(svix_operational).with(AddData::new(operational)),
+                );
+            }
+
             Server::new(TcpListener::bind(format!("0.0.0.0:{port}")))
-                .run(
-                    Route::new()
-                        .at("/graphql", post(graphql_handler).with(AddData::new(state)))
-                        .at("/playground", get(playground))
-                        .at("/health", get(health)),
-                )
+                .run(routes)
                 .await
                 .context("failed to build graphql server")
         })
@@ -0,0 +1,75 @@
+//! Backfill of the organization memberships that authorize access to webhooks.
+
+use std::path::Path;
+
+use hub_core::{
+    anyhow::{Context, Result},
+    prelude::*,
+    uuid::Uuid,
+};
+use sea_orm::{prelude::*, sea_query::OnConflict, Set};
+
+use crate::{db::Connection, entities::organization_members};
+
+/// Adds the members listed in a CSV file of `organization_id,user_id` lines, such as an export of the active
+/// members of hub-orgs, returning how many were added. A header line and blank lines are skipped.
+/// Members already known from `hub-orgs` events are left as they are, so a member deactivated after the export
+/// stays deactivated.
+///
+/// # Errors
+/// This function fails if the file cannot be read, a line is not a pair of UUIDs or a member cannot be stored.
+pub async fn backfill(db: &Connection, path: &Path) -> Result<u64> {
+    let file = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+
+    let mut added = 0;
+
+    for (number, line) in file.lines().enumerate() {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with("organization_id") {
+            continue;
+        }
+
+        let (organization_id, user_id) =
+            parse(line).with_context(|| format!("invalid member on line {}", number + 1))?;
+
+        let member = organization_members::ActiveModel {
+            organization_id: Set(organization_id),
+            user_id: Set(user_id),
+            deactivated_at: Set(None),
+            ..Default::default()
+        };
+
+        let inserted = organization_members::Entity::insert(member)
+            .on_conflict(
+                OnConflict::columns([
+                    organization_members::Column::OrganizationId,
+                    organization_members::Column::UserId,
+                ])
+                .do_nothing()
+                .to_owned(),
+            )
+            .exec_without_returning(db.get())
+            .await?;
+
+        if inserted > 0 {
+            debug!(%organization_id, %user_id, "backfilled organization member");
+        }
+
+        added += inserted;
+    }
+
+    Ok(added)
+}
+
+fn parse(line: &str) -> Result<(Uuid, Uuid)> {
+    let (organization_id, user_id) = line
+        .split_once(',')
+        .context("expected organization_id,user_id")?;
+
+    Ok((
+        Uuid::parse_str(organization_id.trim())?,
+        Uuid::parse_str(user_id.trim())?,
+    ))
+}
@@ -0,0 +1,56 @@
+use hub_core::anyhow::Result;
+use once_cell::sync::Lazy;
+use prometheus::{
+    register_int_counter, register_int_counter_vec, Encoder, IntCounter, IntCounterVec, TextEncoder,
+};
+
+/// Events that were not broadcast to Svix, labeled by event type and reason
+pub static BROADCASTS_SKIPPED: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "hub_webhooks_broadcasts_skipped_total",
+        "Number of events not broadcast to Svix",
+        &["event_type", "reason"]
+    )
+    .expect("failed to register broadcasts skipped metric")
+});
+
+/// Outbox messages that ran out of attempts and were no longer published
+pub static OUTBOX_DEAD_LETTERS: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "hub_webhooks_outbox_dead_letters_total",
+        "Number of outbox messages dead-lettered after running out of attempts"
+    )
+    .expect("failed to register outbox dead letters metric")
+});
+
+/// Differences found between the webhooks in Postgres and the delivery backend, labeled by kind
+pub static RECONCILIATION_DRIFTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "hub_webhooks_reconciliation_drifts_total",
+        "Number of differences found between the webhooks in Postgres and the delivery backend",
+        &["kind"]
+    )
+    .expect("failed to register reconciliation drifts metric")
+});
+
+/// Messages that did not match the schema of their event type, labeled by event type and validation mode
+pub static PAYLOAD_VIOLATIONS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "hub_webhooks_payload_violations_total",
+        "Number of messages that did not match the schema of their event type",
+        &["event_type", "mode"]
+    )
+    .expect("failed to register payload violations metric")
+});
+
+/// Renders every registered metric in the Prometheus text format.
+///
+/// # Errors
+/// This function fails if the metrics cannot be encoded.
+pub fn render() -> Result<String> {
+    let mut buffer = Vec::new();
+
+    TextEncoder::new().encode(&prometheus::gather(), &mut buffer)?;
+
+    Ok(String::from_utf8(buffer)?)
+}
@@ -1,17 +1,27 @@
 use std::ops::Add;
 
-use async_graphql::{self, Context, Enum, Error, InputObject, Object, Result, SimpleObject};
-use hub_core::{chrono::Utc, producer::Producer};
+use async_graphql::{self, Context, Error, InputObject, Object, Result, SimpleObject};
+use hub_core::chrono::{DateTime, Duration, Utc};
 use sea_orm::{prelude::*, JoinType, QuerySelect, Set, TransactionTrait};
-use svix::api::{EndpointIn, EndpointUpdate, Svix};
 
 use crate::{
+    applications,
+    delivery::{
+        Delivery, EndpointIn, EndpointOut, EndpointUpdate, MessageIn,
+        DEFAULT_SECRET_ROTATION_GRACE_PERIOD,
+    },
+    endpoint_cache,
     entities::{organization_applications, webhook_projects, webhooks},
+    event_types::{ApiVersion, FilterType},
+    events,
     objects::Webhook,
     proto::{self, webhook_events::Event, WebhookEventKey, WebhookEvents},
-    AppContext,
+    AppContext, EventsProducer,
 };
 
+/// The longest grace period in hours that can be requested when rotating the secret of a webhook.
+const MAX_SECRET_ROTATION_GRACE_PERIOD: i32 = 7 * 24;
+
 #[derive(Debug, Clone, Copy, Default)]
 pub struct Mutation;
 
@@ -26,22 +36,23 @@ impl Mutation {
         ctx: &Context<'_>,
         input: CreateWebhookInput,
     ) -> Result<CreateWebhookPayload> {
-        let AppContext { db, user_id, .. } = ctx.data::<AppContext>()?;
-        let producer = ctx.data::<Producer<WebhookEvents>>()?;
-        let svix = ctx.data::<Svix>()?;
+        let app_ctx = ctx.data::<AppContext>()?;
+        let AppContext { db, .. } = app_ctx;
+        let producer = ctx.data::<EventsProducer>()?;
+        let delivery = ctx.data::<Delivery>()?;
 
-        let user_id = user_id.ok_or_else(|| Error::new("X-USER-ID header not found"))?;
+        let user_id = app_ctx.authorize(input.organization).await?;
 
-        let org_app = organization_applications::Entity::find()
-            .filter(organization_applications::Column::OrganizationId.eq(input.organization))
-            .one(db.get())
-            .await?
-            .ok_or_else(|| Error::new("organization not found"))?;
+        let app_id = applications::get_or_create(db, delivery, input.organization, None).await?;
 
-        let app_id = org_app.svix_app_id;
+        let api_version = input.api_version.unwrap_or(ApiVersion::LATEST);
 
         let create_endpoint = EndpointIn {
-            channels: Some(input.projects.iter().map(ToString::to_string).collect()),
+            channels: Some(endpoint_channels(
+                input.organization,
+                &input.projects,
+                api_version,
+            )),
             filter_types: Some(input.filter_types.iter().map(|e| e.format()).collect()),
             version: 1,
             description: Some(input.description),
@@ -52,23 +63,23 @@ impl Mutation {
             uid: None,
         };
 
-        let endpoint = svix
-            .endpoint()
-            .create(app_id.clone(), create_endpoint, None)
+        let endpoint = delivery
+            .create_endpoint(app_id.clone(), create_endpoint)
             .await?;
 
-        let endpoint_secret = svix
-            .endpoint()
-            .get_secret(app_id, endpoint.clone().id)
+        let endpoint_secret = delivery
+            .get_endpoint_secret(app_id, endpoint.id.clone())
             .await?;
 
-        let webhook_active_model = webhooks::ActiveModel {
+        let mut webhook_active_model = webhooks::ActiveModel {
             endpoint_id: Set(endpoint.id.clone()),
             organization_id: Set(input.organization),
             updated_at: Set(None),
             created_by: Set(user_id),
+            api_version: Set(api_version.number()),
             ..Default::default()
         };
+        endpoint_cache::store(&mut webhook_active_model, &endpoint);
 
         let webhook = webhook_active_model.insert(db.get()).await?;
 
@@ -84,8 +95,8 @@ impl Mutation {
 
         // return the webhook object and endpoint secret
         let graphql_response = CreateWebhookPayload {
-            webhook: Webhook::new(endpoint, webhook.clone()),
-            secret: endpoint_secret.key,
+            webhook: Webhook::new(webhook.clone()),
+            secret: endpoint_secret,
         };
 
         let event = WebhookEvents {
@@ -100,7 +111,7 @@ impl Mutation {
             user_id: user_id.to_string(),
         };
 
-        producer.send(Some(&event), Some(&key)).await?;
+        producer.send(&event, &key).await?;
 
         Ok(graphql_response)
     }
@@ -114,11 +125,10 @@ impl Mutation {
         ctx: &Context<'_>,
         input: DeleteWebhookInput,
     ) -> Result<DeleteWebhookPayload> {
-        let AppContext { db, user_id, .. } = ctx.data::<AppContext>()?;
-        let producer = ctx.data::<Producer<WebhookEvents>>()?;
-        let svix = ctx.data::<Svix>()?;
-
-        let user_id = user_id.ok_or_else(|| Error::new("X-USER-ID header not found"))?;
+        let app_ctx = ctx.data::<AppContext>()?;
+        let AppContext { db, .. } = app_ctx;
+        let producer = ctx.data::<EventsProducer>()?;
+        let delivery = ctx.data::<Delivery>()?;
 
         let (webhook, organization_application) = webhooks::Entity::find()
             .join(
@@ -131,11 +141,13 @@ impl Mutation {
             .await?
             .ok_or_else(|| Error::new("webhook not found"))?;
 
+        let user_id = app_ctx.authorize(webhook.organization_id).await?;
+
         let organization_application = organization_application
             .ok_or_else(|| Error::new("organization_application not found"))?;
 
-        svix.endpoint()
-            .delete(
+        delivery
+            .delete_endpoint(
                 organization_application.svix_app_id,
                 webhook.endpoint_id.clone(),
             )
@@ -155,7 +167,7 @@ impl Mutation {
             user_id: user_id.to_string(),
         };
 
-        producer.send(Some(&event), Some(&key)).await?;
+        producer.send(&event, &key).await?;
 
         Ok(DeleteWebhookPayload {
             webhook: input.webhook,
@@ -171,8 +183,10 @@ impl Mutation {
         ctx: &Context<'_>,
         input: EditWebhookInput,
     ) -> Result<EditWebhookPayload> {
-        let AppContext { db, .. } = ctx.data::<AppContext>()?;
-        let svix = ctx.data::<Svix>()?;
+        let app_ctx = ctx.data::<AppContext>()?;
+        let AppContext { db, .. } = app_ctx;
+        let producer = ctx.data::<EventsProducer>()?;
+        let delivery = ctx.data::<Delivery>()?;
         let conn = db.get();
 
         let webhook = webhooks::Entity::find()
@@ -181,6 +195,19 @@ impl Mutation {
             .await?
             .ok_or_else(|| Error::new("webhook not found"))?;
 
+        let user_id = app_ctx.authorize(webhook.organization_id).await?;
+
+        let previous_api_version = ApiVersion::of(&webhook);
+        let api_version = input.api_version.unwrap_or(previous_api_version);
+
+        let previous_projects: Vec<Uuid> = webhook_projects::Entity::find()
+            .filter(webhook_projects::Column::WebhookId.eq(webhook.id))
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(|webhook_project| webhook_project.project_id)
+            .collect();
+
         let org_app = organization_applications::Entity::find()
             .filter(organization_applications::Column::OrganizationId.eq(webhook.organization_id))
             .one(conn)
@@ -217,41 +244,390 @@ impl Mutation {
         .await?;
 
         // get and update endpoint
-        let current_endpoint = svix
-            .endpoint()
-            .get(app_id.clone(), webhook.endpoint_id.clone())
+        let current_endpoint = delivery
+            .get_endpoint(app_id.clone(), webhook.endpoint_id.clone())
             .await?;
 
         let update_endpoint = EndpointUpdate {
-            channels: Some(input.projects.iter().map(ToString::to_string).collect()),
+            channels: Some(endpoint_channels(
+                webhook.organization_id,
+                &input.projects,
+                api_version,
+            )),
             filter_types: Some(input.filter_types.iter().map(|e| e.format()).collect()),
             version: current_endpoint.version.add(1),
             description: Some(input.description),
-            disabled: input.disabled,
+            // the delivery backend enables an endpoint when the field is omitted
+            disabled: input.disabled.or(current_endpoint.disabled),
             rate_limit: current_endpoint.rate_limit,
             url: input.url,
             uid: current_endpoint.uid,
         };
 
-        let endpoint = svix
-            .endpoint()
-            .update(
-                app_id.clone(),
-                webhook.endpoint_id.clone(),
-                update_endpoint,
-                None,
-            )
+        let endpoint = delivery
+            .update_endpoint(app_id.clone(), webhook.endpoint_id.clone(), update_endpoint)
             .await?;
 
-        let mut active_webhook: webhooks::ActiveModel = webhook.clone().into();
+        let mut active_webhook: webhooks::ActiveModel = webhook.into();
         active_webhook.updated_at = Set(Some(Utc::now().naive_utc()));
+        active_webhook.api_version = Set(api_version.number());
+        endpoint_cache::store(&mut active_webhook, &endpoint);
+
+        let webhook = active_webhook.update(conn).await?;
 
-        active_webhook.update(conn).await?;
+        let key = WebhookEventKey {
+            id: webhook.id.to_string(),
+            user_id: user_id.to_string(),
+        };
+
+        let changes = changed_fields(
+            &current_endpoint,
+            &previous_projects,
+            previous_api_version,
+            &endpoint,
+            &input.projects,
+            api_version,
+        )?;
+
+        if !changes.is_empty() {
+            let event = WebhookEvents {
+                event: Some(Event::Updated(proto::WebhookUpdate {
+                    organization_id: webhook.organization_id.to_string(),
+                    endpoint_id: webhook.endpoint_id.clone(),
+                    changes,
+                })),
+            };
+
+            producer.send(&event, &key).await?;
+        }
+
+        let was_disabled = current_endpoint.disabled.unwrap_or_default();
+
+        if was_disabled != webhook.disabled {
+            let webhook_proto = proto::Webhook {
+                organization_id: webhook.organization_id.to_string(),
+                endpoint_id: webhook.endpoint_id.clone(),
+            };
+
+            let event = WebhookEvents {
+                event: Some(if webhook.disabled {
+                    Event::Disabled(webhook_proto)
+                } else {
+                    Event::Enabled(webhook_proto)
+                }),
+            };
+
+            producer.send(&event, &key).await?;
+        }
 
         Ok(EditWebhookPayload {
-            webhook: Webhook::new(endpoint, webhook),
+            webhook: Webhook::new(webhook),
+        })
+    }
+
+    /// Resends a message to the endpoint of a webhook, regardless of its previous delivery status.
+    ///
+    /// # Errors
+    /// This function fails if the webhook does not belong to the organization or the delivery backend rejects the resend.
+    pub async fn resend_webhook_message(
+        &self,
+        ctx: &Context<'_>,
+        input: ResendWebhookMessageInput,
+    ) -> Result<ResendWebhookMessagePayload> {
+        let app_ctx = ctx.data::<AppContext>()?;
+        let AppContext { db, .. } = app_ctx;
+        let producer = ctx.data::<EventsProducer>()?;
+        let delivery = ctx.data::<Delivery>()?;
+
+        let user_id = app_ctx.authorize(input.organization).await?;
+
+        let (webhook, svix_app_id) =
+            find_organization_webhook(db.get(), input.organization, input.webhook).await?;
+
+        delivery
+            .resend_message(
+                svix_app_id,
+                input.message_id.clone(),
+                webhook.endpoint_id.clone(),
+            )
+            .await?;
+
+        let event = WebhookEvents {
+            event: Some(Event::MessageResent(proto::WebhookMessage {
+                organization_id: webhook.organization_id.to_string(),
+                endpoint_id: webhook.endpoint_id.clone(),
+                message_id: input.message_id.clone(),
+            })),
+        };
+
+        let key = WebhookEventKey {
+            id: webhook.id.to_string(),
+            user_id: user_id.to_string(),
+        };
+
+        producer.send(&event, &key).await?;
+
+        Ok(ResendWebhookMessagePayload {
+            webhook: input.webhook,
+            message_id: input.message_id,
         })
     }
+
+    /// Resends every message that failed to be delivered to the endpoint of a webhook since the given datetime.
+    ///
+    /// # Errors
+    /// This function fails if the webhook does not belong to the organization or the delivery backend rejects the recovery.
+    pub async fn recover_webhook(
+        &self,
+        ctx: &Context<'_>,
+        input: RecoverWebhookInput,
+    ) -> Result<RecoverWebhookPayload> {
+        let app_ctx = ctx.data::<AppContext>()?;
+        let AppContext { db, .. } = app_ctx;
+        let producer = ctx.data::<EventsProducer>()?;
+        let delivery = ctx.data::<Delivery>()?;
+
+        let user_id = app_ctx.authorize(input.organization).await?;
+
+        let (webhook, svix_app_id) =
+            find_organization_webhook(db.get(), input.organization, input.webhook).await?;
+
+        delivery
+            .recover_endpoint(svix_app_id, webhook.endpoint_id.clone(), input.since)
+            .await?;
+
+        let event = WebhookEvents {
+            event: Some(Event::Recovered(proto::WebhookRecovery {
+                organization_id: webhook.organization_id.to_string(),
+                endpoint_id: webhook.endpoint_id.clone(),
+                since: input.since.to_rfc3339(),
+            })),
+        };
+
+        let key = WebhookEventKey {
+            id: webhook.id.to_string(),
+            user_id: user_id.to_string(),
+        };
+
+        producer.send(&event, &key).await?;
+
+        Ok(RecoverWebhookPayload {
+            webhook: input.webhook,
+        })
+    }
+
+    /// Rotates the secret used to sign the messages sent to the endpoint of a webhook.
+    /// Messages keep being signed with the previous secret as well until `previousSecretExpiresAt`, giving receivers time to roll out the new secret.
+    ///
+    /// # Errors
+    /// This function fails if the webhook does not belong to the organization, the grace period is out of range or the delivery backend rejects the rotation.
+    pub async fn rotate_webhook_secret(
+        &self,
+        ctx: &Context<'_>,
+        input: RotateWebhookSecretInput,
+    ) -> Result<RotateWebhookSecretPayload> {
+        let app_ctx = ctx.data::<AppContext>()?;
+        let AppContext { db, .. } = app_ctx;
+        let producer = ctx.data::<EventsProducer>()?;
+        let delivery = ctx.data::<Delivery>()?;
+
+        let user_id = app_ctx.authorize(input.organization).await?;
+
+        let grace_period = input
+            .grace_period_hours
+            .map_or(DEFAULT_SECRET_ROTATION_GRACE_PERIOD, i64::from);
+
+        if !(0..=i64::from(MAX_SECRET_ROTATION_GRACE_PERIOD)).contains(&grace_period) {
+            return Err(Error::new(format!(
+                "gracePeriodHours must be between 0 and {MAX_SECRET_ROTATION_GRACE_PERIOD}"
+            )));
+        }
+
+        let (webhook, svix_app_id) =
+            find_organization_webhook(db.get(), input.organization, input.webhook).await?;
+
+        let previous_secret_expires_at = delivery
+            .rotate_endpoint_secret(
+                svix_app_id.clone(),
+                webhook.endpoint_id.clone(),
+                input.key,
+                Duration::hours(grace_period),
+            )
+            .await?;
+
+        let endpoint_secret = delivery
+            .get_endpoint_secret(svix_app_id, webhook.endpoint_id.clone())
+            .await?;
+
+        let event = WebhookEvents {
+            event: Some(Event::SecretRotated(proto::Webhook {
+                organization_id: webhook.organization_id.to_string(),
+                endpoint_id: webhook.endpoint_id.clone(),
+            })),
+        };
+
+        let key = WebhookEventKey {
+            id: webhook.id.to_string(),
+            user_id: user_id.to_string(),
+        };
+
+        producer.send(&event, &key).await?;
+
+        Ok(RotateWebhookSecretPayload {
+            webhook: input.webhook,
+            secret: endpoint_secret,
+            previous_secret_expires_at,
+        })
+    }
+
+    /// Sends a sample event with placeholder IDs to the endpoint of a webhook only, regardless of the events it is subscribed to.
+    /// The delivery is not awaited, its result can be followed through the `messageAttempts` of the webhook.
+    ///
+    /// # Errors
+    /// This function fails if the webhook does not belong to the organization, the event type has no sample payload or the delivery backend rejects the message.
+    pub async fn send_test_event(
+        &self,
+        ctx: &Context<'_>,
+        input: SendTestEventInput,
+    ) -> Result<SendTestEventPayload> {
+        let app_ctx = ctx.data::<AppContext>()?;
+        let AppContext { db, .. } = app_ctx;
+        let delivery = ctx.data::<Delivery>()?;
+
+        app_ctx.authorize(input.organization).await?;
+
+        let (webhook, svix_app_id) =
+            find_organization_webhook(db.get(), input.organization, input.webhook).await?;
+
+        let payload = events::example_payload(
+            input.event_type,
+            ApiVersion::of(&webhook),
+            webhook.organization_id,
+        )?;
+
+        // the channel is not subscribed to by any endpoint so the message is only delivered by the resend below
+        let message = MessageIn {
+            channels: Some(vec![format!("test-{}", webhook.id)]),
+            event_id: None,
+            event_type: input.event_type.format(),
+            payload,
+        };
+
+        let message = delivery
+            .create_message(svix_app_id.clone(), message, None)
+            .await?;
+
+        delivery
+            .resend_message(svix_app_id, message.id.clone(), webhook.endpoint_id)
+            .await?;
+
+        Ok(SendTestEventPayload {
+            webhook: input.webhook,
+            message_id: message.id,
+        })
+    }
+}
+
+/// Builds the channels of an endpoint in its API version. Besides its projects, every endpoint listens on the
+/// channel of its organization to receive organization-wide events such as `project.created`.
+pub(crate) fn endpoint_channels(
+    organization: Uuid,
+    projects: &[Uuid],
+    api_version: ApiVersion,
+) -> Vec<String> {
+    projects
+        .iter()
+        .chain([organization].iter())
+        .map(|id| api_version.channel(id))
+        .collect()
+}
+
+/// Lists the fields of a webhook changed by an edit, other than its disabled state which has its own events.
+/// The previous and current values are encoded as JSON, with lists sorted so that reordering is not a change.
+fn changed_fields(
+    previous: &EndpointOut,
+    previous_projects: &[Uuid],
+    previous_api_version: ApiVersion,
+    current: &EndpointOut,
+    current_projects: &[Uuid],
+    current_api_version: ApiVersion,
+) -> Result<Vec<proto::WebhookFieldChange>> {
+    fn sorted<'a, T: ToString + 'a>(values: impl IntoIterator<Item = &'a T>) -> Vec<String> {
+        let mut values: Vec<String> = values.into_iter().map(ToString::to_string).collect();
+        values.sort();
+        values
+    }
+
+    let fields = [
+        (
+            "url",
+            serde_json::to_string(&previous.url)?,
+            serde_json::to_string(&current.url)?,
+        ),
+        (
+            "description",
+            serde_json::to_string(&previous.description)?,
+            serde_json::to_string(&current.description)?,
+        ),
+        (
+            "filter_types",
+            serde_json::to_string(&sorted(previous.filter_types.iter().flatten()))?,
+            serde_json::to_string(&sorted(current.filter_types.iter().flatten()))?,
+        ),
+        (
+            "projects",
+            serde_json::to_string(&sorted(previous_projects))?,
+            serde_json::to_string(&sorted(current_projects))?,
+        ),
+        (
+            "api_version",
+            serde_json::to_string(&previous_api_version.number())?,
+            serde_json::to_string(&current_api_version.number())?,
+        ),
+    ];
+
+    Ok(fields
+        .into_iter()
+        .filter(|(_, previous, current)| previous != current)
+        .map(|(field, previous, current)| proto::WebhookFieldChange {
+            field: field.to_string(),
+            previous,
+            current,
+        })
+        .collect())
+}
+
+/// Fetches a webhook along with the application ID of its organization.
+///
+/// # Errors
+/// This function fails if the webhook does not exist or does not belong to `organization`.
+async fn find_organization_webhook(
+    conn: &DatabaseConnection,
+    organization: Uuid,
+    webhook: Uuid,
+) -> Result<(webhooks::Model, String)> {
+    let (webhook, organization_application) = webhooks::Entity::find()
+        .join(
+            JoinType::InnerJoin,
+            webhooks::Relation::OrganizationApplications.def(),
+        )
+        .select_also(organization_applications::Entity)
+        .filter(webhooks::Column::Id.eq(webhook))
+        .one(conn)
+        .await?
+        .ok_or_else(|| Error::new("webhook not found"))?;
+
+    if webhook.organization_id != organization {
+        return Err(Error::new(format!(
+            "Webhook {} does not belong to organization {}",
+            webhook.id, organization
+        )));
+    }
+
+    let organization_application =
+        organization_application.ok_or_else(|| Error::new("organization_application not found"))?;
+
+    Ok((webhook, organization_application.svix_app_id))
 }
 
 #[derive(Debug, InputObject, Clone)]
@@ -261,6 +637,8 @@ pub struct CreateWebhookInput {
     pub description: String,
     pub projects: Vec<Uuid>,
     pub filter_types: Vec<FilterType>,
+    /// The version of the messages sent to the webhook, the latest when not provided.
+    pub api_version: Option<ApiVersion>,
 }
 
 #[derive(SimpleObject, Debug, Clone)]
@@ -269,69 +647,6 @@ pub struct CreateWebhookPayload {
     pub secret: String,
 }
 
-/// An enumeration of event types that can be subscribed to by a webhook.
-#[derive(Debug, Clone, PartialEq, Eq, Copy, Enum)]
-pub enum FilterType {
-    /// Event triggered when a new project is created
-    ProjectCreated,
-    /// Event triggered when a new customer is created
-    CustomerCreated,
-    /// Event triggered when a new customer treasury is created
-    CustomerTreasuryCreated,
-    /// Event triggered when a new wallet is created for a project
-    ProjectWalletCreated,
-    /// Event triggered when a new wallet is created for a customer
-    CustomerWalletCreated,
-    /// Event triggered when a new drop is created
-    DropCreated,
-    /// Event triggered when a new drop is minted
-    DropMinted,
-    /// Event triggered when a mint has been successfully transfered
-    MintTransfered,
-    /// Event triggered when a new collection is created
-    CollectionCreated,
-    /// Event triggered when an NFT is minted to a collection
-    MintedToCollection,
-}
-
-impl FilterType {
-    #[must_use]
-    pub fn format(self) -> String {
-        match self {
-            Self::ProjectCreated => "project.created".to_string(),
-            Self::CustomerCreated => "customer.created".to_string(),
-            Self::CustomerTreasuryCreated => "customer_treasury.created".to_string(),
-            Self::CustomerWalletCreated => "customer_wallet.created".to_string(),
-            Self::ProjectWalletCreated => "project_wallet.created".to_string(),
-            Self::DropCreated => "drop.created".to_string(),
-            Self::DropMinted => "drop.minted".to_string(),
-            Self::MintTransfered => "mint.transfered".to_string(),
-            Self::CollectionCreated => "collection.created".to_string(),
-            Self::MintedToCollection => "collection.minted".to_string(),
-        }
-    }
-}
-
-impl std::str::FromStr for FilterType {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "project.created" => Ok(FilterType::ProjectCreated),
-            "customer.created" => Ok(FilterType::CustomerCreated),
-            "customer_treasury.created" => Ok(FilterType::CustomerTreasuryCreated),
-            "customer_wallet.created" => Ok(FilterType::CustomerWalletCreated),
-            "project_wallet.created" => Ok(FilterType::ProjectWalletCreated),
-            "drop.created" => Ok(FilterType::DropCreated),
-            "drop.minted" => Ok(FilterType::DropMinted),
-            "mint.transfered" => Ok(FilterType::MintTransfered),
-            "collection.created" => Ok(FilterType::CollectionCreated),
-            "collection.minted" => Ok(FilterType::MintedToCollection),
-            _ => Err(format!("'{s}' is not a valid value for FilterType")),
-        }
-    }
-}
-
 #[derive(Debug, Clone, InputObject)]
 pub struct DeleteWebhookInput {
     pub webhook: Uuid,
@@ -349,10 +664,71 @@ pub struct EditWebhookInput {
     pub description: String,
     pub projects: Vec<Uuid>,
     pub filter_types: Vec<FilterType>,
+    /// Whether the webhook is disabled, unchanged when not provided.
     pub disabled: Option<bool>,
+    /// The version of the messages sent to the webhook, unchanged when not provided.
+    pub api_version: Option<ApiVersion>,
 }
 
 #[derive(SimpleObject, Debug, Clone)]
 pub struct EditWebhookPayload {
     pub webhook: Webhook,
 }
+
+#[derive(Debug, Clone, InputObject)]
+pub struct ResendWebhookMessageInput {
+    pub organization: Uuid,
+    pub webhook: Uuid,
+    pub message_id: String,
+}
+
+#[derive(Debug, Clone, SimpleObject)]
+pub struct ResendWebhookMessagePayload {
+    webhook: Uuid,
+    message_id: String,
+}
+
+#[derive(Debug, Clone, InputObject)]
+pub struct RecoverWebhookInput {
+    pub organization: Uuid,
+    pub webhook: Uuid,
+    pub since: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, SimpleObject)]
+pub struct RecoverWebhookPayload {
+    webhook: Uuid,
+}
+
+#[derive(Debug, Clone, InputObject)]
+pub struct RotateWebhookSecretInput {
+    pub organization: Uuid,
+    pub webhook: Uuid,
+    /// The new secret, generated by Svix when not provided. Must be prefixed with `whsec_`.
+    pub key: Option<String>,
+    /// Hours messages keep being signed with the previous secret as well, 24 when not provided. Svix only supports
+    /// the default, other grace periods require the native delivery backend.
+    pub grace_period_hours: Option<i32>,
+}
+
+#[derive(Debug, Clone, SimpleObject)]
+pub struct RotateWebhookSecretPayload {
+    webhook: Uuid,
+    secret: String,
+    /// Until when messages are also signed with the previous secret.
+    previous_secret_expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, InputObject)]
+pub struct SendTestEventInput {
+    pub organization: Uuid,
+    pub webhook: Uuid,
+    pub event_type: FilterType,
+}
+
+#[derive(Debug, Clone, SimpleObject)]
+pub struct SendTestEventPayload {
+    webhook: Uuid,
+    /// The ID of the test message, matching the `messageId` of its attempts.
+    message_id: String,
+}
@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+
+use async_graphql::{Enum, Result, SimpleObject};
+use hub_core::chrono::{DateTime, Utc};
+
+use crate::{
+    delivery::{MessageAttemptOut, MessageStatus},
+    event_types::FilterType,
+};
+
+/// The maximum number of characters of a response body kept on a message attempt.
+const RESPONSE_EXCERPT_LENGTH: usize = 256;
+
+/// A single attempt at delivering a message to a webhook's endpoint.
+#[derive(SimpleObject, Debug, Clone)]
+pub struct MessageAttempt {
+    /// The ID of the message attempt.
+    pub id: String,
+    /// The ID of the message that was delivered.
+    pub message_id: String,
+    /// The ID of the endpoint the message was delivered to.
+    pub endpoint_id: String,
+    /// The URL the message was delivered to.
+    pub url: String,
+    /// The delivery status of the attempt.
+    pub status: DeliveryStatus,
+    /// The HTTP status code returned by the endpoint.
+    pub response_status_code: i32,
+    /// The first characters of the response body returned by the endpoint.
+    pub response: String,
+    /// The event type of the message, when it is known.
+    pub event_type: Option<FilterType>,
+    /// The datetime of the attempt.
+    pub timestamp: DateTime<Utc>,
+    /// The position of this attempt among all attempts to deliver the message to the endpoint, starting at 1.
+    pub attempt_number: usize,
+}
+
+impl MessageAttempt {
+    /// Builds the `attempt_number`th attempt of a message from the delivery backend response.
+    ///
+    /// # Errors
+    /// This function fails if the attempt timestamp is not a valid RFC 3339 datetime.
+    pub fn new(attempt: MessageAttemptOut, attempt_number: usize) -> Result<Self> {
+        let MessageAttemptOut {
+            endpoint_id,
+            id,
+            msg,
+            msg_id,
+            response,
+            response_status_code,
+            status,
+            timestamp,
+            url,
+            ..
+        } = attempt;
+
+        let timestamp = DateTime::parse_from_rfc3339(&timestamp)?.with_timezone(&Utc);
+        let event_type = msg.and_then(|msg| msg.event_type.parse().ok());
+        let response = response.chars().take(RESPONSE_EXCERPT_LENGTH).collect();
+
+        Ok(Self {
+            id,
+            message_id: msg_id,
+            endpoint_id,
+            url,
+            status: status.into(),
+            response_status_code,
+            response,
+            event_type,
+            timestamp,
+            attempt_number,
+        })
+    }
+}
+
+/// Numbers the attempts made for a message by the order they were made to each endpoint, starting at 1.
+/// The numbers are keyed by the ID of the attempt, and `attempts` must hold every attempt of the message.
+#[must_use]
+pub fn attempt_numbers(attempts: &[MessageAttemptOut]) -> HashMap<String, usize> {
+    let timestamp =
+        |attempt: &MessageAttemptOut| DateTime::parse_from_rfc3339(&attempt.timestamp).ok();
+
+    attempts
+        .iter()
+        .map(|attempt| {
+            let previous = attempts
+                .iter()
+                .filter(|other| other.endpoint_id == attempt.endpoint_id)
+                .filter(|other| timestamp(other) < timestamp(attempt))
+                .count();
+
+            (attempt.id.clone(), previous + 1)
+        })
+        .collect()
+}
+
+/// The delivery status of a message attempt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
+pub enum DeliveryStatus {
+    /// The endpoint acknowledged the message with a successful status code.
+    Success,
+    /// The message is waiting to be delivered.
+    Pending,
+    /// The endpoint failed to acknowledge the message.
+    Failed,
+    /// The message is currently being delivered.
+    Sending,
+}
+
+impl From<MessageStatus> for DeliveryStatus {
+    fn from(value: MessageStatus) -> Self {
+        match value {
+            MessageStatus::Success => Self::Success,
+            MessageStatus::Pending => Self::Pending,
+            MessageStatus::Fail => Self::Failed,
+            MessageStatus::Sending => Self::Sending,
+        }
+    }
+}
+
+impl From<DeliveryStatus> for MessageStatus {
+    fn from(value: DeliveryStatus) -> Self {
+        match value {
+            DeliveryStatus::Success => Self::Success,
+            DeliveryStatus::Pending => Self::Pending,
+            DeliveryStatus::Failed => Self::Fail,
+            DeliveryStatus::Sending => Self::Sending,
+        }
+    }
+}
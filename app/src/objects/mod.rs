@@ -1,7 +1,9 @@
 #![allow(clippy::unused_async)]
 
+mod message_attempt;
 mod organization;
 mod webhook;
 
-pub use organization::Organization;
-pub use webhook::Webhook;
+pub use message_attempt::{attempt_numbers, DeliveryStatus, MessageAttempt};
+pub use organization::{Organization, SortDirection, WebhookFilter};
+pub use webhook::{EndpointHealth, Webhook};
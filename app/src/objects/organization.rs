@@ -1,7 +1,26 @@
-use async_graphql::{ComplexObject, Context, Error, Result, SimpleObject};
+use async_graphql::{
+    connection::{Connection, Edge},
+    ComplexObject, Context, Enum, Error, InputObject, Result, SimpleObject,
+};
 use hub_core::uuid::Uuid;
+use sea_orm::{
+    prelude::*,
+    sea_query::{Expr, Query},
+    Condition, QueryOrder, QuerySelect,
+};
+use serde_json::json;
 
-use crate::{objects::Webhook, AppContext};
+use crate::{
+    entities::{webhook_projects, webhooks},
+    event_types::FilterType,
+    objects::Webhook,
+    AppContext,
+};
+
+/// The number of webhooks returned when `first` is not provided.
+const DEFAULT_WEBHOOKS_LIMIT: i32 = 50;
+/// The maximum number of webhooks that can be requested at once.
+const MAX_WEBHOOKS_LIMIT: i32 = 250;
 
 #[derive(SimpleObject, Debug, Clone)]
 #[graphql(complex)]
@@ -12,26 +31,85 @@ pub struct Organization {
 
 #[ComplexObject]
 impl Organization {
-    /// Retrieves a list of all webhooks associated with the organization.
+    /// Retrieves a page of the webhooks associated with the organization.
     ///
     /// # Arguments
     ///
     /// * `ctx` - The context object representing the current request.
+    /// * `first` - The number of webhooks to return, defaults to 50 and is capped at 250.
+    /// * `after` - The cursor of the last webhook from the previous page.
+    /// * `filter` - Only return webhooks matching every provided criteria.
+    /// * `order` - The order of the webhooks by creation datetime, most recent first by default.
     ///
     /// # Returns
     ///
-    /// A vector of all Webhook objects associated with the Organization, or None if there are none.
+    /// A connection of the Webhook objects associated with the Organization.
     ///
     /// # Errors
     ///
-    /// This function will return an error if the data context cannot be retrieved.
-    pub async fn webhooks(&self, ctx: &Context<'_>) -> Result<Option<Vec<Webhook>>> {
+    /// This function will return an error if the data context cannot be retrieved, the user is not a member of the organization or the cursor is invalid.
+    pub async fn webhooks(
+        &self,
+        ctx: &Context<'_>,
+        first: Option<i32>,
+        after: Option<String>,
+        filter: Option<WebhookFilter>,
+        order: Option<SortDirection>,
+    ) -> Result<Connection<String, Webhook>> {
+        let app_ctx = ctx.data::<AppContext>()?;
         let AppContext {
-            organization_webhooks_loader,
-            ..
-        } = ctx.data::<AppContext>()?;
+            db, webhook_loader, ..
+        } = app_ctx;
+
+        app_ctx.authorize(self.id).await?;
+
+        let has_previous_page = after.is_some();
+        let limit = first
+            .unwrap_or(DEFAULT_WEBHOOKS_LIMIT)
+            .clamp(1, MAX_WEBHOOKS_LIMIT);
+        let order = order.unwrap_or_default();
+
+        let mut query = webhooks::Entity::find()
+            .filter(webhooks::Column::OrganizationId.eq(self.id))
+            .filter(filter.unwrap_or_default().condition());
+
+        if let Some(after) = after {
+            let cursor = webhooks::Entity::find_by_id(Uuid::parse_str(&after)?)
+                .filter(webhooks::Column::OrganizationId.eq(self.id))
+                .one(db.get())
+                .await?
+                .ok_or_else(|| Error::new(format!("invalid cursor {after}")))?;
+
+            query = query.filter(order.after(&cursor));
+        }
+
+        let mut page = match order {
+            SortDirection::Asc => query
+                .order_by_asc(webhooks::Column::CreatedAt)
+                .order_by_asc(webhooks::Column::Id),
+            SortDirection::Desc => query
+                .order_by_desc(webhooks::Column::CreatedAt)
+                .order_by_desc(webhooks::Column::Id),
+        }
+        .limit(u64::try_from(limit)? + 1)
+        .all(db.get())
+        .await?;
+
+        let has_next_page = page.len() > usize::try_from(limit)?;
+        page.truncate(usize::try_from(limit)?);
 
-        organization_webhooks_loader.load_one(self.id).await
+        let mut loaded = webhook_loader
+            .load_many(page.iter().map(|webhook| webhook.id))
+            .await?;
+
+        let mut connection = Connection::new(has_previous_page, has_next_page);
+        connection.edges.extend(page.into_iter().filter_map(|model| {
+            loaded
+                .remove(&model.id)
+                .map(|webhook| Edge::new(model.id.to_string(), webhook))
+        }));
+
+        Ok(connection)
     }
 
     /// Retrieves a specific webhook associated with the organization, based on its ID.
@@ -47,9 +125,12 @@ impl Organization {
     ///
     /// # Errors
     ///
-    /// This function will return an error if the data context cannot be retrieved.
+    /// This function will return an error if the data context cannot be retrieved or the user is not a member of the organization.
     pub async fn webhook(&self, ctx: &Context<'_>, id: Uuid) -> Result<Option<Webhook>> {
-        let AppContext { webhook_loader, .. } = ctx.data::<AppContext>()?;
+        let app_ctx = ctx.data::<AppContext>()?;
+        let AppContext { webhook_loader, .. } = app_ctx;
+
+        app_ctx.authorize(self.id).await?;
 
         let webhook = webhook_loader.load_one(id).await?;
 
@@ -67,3 +148,108 @@ impl Organization {
         Ok(None)
     }
 }
+
+/// Criteria a webhook must match to be listed. Omitted criteria match every webhook.
+#[derive(Debug, Clone, Default, InputObject)]
+pub struct WebhookFilter {
+    /// Only return webhooks subscribed to at least one of these projects.
+    pub projects: Option<Vec<Uuid>>,
+    /// Only return webhooks subscribed to at least one of these event types.
+    pub event_types: Option<Vec<FilterType>>,
+    /// Only return webhooks that are disabled, or enabled when false.
+    pub disabled: Option<bool>,
+    /// Only return webhooks whose URL contains this text.
+    pub url: Option<String>,
+}
+
+impl WebhookFilter {
+    fn condition(self) -> Condition {
+        let Self {
+            projects,
+            event_types,
+            disabled,
+            url,
+        } = self;
+
+        let mut condition = Condition::all();
+
+        if let Some(projects) = projects {
+            condition = condition.add(
+                webhooks::Column::Id.in_subquery(
+                    Query::select()
+                        .column(webhook_projects::Column::WebhookId)
+                        .from(webhook_projects::Entity)
+                        .and_where(webhook_projects::Column::ProjectId.is_in(projects))
+                        .to_owned(),
+                ),
+            );
+        }
+
+        if let Some(event_types) = event_types {
+            // webhooks created before their filter types were stored are assumed to listen to every event type
+            let subscribed = event_types.into_iter().fold(
+                Condition::any().add(webhooks::Column::FilterTypes.is_null()),
+                |condition, event_type| {
+                    condition.add(Expr::cust_with_values(
+                        r#""webhooks"."filter_types" @> $1"#,
+                        [json!([event_type.format()])],
+                    ))
+                },
+            );
+
+            condition = condition.add(subscribed);
+        }
+
+        if let Some(disabled) = disabled {
+            condition = condition.add(webhooks::Column::Disabled.eq(disabled));
+        }
+
+        if let Some(url) = url {
+            condition = condition.add(Expr::cust_with_values(
+                r#""webhooks"."url" LIKE $1 ESCAPE '\'"#,
+                [format!("%{}%", escape_like(&url))],
+            ));
+        }
+
+        condition
+    }
+}
+
+/// Escapes the wildcards of a `LIKE` pattern, so the text is matched literally.
+fn escape_like(text: &str) -> String {
+    text.replace('\\', r"\\")
+        .replace('%', r"\%")
+        .replace('_', r"\_")
+}
+
+/// The order in which webhooks are listed by creation datetime.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Enum)]
+pub enum SortDirection {
+    /// Oldest first
+    Asc,
+    /// Most recent first
+    #[default]
+    Desc,
+}
+
+impl SortDirection {
+    /// Matches the webhooks listed after `cursor`. Webhooks created at the same time are ordered by ID.
+    fn after(self, cursor: &webhooks::Model) -> Condition {
+        let (created_at, id) = match self {
+            Self::Asc => (
+                webhooks::Column::CreatedAt.gt(cursor.created_at),
+                webhooks::Column::Id.gt(cursor.id),
+            ),
+            Self::Desc => (
+                webhooks::Column::CreatedAt.lt(cursor.created_at),
+                webhooks::Column::Id.lt(cursor.id),
+            ),
+        };
+
+        Condition::any().add(created_at).add(
+            Condition::all()
+                .add(webhooks::Column::CreatedAt.eq(cursor.created_at))
+                .add(id),
+        )
+    }
+}
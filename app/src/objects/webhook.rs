@@ -1,22 +1,51 @@
-use async_graphql::{Object, Result};
+use std::collections::HashMap;
+
+use async_graphql::{
+    connection::{Connection, Edge},
+    Context, Enum, Error, Object, Result,
+};
 use hub_core::{chrono::NaiveDateTime, uuid::Uuid};
-use svix::api::EndpointOut;
+use sea_orm::prelude::*;
+
+use crate::{
+    db::Connection as DbConnection,
+    delivery::{Delivery, MessageAttemptListOptions},
+    endpoint_health::Health,
+    entities::{organization_applications, webhooks::Model},
+    event_types::{ApiVersion, FilterType},
+    objects::{attempt_numbers, DeliveryStatus, MessageAttempt},
+    AppContext,
+};
 
-use crate::{entities::webhooks::Model, mutations::webhook::FilterType};
+/// The number of message attempts returned when `first` is not provided.
+const DEFAULT_MESSAGE_ATTEMPTS_LIMIT: i32 = 50;
+/// The maximum number of message attempts that can be requested at once.
+const MAX_MESSAGE_ATTEMPTS_LIMIT: i32 = 250;
 
 /// A webhook represents an endpoint registered to receive notifications for specific events within a project.
+/// The metadata of the endpoint is read from the copy stored on the database model.
 #[derive(Debug, Clone)]
 pub struct Webhook {
-    /// The endpoint that the webhook is registered to.
-    pub endpoint: EndpointOut,
     /// The database model for the webhook.
     pub model: Model,
 }
 
 impl Webhook {
     #[must_use]
-    pub fn new(endpoint: EndpointOut, model: Model) -> Self {
-        Self { endpoint, model }
+    pub fn new(model: Model) -> Self {
+        Self { model }
+    }
+
+    async fn svix_app_id(&self, db: &DbConnection) -> Result<String> {
+        let org_app = organization_applications::Entity::find()
+            .filter(
+                organization_applications::Column::OrganizationId.eq(self.model.organization_id),
+            )
+            .one(db.get())
+            .await?
+            .ok_or_else(|| Error::new("organization not found"))?;
+
+        Ok(org_app.svix_app_id)
     }
 }
 
@@ -30,31 +59,52 @@ impl Webhook {
 
     /// Retrieves the ID of the webhook's endpoint.
     async fn endpoint_id(&self) -> &str {
-        &self.endpoint.id
+        &self.model.endpoint_id
     }
 
     /// Retrieves the URL of the webhook's endpoint.
     async fn url(&self) -> &str {
-        &self.endpoint.url
+        self.model.url.as_deref().unwrap_or_default()
     }
 
     /// Retrieves the events the webhook is subscribed to.
     async fn events(&self) -> Result<Vec<FilterType>> {
-        let filter_types = self.endpoint.filter_types.clone();
-
-        filter_types
-            .unwrap_or_default()
-            .into_iter()
+        strings(self.model.filter_types.as_ref())
             .map(|v| v.parse())
             .collect::<Result<Vec<FilterType>, _>>()
             .map_err(Into::into)
     }
 
+    /// Retrieves the version of the messages sent to the webhook.
+    async fn api_version(&self) -> ApiVersion {
+        ApiVersion::of(&self.model)
+    }
+
     /// Retrieves the webhook's description.
-    async fn description(&self) -> String {
-        let description = self.endpoint.description.clone();
+    async fn description(&self) -> &str {
+        self.model.description.as_deref().unwrap_or_default()
+    }
 
-        description.unwrap_or_default()
+    /// Retrieves whether the webhook's endpoint is disabled and no longer receives messages.
+    async fn disabled(&self) -> bool {
+        self.model.disabled
+    }
+
+    /// Retrieves whether the messages sent to the webhook's endpoint are being delivered.
+    async fn health(&self) -> EndpointHealth {
+        if self.model.disabled {
+            return EndpointHealth::Disabled;
+        }
+
+        match Health::from_name(&self.model.health) {
+            Health::Healthy => EndpointHealth::Healthy,
+            Health::Failing => EndpointHealth::Failing,
+        }
+    }
+
+    /// Retrieves the datetime the health of the webhook's endpoint last changed.
+    async fn health_changed_at(&self) -> Option<NaiveDateTime> {
+        self.model.health_changed_at
     }
 
     /// Retrieves the creation datetime of the webhook.
@@ -79,8 +129,130 @@ impl Webhook {
 
     /// Retrieves the channels the webhook is subscribed to.
     async fn channels(&self) -> Vec<String> {
-        let channels = self.endpoint.channels.clone();
+        let organization_channel = self.model.organization_id.to_string();
+
+        strings(self.model.channels.as_ref())
+            .map(ApiVersion::channel_id)
+            .filter(|channel| *channel != organization_channel)
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    /// Retrieves the secret used to sign the messages sent to the webhook's endpoint.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the user is not a member of the organization or the secret cannot be fetched.
+    async fn secret(&self, ctx: &Context<'_>) -> Result<String> {
+        let app_ctx = ctx.data::<AppContext>()?;
+        let AppContext { db, .. } = app_ctx;
+        let delivery = ctx.data::<Delivery>()?;
+
+        app_ctx.authorize(self.model.organization_id).await?;
+
+        let svix_app_id = self.svix_app_id(db).await?;
+
+        let secret = delivery
+            .get_endpoint_secret(svix_app_id, self.model.endpoint_id.clone())
+            .await?;
 
-        channels.unwrap_or_default()
+        Ok(secret)
     }
+
+    /// Retrieves the delivery attempts made to the webhook's endpoint, most recent first.
+    ///
+    /// # Arguments
+    ///
+    /// * `first` - The number of attempts to return, defaults to 50 and is capped at 250.
+    /// * `after` - The cursor of the last attempt from the previous page.
+    /// * `status` - Only return attempts with this delivery status.
+    /// * `event_types` - Only return attempts for messages of these event types.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the user is not a member of the organization, the organization's application cannot be found or the attempts cannot be fetched.
+    async fn message_attempts(
+        &self,
+        ctx: &Context<'_>,
+        first: Option<i32>,
+        after: Option<String>,
+        status: Option<DeliveryStatus>,
+        event_types: Option<Vec<FilterType>>,
+    ) -> Result<Connection<String, MessageAttempt>> {
+        let app_ctx = ctx.data::<AppContext>()?;
+        let AppContext { db, .. } = app_ctx;
+        let delivery = ctx.data::<Delivery>()?;
+
+        app_ctx.authorize(self.model.organization_id).await?;
+
+        let svix_app_id = self.svix_app_id(db).await?;
+
+        let has_previous_page = after.is_some();
+        let limit = first
+            .unwrap_or(DEFAULT_MESSAGE_ATTEMPTS_LIMIT)
+            .clamp(1, MAX_MESSAGE_ATTEMPTS_LIMIT);
+
+        let options = MessageAttemptListOptions {
+            iterator: after,
+            limit: Some(limit),
+            status: status.map(Into::into),
+            event_types: event_types
+                .map(|event_types| event_types.into_iter().map(FilterType::format).collect()),
+        };
+
+        let attempts = delivery
+            .list_endpoint_attempts(svix_app_id.clone(), self.model.endpoint_id.clone(), options)
+            .await?;
+
+        // every attempt of a message is needed to number its attempts, so they are fetched once per message
+        let mut numbers = HashMap::new();
+
+        for attempt in &attempts.data {
+            if numbers.contains_key(&attempt.id) {
+                continue;
+            }
+
+            let message_attempts = delivery
+                .list_message_attempts(svix_app_id.clone(), attempt.msg_id.clone())
+                .await?;
+
+            numbers.extend(attempt_numbers(&message_attempts));
+        }
+
+        let edges = attempts
+            .data
+            .into_iter()
+            .map(|attempt| {
+                let attempt_number = numbers.get(&attempt.id).copied().unwrap_or(1);
+                let attempt = MessageAttempt::new(attempt, attempt_number)?;
+
+                Ok(Edge::new(attempt.id.clone(), attempt))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut connection = Connection::new(has_previous_page, !attempts.done);
+        connection.edges.extend(edges);
+
+        Ok(connection)
+    }
+}
+
+/// The health of a webhook's endpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
+pub enum EndpointHealth {
+    /// The last message that finished its attempts was delivered.
+    Healthy,
+    /// The last message that finished its attempts was not delivered.
+    Failing,
+    /// The endpoint is disabled and no longer receives messages.
+    Disabled,
+}
+
+/// The strings of a JSON array stored on the model, skipping anything else.
+fn strings(value: Option<&Json>) -> impl Iterator<Item = &str> {
+    value
+        .and_then(Json::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Json::as_str)
 }
@@ -0,0 +1,176 @@
+//! Ingestion of the operational webhooks Svix sends about the endpoints of its applications.
+
+use hub_core::{
+    anyhow::{anyhow, bail, Result},
+    chrono::Utc,
+    clap,
+};
+use poem::http::HeaderMap;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    db::Connection,
+    delivery::native::signature,
+    endpoint_health::{self, HealthChange},
+    EventsProducer,
+};
+
+/// Seconds the timestamp of an operational webhook may differ from the current time, limiting replays.
+const TIMESTAMP_TOLERANCE: i64 = 5 * 60;
+
+/// Arguments for receiving the operational webhooks of Svix
+#[derive(Debug, clap::Args)]
+pub struct OperationalArgs {
+    /// Secret of the Svix operational webhook endpoint. The `/svix/operational` route is not served when not set
+    #[arg(long, env)]
+    pub svix_operational_secret: Option<String>,
+}
+
+/// Verifies the operational webhooks of Svix and records the health changes they report.
+#[derive(Clone)]
+pub struct OperationalWebhooks {
+    db: Connection,
+    producer: EventsProducer,
+    secret: String,
+}
+
+impl OperationalWebhooks {
+    #[must_use]
+    pub fn new(db: Connection, producer: EventsProducer, secret: String) -> Self {
+        Self {
+            db,
+            producer,
+            secret,
+        }
+    }
+
+    /// Checks the signature and timestamp of an operational webhook. Both the `svix-` and `webhook-`
+    /// prefixed headers are accepted.
+    ///
+    /// # Errors
+    /// This function fails if a header is missing, the timestamp is too old or no signature matches.
+    pub fn verify(&self, headers: &HeaderMap, body: &str) -> Result<()> {
+        let header = |name: &str| {
+            headers
+                .get(format!("svix-{name}"))
+                .or_else(|| headers.get(format!("webhook-{name}")))
+                .and_then(|value| value.to_str().ok())
+                .ok_or_else(|| anyhow!("missing {name} header"))
+        };
+
+        let message_id = header("id")?;
+        let timestamp: i64 = header("timestamp")?.parse()?;
+        let signatures = header("signature")?;
+
+        if (Utc::now().timestamp() - timestamp).abs() > TIMESTAMP_TOLERANCE {
+            bail!("timestamp {timestamp} is outside of the tolerance");
+        }
+
+        signature::verify(&self.secret, message_id, timestamp, body, signatures)
+    }
+
+    /// Records the health change reported by an operational webhook.
+    ///
+    /// # Errors
+    /// This function fails if the change cannot be recorded or published.
+    pub async fn process(&self, event: OperationalEvent) -> Result<()> {
+        let OperationalEvent {
+            endpoint_id,
+            change,
+        } = event;
+
+        endpoint_health::record(&self.db, &self.producer, &endpoint_id, change).await
+    }
+}
+
+/// An operational webhook reporting a change in the health of an endpoint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OperationalEvent {
+    pub endpoint_id: String,
+    pub change: HealthChange,
+}
+
+impl OperationalEvent {
+    /// Decodes the body of an operational webhook, returning `None` for the event types that do not change
+    /// the health of an endpoint.
+    ///
+    /// # Errors
+    /// This function fails if the body is not a valid operational webhook.
+    pub fn parse(body: &str) -> Result<Option<Self>> {
+        let Envelope { event_type, data } = serde_json::from_str(body)?;
+
+        let event = match event_type.as_str() {
+            "endpoint.disabled" | "endpoint.enabled" => {
+                let EndpointData { endpoint_id } = serde_json::from_value(data)?;
+
+                let change = if event_type == "endpoint.disabled" {
+                    HealthChange::Disabled
+                } else {
+                    HealthChange::Enabled
+                };
+
+                Self {
+                    endpoint_id,
+                    change,
+                }
+            },
+            "message.attempt.exhausted" => {
+                let AttemptData {
+                    endpoint_id,
+                    msg_id,
+                    last_attempt,
+                } = serde_json::from_value(data)?;
+
+                Self {
+                    endpoint_id,
+                    // Svix does not report how many attempts were made
+                    change: HealthChange::Failing {
+                        message_id: msg_id,
+                        attempts: 0,
+                        response_status_code: last_attempt
+                            .map_or(0, |attempt| attempt.response_status_code),
+                    },
+                }
+            },
+            "message.attempt.recovered" => {
+                let AttemptData { endpoint_id, .. } = serde_json::from_value(data)?;
+
+                Self {
+                    endpoint_id,
+                    change: HealthChange::Recovered,
+                }
+            },
+            _ => return Ok(None),
+        };
+
+        Ok(Some(event))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Envelope {
+    #[serde(rename = "type")]
+    event_type: String,
+    data: Value,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EndpointData {
+    endpoint_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AttemptData {
+    endpoint_id: String,
+    msg_id: String,
+    last_attempt: Option<LastAttempt>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LastAttempt {
+    response_status_code: i32,
+}
@@ -0,0 +1,339 @@
+use std::{
+    collections::HashSet,
+    time::{Duration, Instant},
+};
+
+use hub_core::{
+    anyhow::Result,
+    chrono::{self, Utc},
+    clap,
+    prelude::*,
+    tokio,
+};
+use sea_orm::{
+    prelude::*,
+    sea_query::{Expr, OnConflict},
+    DbBackend, Set, Statement, TransactionTrait,
+};
+
+use crate::{
+    db::Connection,
+    delivery::{Delivery, MessageIn},
+    entities::{message_idempotency_keys, outbox_messages},
+    metrics,
+};
+
+/// Arguments for draining the outbox of webhook messages
+#[derive(Debug, clap::Args)]
+pub struct OutboxArgs {
+    /// Seconds to wait before polling the outbox again when it is empty
+    #[arg(long, env, default_value_t = 1)]
+    pub outbox_poll_interval: u64,
+    /// Maximum number of messages published per poll
+    #[arg(long, env, default_value_t = 100)]
+    pub outbox_batch_size: u64,
+    /// Seconds to wait before the first retry of a failed message, doubled on every attempt
+    #[arg(long, env, default_value_t = 5)]
+    pub outbox_retry_base_delay: u64,
+    /// Maximum seconds to wait between two attempts of a failed message
+    #[arg(long, env, default_value_t = 3600)]
+    pub outbox_retry_max_delay: u64,
+    /// Number of attempts after which a failed message is dead-lettered
+    #[arg(long, env, default_value_t = 20)]
+    pub outbox_max_attempts: i32,
+    /// Seconds a worker holds the messages it claimed before another worker may claim them again
+    #[arg(long, env, default_value_t = 300)]
+    pub outbox_lease: i64,
+    /// Hours an event ID is remembered to skip redelivered events
+    #[arg(long, env, default_value_t = 168)]
+    pub outbox_idempotency_retention: i64,
+}
+
+/// Stores a message to be published to the application `svix_app_id` by the outbox worker.
+/// A message with an event ID that was already enqueued is skipped, in which case `false` is returned.
+/// Messages are published in order per channel, the first channel of the message.
+///
+/// # Errors
+/// This function fails if the message cannot be serialized or inserted.
+pub async fn enqueue<C: ConnectionTrait + TransactionTrait>(
+    conn: &C,
+    svix_app_id: String,
+    message: &MessageIn,
+) -> Result<bool> {
+    let tx = conn.begin().await?;
+
+    if let Some(event_id) = &message.event_id {
+        let idempotency_key = message_idempotency_keys::ActiveModel {
+            event_id: Set(event_id.clone()),
+            ..Default::default()
+        };
+
+        let inserted = message_idempotency_keys::Entity::insert(idempotency_key)
+            .on_conflict(
+                OnConflict::column(message_idempotency_keys::Column::EventId)
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .exec_without_returning(&tx)
+            .await?;
+
+        if inserted == 0 {
+            return Ok(false);
+        }
+    }
+
+    let channel = message
+        .channels
+        .as_ref()
+        .and_then(|channels| channels.first())
+        .cloned()
+        .unwrap_or_default();
+
+    let outbox_message = outbox_messages::ActiveModel {
+        svix_app_id: Set(svix_app_id),
+        channel: Set(channel),
+        message: Set(serde_json::to_value(message)?),
+        ..Default::default()
+    };
+
+    outbox_message.insert(&tx).await?;
+
+    tx.commit().await?;
+
+    Ok(true)
+}
+
+/// Publishes the messages stored in the outbox to the delivery backend until the process exits.
+/// Messages of a channel are published in the order they were enqueued. A message that fails to
+/// publish is retried with an exponential backoff, holding back the later messages of its channel,
+/// until it runs out of attempts and is dead-lettered.
+pub async fn run(db: Connection, delivery: Delivery, args: OutboxArgs) {
+    let OutboxArgs {
+        outbox_poll_interval,
+        outbox_batch_size,
+        outbox_retry_base_delay,
+        outbox_retry_max_delay,
+        outbox_max_attempts,
+        outbox_lease,
+        outbox_idempotency_retention,
+    } = args;
+
+    let drainer = Drainer {
+        batch_size: outbox_batch_size,
+        max_attempts: outbox_max_attempts,
+        lease: chrono::Duration::seconds(outbox_lease),
+        backoff: Backoff {
+            base: Duration::from_secs(outbox_retry_base_delay),
+            max: Duration::from_secs(outbox_retry_max_delay),
+        },
+    };
+
+    let mut pruned_at: Option<Instant> = None;
+
+    loop {
+        // pruned on a timer rather than when the outbox is empty, which may never happen under load
+        if pruned_at.map_or(true, |at| at.elapsed() >= PRUNE_INTERVAL) {
+            if let Err(e) = prune_idempotency_keys(&db, outbox_idempotency_retention).await {
+                error!("failed to prune idempotency keys: {:?}", e);
+            }
+
+            pruned_at = Some(Instant::now());
+        }
+
+        match drainer.drain(&db, &delivery).await {
+            Ok(count) if count > 0 => continue,
+            Ok(_) => (),
+            Err(e) => error!("failed to drain the outbox: {:?}", e),
+        }
+
+        tokio::time::sleep(Duration::from_secs(outbox_poll_interval)).await;
+    }
+}
+
+/// How often the expired idempotency keys are deleted.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Key of the advisory lock serializing claims, so two replicas never claim the messages of one
+/// channel out of order.
+const CLAIM_LOCK_KEY: i64 = 0x6f75_7462_6f78;
+
+/// Leases the next due messages of every channel whose earlier messages are neither leased nor
+/// waiting for a retry, returning them in publishing order.
+const CLAIM_SQL: &str = r#"
+UPDATE outbox_messages SET locked_until = $1
+WHERE id IN (
+    SELECT m.id FROM outbox_messages m
+    WHERE m.dead_lettered_at IS NULL
+        AND m.next_attempt_at <= $2
+        AND (m.locked_until IS NULL OR m.locked_until <= $2)
+        AND NOT EXISTS (
+            SELECT 1 FROM outbox_messages earlier
+            WHERE earlier.channel = m.channel
+                AND earlier.position < m.position
+                AND earlier.dead_lettered_at IS NULL
+                AND (earlier.next_attempt_at > $2 OR earlier.locked_until > $2)
+        )
+    ORDER BY m.channel, m.position
+    LIMIT $3
+    FOR UPDATE SKIP LOCKED
+)
+RETURNING *
+"#;
+
+struct Drainer {
+    batch_size: u64,
+    max_attempts: i32,
+    lease: chrono::Duration,
+    backoff: Backoff,
+}
+
+impl Drainer {
+    /// Publishes a batch of due messages, returning how many were claimed.
+    async fn drain(&self, db: &Connection, delivery: &Delivery) -> Result<usize> {
+        let messages = self.claim(db).await?;
+        let count = messages.len();
+
+        let mut blocked_channels = HashSet::new();
+        let mut skipped = Vec::new();
+
+        for outbox_message in messages {
+            if blocked_channels.contains(&outbox_message.channel) {
+                skipped.push(outbox_message.id);
+                continue;
+            }
+
+            let message: MessageIn = serde_json::from_value(outbox_message.message.clone())?;
+
+            // the backend skips messages with an idempotency key it already accepted, in case a
+            // previous attempt was published but not removed from the outbox
+            let idempotency_key = message.event_id.clone();
+
+            match delivery
+                .create_message(outbox_message.svix_app_id.clone(), message, idempotency_key)
+                .await
+            {
+                Ok(_) => {
+                    outbox_messages::Entity::delete_by_id(outbox_message.id)
+                        .exec(db.get())
+                        .await?;
+                },
+                Err(e) => {
+                    warn!(id = ?outbox_message.id, "failed to publish outbox message {:?}", e);
+
+                    if !self
+                        .record_failure(db, outbox_message.clone(), e.to_string())
+                        .await?
+                    {
+                        blocked_channels.insert(outbox_message.channel);
+                    }
+                },
+            }
+        }
+
+        if !skipped.is_empty() {
+            outbox_messages::Entity::update_many()
+                .col_expr(
+                    outbox_messages::Column::LockedUntil,
+                    Expr::value(Option::<DateTime>::None),
+                )
+                .filter(outbox_messages::Column::Id.is_in(skipped))
+                .exec(db.get())
+                .await?;
+        }
+
+        Ok(count)
+    }
+
+    /// Leases a batch of messages in a short transaction, so no lock is held while publishing.
+    async fn claim(&self, db: &Connection) -> Result<Vec<outbox_messages::Model>> {
+        let now = Utc::now();
+        let tx = db.get().begin().await?;
+
+        tx.execute(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "SELECT pg_advisory_xact_lock($1)",
+            [CLAIM_LOCK_KEY.into()],
+        ))
+        .await?;
+
+        let mut messages = outbox_messages::Entity::find()
+            .from_raw_sql(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                CLAIM_SQL,
+                [
+                    (now + self.lease).naive_utc().into(),
+                    now.naive_utc().into(),
+                    i64::try_from(self.batch_size).unwrap_or(i64::MAX).into(),
+                ],
+            ))
+            .all(&tx)
+            .await?;
+
+        tx.commit().await?;
+
+        messages.sort_by(|a, b| (&a.channel, a.position).cmp(&(&b.channel, b.position)));
+
+        Ok(messages)
+    }
+
+    /// Schedules the retry of a message that failed to publish, or dead-letters it once it ran out
+    /// of attempts. Returns whether the message was dead-lettered.
+    async fn record_failure(
+        &self,
+        db: &Connection,
+        outbox_message: outbox_messages::Model,
+        error: String,
+    ) -> Result<bool> {
+        let attempts = outbox_message.attempts + 1;
+        let dead_lettered = attempts >= self.max_attempts;
+        let id = outbox_message.id;
+
+        let mut active_message: outbox_messages::ActiveModel = outbox_message.into();
+        active_message.attempts = Set(attempts);
+        active_message.last_error = Set(Some(error));
+        active_message.locked_until = Set(None);
+
+        if dead_lettered {
+            error!(?id, attempts, "outbox message dead-lettered");
+
+            active_message.dead_lettered_at = Set(Some(Utc::now().naive_utc()));
+            metrics::OUTBOX_DEAD_LETTERS.inc();
+        } else {
+            let next_attempt_at = Utc::now() + self.backoff.delay(attempts);
+            active_message.next_attempt_at = Set(next_attempt_at.naive_utc());
+        }
+
+        active_message.update(db.get()).await?;
+
+        Ok(dead_lettered)
+    }
+}
+
+async fn prune_idempotency_keys(db: &Connection, retention: i64) -> Result<()> {
+    let expired_before = Utc::now() - chrono::Duration::hours(retention);
+
+    message_idempotency_keys::Entity::delete_many()
+        .filter(message_idempotency_keys::Column::CreatedAt.lt(expired_before.naive_utc()))
+        .exec(db.get())
+        .await?;
+
+    Ok(())
+}
+
+/// An exponential delay between the attempts of a failed operation, capped at `max`.
+pub(crate) struct Backoff {
+    pub(crate) base: Duration,
+    pub(crate) max: Duration,
+}
+
+impl Backoff {
+    /// The delay to wait after the given number of failed attempts.
+    pub(crate) fn delay(&self, attempts: i32) -> chrono::Duration {
+        let exponent = u32::try_from(attempts.saturating_sub(1))
+            .unwrap_or_default()
+            .min(16);
+        let delay = self.base.saturating_mul(2u32.pow(exponent)).min(self.max);
+
+        chrono::Duration::from_std(delay).unwrap_or_else(|_| chrono::Duration::max_value())
+    }
+}
@@ -0,0 +1,278 @@
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeSet, HashMap},
+    hash::{Hash, Hasher},
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::Duration,
+};
+
+use hub_core::{
+    anyhow::{anyhow, Result},
+    clap,
+    prelude::*,
+    tokio::{self, sync::mpsc},
+};
+
+use crate::{
+    db::Connection,
+    dead_letters::{self, DeadLetterQueue},
+    delivery::Delivery,
+    events,
+    validation::{InvalidPayload, PayloadValidation},
+    Consumed, EventsProducer, Source,
+};
+
+/// Arguments for processing the events consumed from Kafka
+#[derive(Debug, clap::Args)]
+pub struct PipelineArgs {
+    /// Number of workers processing events concurrently. Events of a project are always
+    /// processed in order by the same worker
+    #[arg(long, env, default_value_t = 16)]
+    pub consumer_workers: usize,
+    /// Number of events buffered by each worker before the consumer stops reading from Kafka
+    #[arg(long, env, default_value_t = 64)]
+    pub consumer_queue_size: usize,
+    /// Number of times an event is retried when it fails to be processed
+    #[arg(long, env, default_value_t = 3)]
+    pub consumer_max_retries: u32,
+    /// Milliseconds to wait before retrying a failed event, doubled on every retry
+    #[arg(long, env, default_value_t = 500)]
+    pub consumer_retry_delay: u64,
+}
+
+/// Routes consumed events to a bounded pool of workers, keyed by project.
+pub struct Pipeline {
+    workers: Vec<mpsc::Sender<Consumed>>,
+    offsets: Arc<Mutex<Offsets>>,
+}
+
+impl Pipeline {
+    /// Spawns the workers of the pipeline.
+    #[must_use]
+    pub fn new(
+        args: PipelineArgs,
+        db: Connection,
+        delivery: Delivery,
+        producer: EventsProducer,
+        dead_letter_queue: DeadLetterQueue,
+        validation: PayloadValidation,
+    ) -> Self {
+        let PipelineArgs {
+            consumer_workers,
+            consumer_queue_size,
+            consumer_max_retries,
+            consumer_retry_delay,
+        } = args;
+
+        let offsets = Arc::new(Mutex::new(Offsets::default()));
+
+        let workers = (0..consumer_workers.max(1))
+            .map(|_| {
+                let (tx, rx) = mpsc::channel(consumer_queue_size.max(1));
+
+                tokio::spawn(work(
+                    rx,
+                    offsets.clone(),
+                    db.clone(),
+                    delivery.clone(),
+                    producer.clone(),
+                    dead_letter_queue.clone(),
+                    validation,
+                    Retry {
+                        max_retries: consumer_max_retries,
+                        delay: Duration::from_millis(consumer_retry_delay),
+                    },
+                ));
+
+                tx
+            })
+            .collect();
+
+        Self { workers, offsets }
+    }
+
+    /// Queues an event on the worker of its project, waiting for room in the queue when it is full.
+    ///
+    /// # Errors
+    /// This function fails if the worker has stopped.
+    pub async fn dispatch(&self, consumed: Consumed) -> Result<()> {
+        let mut hasher = DefaultHasher::new();
+        consumed.msg.ordering_key().hash(&mut hasher);
+
+        #[allow(clippy::cast_possible_truncation)]
+        let worker = (hasher.finish() % self.workers.len() as u64) as usize;
+
+        lock(&self.offsets).read(&consumed.source);
+
+        self.workers[worker]
+            .send(consumed)
+            .await
+            .map_err(|_| anyhow!("pipeline worker {worker} stopped"))
+    }
+
+    /// The offsets to commit for the partitions that made progress since the last call, that is
+    /// the offset of the next message to read after every processed event of the partition.
+    #[must_use]
+    pub fn committable(&self) -> Vec<Offset> {
+        lock(&self.offsets).committable()
+    }
+
+    /// Forgets the events read from a partition the consumer no longer owns, e.g. when its offset
+    /// fails to be stored after a rebalance. Its events are read again from its committed offset
+    /// once it is assigned again.
+    pub fn revoke(&self, topic: &str, partition: i32) {
+        lock(&self.offsets)
+            .partitions
+            .remove(&(topic.to_string(), partition));
+    }
+}
+
+/// The position of the next message to read from a partition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Offset {
+    pub topic: String,
+    pub partition: i32,
+    pub offset: i64,
+}
+
+/// The events read from each partition that are still being processed by the workers.
+/// Events of a partition are spread over the workers and finish out of order, so a partition
+/// is only committed up to its oldest event in flight.
+#[derive(Debug, Default)]
+struct Offsets {
+    partitions: HashMap<(String, i32), Partition>,
+}
+
+#[derive(Debug, Default)]
+struct Partition {
+    in_flight: BTreeSet<i64>,
+    next_offset: i64,
+    committed: Option<i64>,
+}
+
+impl Offsets {
+    fn read(&mut self, source: &Source) {
+        let partition = self
+            .partitions
+            .entry((source.topic.clone(), source.partition))
+            .or_default();
+
+        // messages of a partition are read in order, so an earlier offset means the partition was
+        // revoked and assigned again, and every event since its committed offset is read again
+        if source.offset < partition.next_offset {
+            *partition = Partition::default();
+        }
+
+        partition.in_flight.insert(source.offset);
+        partition.next_offset = partition.next_offset.max(source.offset + 1);
+    }
+
+    fn finish(&mut self, source: &Source) {
+        if let Some(partition) = self
+            .partitions
+            .get_mut(&(source.topic.clone(), source.partition))
+        {
+            partition.in_flight.remove(&source.offset);
+        }
+    }
+
+    fn committable(&mut self) -> Vec<Offset> {
+        self.partitions
+            .iter_mut()
+            .filter_map(|((topic, partition_id), partition)| {
+                let offset = partition
+                    .in_flight
+                    .first()
+                    .copied()
+                    .unwrap_or(partition.next_offset);
+
+                if partition.committed == Some(offset) {
+                    return None;
+                }
+
+                partition.committed = Some(offset);
+
+                Some(Offset {
+                    topic: topic.clone(),
+                    partition: *partition_id,
+                    offset,
+                })
+            })
+            .collect()
+    }
+}
+
+fn lock(offsets: &Mutex<Offsets>) -> MutexGuard<'_, Offsets> {
+    // the offsets are only updated in place, so they are still consistent after a panic
+    offsets.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+struct Retry {
+    max_retries: u32,
+    delay: Duration,
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn work(
+    mut rx: mpsc::Receiver<Consumed>,
+    offsets: Arc<Mutex<Offsets>>,
+    db: Connection,
+    delivery: Delivery,
+    producer: EventsProducer,
+    dead_letter_queue: DeadLetterQueue,
+    validation: PayloadValidation,
+    retry: Retry,
+) {
+    while let Some(Consumed { msg, source }) = rx.recv().await {
+        let mut attempt = 0;
+
+        loop {
+            match events::process(
+                msg.clone(),
+                &source,
+                db.clone(),
+                delivery.clone(),
+                producer.clone(),
+                validation,
+            )
+            .await
+            {
+                Ok(()) => {
+                    lock(&offsets).finish(&source);
+
+                    break;
+                },
+                // an invalid payload is built again the same way, retrying it would not help
+                Err(e) if attempt < retry.max_retries && !e.is::<InvalidPayload>() => {
+                    warn!(?e, attempt, "failed to process message, retrying");
+
+                    tokio::time::sleep(retry.delay * 2u32.pow(attempt.min(16))).await;
+                    attempt += 1;
+                },
+                Err(e) => {
+                    match dead_letters::store(
+                        &db,
+                        &dead_letter_queue,
+                        &msg,
+                        &source,
+                        &e,
+                        attempt + 1,
+                    )
+                    .await
+                    {
+                        Ok(id) => {
+                            error!(?e, %id, "failed to process message, stored as dead letter");
+
+                            lock(&offsets).finish(&source);
+                        },
+                        // the offset is left uncommitted, so the event is read again after a restart
+                        Err(store_err) => {
+                            error!(?e, ?store_err, ?msg, "failed to process message");
+                        },
+                    }
+
+                    break;
+                },
+            }
+        }
+    }
+}
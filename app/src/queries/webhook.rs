@@ -1,9 +1,12 @@
 use async_graphql::{Context, Error, Object, Result, SimpleObject, Value};
 use hub_core::serde_json;
 use sea_orm::prelude::*;
-use svix::api::{EventTypeOut, Svix};
 
-use crate::{objects::Webhook, AppContext};
+use crate::{
+    delivery::{Delivery, EventTypeOut},
+    objects::Webhook,
+    AppContext,
+};
 
 #[derive(Debug, Clone, Copy, Default)]
 pub struct Query;
@@ -20,12 +23,11 @@ impl Query {
     ///
     /// This function returns an error if there was a problem with retrieving the event types.
     async fn event_types(&self, ctx: &Context<'_>) -> Result<Vec<EventType>> {
-        let svix = ctx.data::<Svix>()?;
+        let delivery = ctx.data::<Delivery>()?;
 
-        let event_types = svix.event_type().list(None).await?;
+        let event_types = delivery.list_event_types().await?;
 
         event_types
-            .data
             .iter()
             .map(|d| d.clone().try_into())
             .collect::<_>()
@@ -41,9 +43,16 @@ impl Query {
         ctx: &Context<'_>,
         #[graphql(key)] id: Uuid,
     ) -> Result<Option<Webhook>> {
-        let AppContext { webhook_loader, .. } = ctx.data::<AppContext>()?;
+        let app_ctx = ctx.data::<AppContext>()?;
+        let AppContext { webhook_loader, .. } = app_ctx;
 
-        webhook_loader.load_one(id).await
+        let webhook = webhook_loader.load_one(id).await?;
+
+        if let Some(webhook) = &webhook {
+            app_ctx.authorize(webhook.model.organization_id).await?;
+        }
+
+        Ok(webhook)
     }
 }
 
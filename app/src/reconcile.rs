@@ -0,0 +1,370 @@
+use std::{collections::HashMap, ops::Add, time::Duration};
+
+use hub_core::{
+    anyhow::Result,
+    chrono::{self, DateTime, Utc},
+    clap,
+    prelude::*,
+    tokio,
+    uuid::Uuid,
+};
+use sea_orm::{prelude::*, Set};
+
+use crate::{
+    db::Connection,
+    delivery::{Delivery, EndpointOut, EndpointUpdate},
+    endpoint_cache,
+    entities::{organization_applications, webhook_projects, webhooks},
+    event_types::ApiVersion,
+    metrics,
+    mutations::webhook::endpoint_channels,
+};
+
+/// Arguments for reconciling the webhooks stored in Postgres with the delivery backend
+#[derive(Debug, clap::Args)]
+pub struct ReconcileArgs {
+    /// Seconds between two reconciliations in the background. Disabled when not set
+    #[arg(long, env)]
+    pub reconcile_interval: Option<u64>,
+    /// Repair the drift found by the background reconciliation instead of only reporting it
+    #[arg(long, env)]
+    pub reconcile_repair: bool,
+    /// Seconds a webhook or endpoint is left alone after a change, so writes in progress are not reported as drift
+    #[arg(long, env, default_value_t = 600)]
+    pub reconcile_grace_period: u64,
+}
+
+/// A difference between the webhooks stored in Postgres and the delivery backend.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Drift {
+    /// The application of an organization cannot be read from the delivery backend. Never repaired,
+    /// as recreating it would lose its endpoints.
+    ApplicationUnavailable {
+        organization_id: Uuid,
+        svix_app_id: String,
+        error: String,
+    },
+    /// A webhook whose endpoint no longer exists. Repaired by deleting the webhook.
+    DanglingWebhook {
+        webhook_id: Uuid,
+        endpoint_id: String,
+    },
+    /// An endpoint that no webhook refers to. Repaired by deleting the endpoint.
+    OrphanEndpoint {
+        svix_app_id: String,
+        endpoint_id: String,
+    },
+    /// An endpoint whose channels differ from the organization and projects of its webhook.
+    /// Repaired by resetting the channels of the endpoint.
+    ChannelsMismatch {
+        webhook_id: Uuid,
+        expected: Vec<String>,
+        actual: Vec<String>,
+    },
+}
+
+impl Drift {
+    /// The name of the kind of drift, used as a metric label.
+    #[must_use]
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::ApplicationUnavailable { .. } => "application_unavailable",
+            Self::DanglingWebhook { .. } => "dangling_webhook",
+            Self::OrphanEndpoint { .. } => "orphan_endpoint",
+            Self::ChannelsMismatch { .. } => "channels_mismatch",
+        }
+    }
+}
+
+/// Reconciles every `reconcile_interval` seconds until the process exits. Returns immediately when no interval is set.
+pub async fn run(db: Connection, delivery: Delivery, args: ReconcileArgs) {
+    let ReconcileArgs {
+        reconcile_interval,
+        reconcile_repair,
+        reconcile_grace_period,
+    } = args;
+
+    let Some(interval) = reconcile_interval else {
+        return;
+    };
+
+    loop {
+        if let Err(e) = reconcile(
+            &db,
+            &delivery,
+            Duration::from_secs(reconcile_grace_period),
+            reconcile_repair,
+        )
+        .await
+        {
+            error!("failed to reconcile webhooks: {:?}", e);
+        }
+
+        tokio::time::sleep(Duration::from_secs(interval)).await;
+    }
+}
+
+/// Compares the organization applications, webhooks and webhook projects with the applications, endpoints and
+/// channels of the delivery backend, returning the drift found. Webhooks and endpoints changed within the
+/// `grace_period` are skipped. When `repair` is set, the drift that can be is repaired.
+///
+/// # Errors
+/// This function fails if the database cannot be read or a repair fails.
+pub async fn reconcile(
+    db: &Connection,
+    delivery: &Delivery,
+    grace_period: Duration,
+    repair: bool,
+) -> Result<Vec<Drift>> {
+    let settled_before = Utc::now() - chrono::Duration::from_std(grace_period)?;
+    let organization_applications = organization_applications::Entity::find()
+        .all(db.get())
+        .await?;
+
+    let mut drifts = Vec::new();
+
+    for organization_application in organization_applications {
+        drifts.extend(
+            reconcile_application(db, delivery, &organization_application, settled_before).await?,
+        );
+    }
+
+    for drift in &drifts {
+        warn!(?drift, repair, "webhooks drifted from the delivery backend");
+
+        metrics::RECONCILIATION_DRIFTS
+            .with_label_values(&[drift.kind()])
+            .inc();
+
+        if repair {
+            self::repair(db, delivery, drift).await?;
+        }
+    }
+
+    Ok(drifts)
+}
+
+async fn reconcile_application(
+    db: &Connection,
+    delivery: &Delivery,
+    organization_application: &organization_applications::Model,
+    settled_before: DateTime<Utc>,
+) -> Result<Vec<Drift>> {
+    let organization_applications::Model {
+        svix_app_id,
+        organization_id,
+        ..
+    } = organization_application;
+
+    let endpoints = match delivery.list_endpoints(svix_app_id.clone()).await {
+        Ok(endpoints) => endpoints,
+        Err(e) => {
+            return Ok(vec![Drift::ApplicationUnavailable {
+                organization_id: *organization_id,
+                svix_app_id: svix_app_id.clone(),
+                error: e.to_string(),
+            }]);
+        },
+    };
+
+    let mut endpoints: HashMap<String, EndpointOut> = endpoints
+        .into_iter()
+        .map(|endpoint| (endpoint.id.clone(), endpoint))
+        .collect();
+
+    let webhooks = webhooks::Entity::find()
+        .filter(webhooks::Column::OrganizationId.eq(*organization_id))
+        .find_with_related(webhook_projects::Entity)
+        .all(db.get())
+        .await?;
+
+    let mut drifts = Vec::new();
+
+    for (webhook, projects) in webhooks {
+        let endpoint = endpoints.remove(&webhook.endpoint_id);
+
+        let changed_at = webhook.updated_at.unwrap_or(webhook.created_at);
+        if changed_at > settled_before.naive_utc() {
+            continue;
+        }
+
+        let Some(endpoint) = endpoint else {
+            drifts.push(Drift::DanglingWebhook {
+                webhook_id: webhook.id,
+                endpoint_id: webhook.endpoint_id,
+            });
+
+            continue;
+        };
+
+        let projects: Vec<Uuid> = projects.iter().map(|project| project.project_id).collect();
+
+        let mut expected = endpoint_channels(*organization_id, &projects, ApiVersion::of(&webhook));
+        let mut actual = endpoint.channels.unwrap_or_default();
+        expected.sort();
+        actual.sort();
+
+        if expected != actual {
+            drifts.push(Drift::ChannelsMismatch {
+                webhook_id: webhook.id,
+                expected,
+                actual,
+            });
+        }
+    }
+
+    // endpoints with an unreadable creation date are assumed to be settled
+    drifts.extend(
+        endpoints
+            .into_values()
+            .filter(|endpoint| {
+                DateTime::parse_from_rfc3339(&endpoint.created_at)
+                    .map_or(true, |created_at| created_at < settled_before)
+            })
+            .map(|endpoint| Drift::OrphanEndpoint {
+                svix_app_id: svix_app_id.clone(),
+                endpoint_id: endpoint.id,
+            }),
+    );
+
+    Ok(drifts)
+}
+
+async fn repair(db: &Connection, delivery: &Delivery, drift: &Drift) -> Result<()> {
+    match drift {
+        Drift::ApplicationUnavailable { .. } => Ok(()),
+        Drift::DanglingWebhook { webhook_id, .. } => {
+            // its projects are removed by the cascading foreign key
+            webhooks::Entity::delete_by_id(*webhook_id)
+                .exec(db.get())
+                .await?;
+
+            Ok(())
+        },
+        Drift::OrphanEndpoint {
+            svix_app_id,
+            endpoint_id,
+        } => {
+            delivery
+                .delete_endpoint(svix_app_id.clone(), endpoint_id.clone())
+                .await
+        },
+        Drift::ChannelsMismatch {
+            webhook_id,
+            expected,
+            ..
+        } => {
+            let Some((webhook, Some(organization_application))) =
+                webhooks::Entity::find_by_id(*webhook_id)
+                    .find_also_related(organization_applications::Entity)
+                    .one(db.get())
+                    .await?
+            else {
+                return Ok(());
+            };
+
+            let endpoint = delivery
+                .get_endpoint(
+                    organization_application.svix_app_id.clone(),
+                    webhook.endpoint_id.clone(),
+                )
+                .await?;
+
+            set_channels(
+                db,
+                delivery,
+                webhook,
+                organization_application.svix_app_id,
+                endpoint,
+                expected.clone(),
+            )
+            .await
+        },
+    }
+}
+
+/// Subscribes the endpoint of every webhook to the channel of its organization, which endpoints created before
+/// organization-wide events existed do not listen on. Their other channels are left as they are.
+/// Returns the number of endpoints subscribed.
+///
+/// # Errors
+/// This function fails if the webhooks cannot be read or an endpoint cannot be read or updated.
+pub async fn backfill_organization_channels(db: &Connection, delivery: &Delivery) -> Result<usize> {
+    let webhooks = webhooks::Entity::find()
+        .find_also_related(organization_applications::Entity)
+        .all(db.get())
+        .await?;
+
+    let mut subscribed = 0;
+
+    for (webhook, organization_application) in webhooks {
+        let Some(organization_application) = organization_application else {
+            continue;
+        };
+
+        let endpoint = delivery
+            .get_endpoint(
+                organization_application.svix_app_id.clone(),
+                webhook.endpoint_id.clone(),
+            )
+            .await?;
+
+        let organization_channel = ApiVersion::of(&webhook).channel(webhook.organization_id);
+        let mut channels = endpoint.channels.clone().unwrap_or_default();
+
+        if channels.contains(&organization_channel) {
+            continue;
+        }
+
+        channels.push(organization_channel);
+
+        info!(webhook_id = %webhook.id, "subscribing endpoint to its organization channel");
+
+        set_channels(
+            db,
+            delivery,
+            webhook,
+            organization_application.svix_app_id,
+            endpoint,
+            channels,
+        )
+        .await?;
+
+        subscribed += 1;
+    }
+
+    Ok(subscribed)
+}
+
+/// Replaces the channels of the endpoint of a webhook, then caches the updated endpoint on the webhook.
+async fn set_channels(
+    db: &Connection,
+    delivery: &Delivery,
+    webhook: webhooks::Model,
+    svix_app_id: String,
+    endpoint: EndpointOut,
+    channels: Vec<String>,
+) -> Result<()> {
+    let update_endpoint = EndpointUpdate {
+        channels: Some(channels),
+        filter_types: endpoint.filter_types,
+        version: endpoint.version.add(1),
+        description: endpoint.description,
+        disabled: endpoint.disabled,
+        rate_limit: endpoint.rate_limit,
+        url: endpoint.url,
+        uid: endpoint.uid,
+    };
+
+    let endpoint = delivery
+        .update_endpoint(svix_app_id, webhook.endpoint_id.clone(), update_endpoint)
+        .await?;
+
+    let mut active_webhook: webhooks::ActiveModel = webhook.into();
+    endpoint_cache::store(&mut active_webhook, &endpoint);
+    active_webhook.updated_at = Set(Some(Utc::now().naive_utc()));
+
+    active_webhook.update(db.get()).await?;
+
+    Ok(())
+}
@@ -0,0 +1,183 @@
+//! Validation of the messages sent to webhooks against the JSON schema published for their event type.
+//! The published schemas are a committed snapshot, so a payload that drifts from them is caught here instead of
+//! silently changing the catalog.
+
+use std::fmt;
+
+use hub_core::{anyhow::Result, clap, prelude::*};
+use serde_json::Value;
+
+use crate::{
+    event_types::{ApiVersion, FilterType},
+    metrics,
+};
+
+/// Arguments for validating the messages sent to webhooks
+#[derive(Debug, clap::Args)]
+pub struct ValidationArgs {
+    /// What to do with messages that do not match the schema of their event type. `strict` rejects them and
+    /// stores their event as a dead letter, `lenient` sends them anyway
+    #[arg(long, env, value_enum, default_value_t = PayloadValidation::Lenient)]
+    pub payload_validation: PayloadValidation,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum PayloadValidation {
+    /// Reject messages that do not match their schema
+    Strict,
+    /// Log and count messages that do not match their schema, then send them
+    Lenient,
+}
+
+impl PayloadValidation {
+    /// The name of the mode, as used in the metric labels.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Strict => "strict",
+            Self::Lenient => "lenient",
+        }
+    }
+
+    /// Checks a message against the published schema of its event type in an API version. Violations are logged and
+    /// counted in both modes.
+    ///
+    /// # Errors
+    /// In strict mode, this function fails with [`InvalidPayload`] if the message does not match the schema.
+    pub fn check(
+        self,
+        event_type: FilterType,
+        api_version: ApiVersion,
+        message: &Value,
+    ) -> Result<()> {
+        let violations = match event_type.registered_schema(api_version) {
+            Some(schema) => violations(schema, message),
+            None => vec![format!(
+                "no schema is published for API version {}",
+                api_version.number()
+            )],
+        };
+
+        if violations.is_empty() {
+            return Ok(());
+        }
+
+        metrics::PAYLOAD_VIOLATIONS
+            .with_label_values(&[event_type.name(), self.name()])
+            .inc();
+
+        match self {
+            Self::Strict => Err(InvalidPayload {
+                event_type,
+                api_version,
+                violations,
+            }
+            .into()),
+            Self::Lenient => {
+                warn!(
+                    event_type = event_type.name(),
+                    ?api_version,
+                    ?violations,
+                    "sending a message that does not match its schema"
+                );
+
+                Ok(())
+            },
+        }
+    }
+}
+
+/// A message rejected because it does not match the schema of its event type.
+/// The pipeline stores its event as a dead letter without retrying it.
+#[derive(Debug)]
+pub struct InvalidPayload {
+    pub event_type: FilterType,
+    pub api_version: ApiVersion,
+    pub violations: Vec<String>,
+}
+
+impl fmt::Display for InvalidPayload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} message of API version {} does not match its schema: {}",
+            self.event_type.name(),
+            self.api_version.number(),
+            self.violations.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for InvalidPayload {}
+
+/// Checks a value against a JSON schema, returning the violations found.
+/// Only the `type`, `enum`, `properties` and `required` keywords used by the registered schemas are supported,
+/// other keywords such as `format` are ignored.
+/// Properties the schema does not declare are violations too, as receivers only know of the declared ones.
+#[must_use]
+pub fn violations(schema: &Value, value: &Value) -> Vec<String> {
+    let mut violations = Vec::new();
+
+    check(schema, value, "$", &mut violations);
+
+    violations
+}
+
+fn check(schema: &Value, value: &Value, path: &str, violations: &mut Vec<String>) {
+    // the type is either a single type or a list of the types allowed
+    let expected: Vec<&str> = match &schema["type"] {
+        Value::String(expected) => vec![expected.as_str()],
+        Value::Array(expected) => expected.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    };
+
+    if !expected.is_empty() && !expected.iter().any(|expected| is_of_type(value, expected)) {
+        violations.push(format!("{path} is not of type {}", expected.join(" or ")));
+
+        return;
+    }
+
+    if let Some(values) = schema["enum"].as_array() {
+        if !values.contains(value) {
+            let values = Value::from(values.clone());
+
+            violations.push(format!("{path} is not one of {values}"));
+        }
+    }
+
+    let (Some(properties), Some(object)) = (schema["properties"].as_object(), value.as_object())
+    else {
+        return;
+    };
+
+    for field in schema["required"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+    {
+        if !object.contains_key(field) {
+            violations.push(format!("{path}.{field} is missing"));
+        }
+    }
+
+    for (field, value) in object {
+        match properties.get(field) {
+            Some(schema) => check(schema, value, &format!("{path}.{field}"), violations),
+            None => violations.push(format!("{path}.{field} is not declared")),
+        }
+    }
+}
+
+fn is_of_type(value: &Value, expected: &str) -> bool {
+    match expected {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "number" => value.is_number(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
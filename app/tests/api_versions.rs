@@ -0,0 +1,160 @@
+mod common;
+
+use holaplex_hub_webhooks::{
+    proto::{self, nft_events, webhook_events::Event},
+    Services,
+};
+use hub_core::uuid::Uuid;
+use serde_json::{json, Value};
+
+use self::common::{create_webhook, TestApp, CREATE_WEBHOOK, EDIT_WEBHOOK};
+
+const WEBHOOK_API_VERSION: &str = r#"
+    query WebhookApiVersion($id: UUID!) {
+        _entities(representations: [{ __typename: "Webhook", id: $id }]) {
+            ... on Webhook { apiVersion channels }
+        }
+    }
+"#;
+
+#[tokio::test]
+async fn webhooks_receive_messages_of_their_api_version() {
+    let app = TestApp::start().await;
+    let user_id = Uuid::new_v4();
+    let project_id = Uuid::new_v4();
+
+    let organization_id = app.create_organization(user_id).await;
+    let svix_app_id = app.svix_app_id(organization_id).await;
+
+    let response = create_webhook(&app, user_id, organization_id, project_id, &[
+        "DROP_MINTED",
+    ])
+    .await;
+    assert_eq!(response["errors"], Value::Null, "{response}");
+
+    let response = app
+        .graphql(
+            user_id,
+            CREATE_WEBHOOK,
+            json!({
+                "input": {
+                    "url": "https://example.com/pinned",
+                    "organization": organization_id,
+                    "description": "Pinned webhook",
+                    "projects": [project_id],
+                    "filterTypes": ["DROP_MINTED"],
+                    "apiVersion": "V1",
+                }
+            }),
+        )
+        .await;
+    assert_eq!(response["errors"], Value::Null, "{response}");
+
+    let pinned = &response["data"]["createWebhook"]["webhook"];
+    let pinned_id = pinned["id"].clone();
+    let pinned_endpoint_id = pinned["endpointId"].clone();
+
+    let response = app
+        .graphql(user_id, WEBHOOK_API_VERSION, json!({ "id": pinned_id }))
+        .await;
+    assert_eq!(
+        response["data"]["_entities"][0],
+        json!({ "apiVersion": "V1", "channels": [project_id] })
+    );
+
+    let mint_id = Uuid::new_v4();
+    let drop_id = Uuid::new_v4();
+
+    let key = proto::NftEventKey {
+        id: mint_id.to_string(),
+        project_id: project_id.to_string(),
+        ..Default::default()
+    };
+
+    let event = proto::NftEvents {
+        event: Some(nft_events::Event::DropMinted(proto::MintCreation {
+            drop_id: drop_id.to_string(),
+            status: proto::CreationStatus::Created.into(),
+        })),
+    };
+
+    app.process(Services::Nfts(key, event)).await;
+
+    let mut messages = app.svix.wait_for_messages(&svix_app_id, 2).await;
+    messages.sort_by(|a, b| a["eventId"].as_str().cmp(&b["eventId"].as_str()));
+
+    let event_id = format!("drop.minted.{mint_id}.CREATED.0-0");
+
+    assert_eq!(messages.len(), 2);
+    assert_eq!(messages[0]["eventId"], event_id);
+    assert_eq!(messages[0]["channels"], json!([project_id]));
+    assert_eq!(messages[0]["payload"]["id"], event_id);
+    assert_eq!(messages[0]["payload"]["api_version"], 1);
+    assert_eq!(
+        messages[0]["payload"]["payload"]["creation_status"],
+        "CREATED"
+    );
+    assert_eq!(messages[1]["eventId"], format!("{event_id}.v2"));
+    assert_eq!(messages[1]["channels"], json!([format!("{project_id}.v2")]));
+    assert_eq!(messages[1]["payload"]["id"], format!("{event_id}.v2"));
+    assert_eq!(messages[1]["payload"]["api_version"], 2);
+    assert_eq!(
+        messages[0]["payload"]["created_at"],
+        "2023-05-09T10:00:00.000Z"
+    );
+    assert_eq!(
+        messages[1]["payload"]["created_at"],
+        messages[0]["payload"]["created_at"]
+    );
+    assert_eq!(messages[1]["payload"]["payload"]["status"], "CREATED");
+    assert_eq!(
+        messages[1]["payload"]["payload"].get("creation_status"),
+        None
+    );
+
+    let response = app
+        .graphql(
+            user_id,
+            EDIT_WEBHOOK,
+            json!({
+                "input": {
+                    "webhook": pinned_id,
+                    "url": "https://example.com/pinned",
+                    "description": "Pinned webhook",
+                    "projects": [project_id],
+                    "filterTypes": ["DROP_MINTED"],
+                    "apiVersion": "V2",
+                }
+            }),
+        )
+        .await;
+    assert_eq!(response["errors"], Value::Null, "{response}");
+
+    let endpoint = app
+        .svix
+        .endpoints(&svix_app_id)
+        .into_iter()
+        .find(|endpoint| endpoint["id"] == pinned_endpoint_id)
+        .unwrap();
+    let mut channels: Vec<Value> = endpoint["channels"].as_array().unwrap().clone();
+    channels.sort_by(|a, b| a.as_str().cmp(&b.as_str()));
+
+    let mut expected = vec![
+        json!(format!("{organization_id}.v2")),
+        json!(format!("{project_id}.v2")),
+    ];
+    expected.sort_by(|a, b| a.as_str().cmp(&b.as_str()));
+
+    assert_eq!(channels, expected);
+
+    let events = app.producer.events();
+    let (last_event, _) = events.last().unwrap();
+
+    let Some(Event::Updated(update)) = &last_event.event else {
+        panic!("expected an update, got {last_event:?}");
+    };
+    assert_eq!(update.changes.len(), 1);
+    assert_eq!(update.changes[0].field, "api_version");
+    assert_eq!(update.changes[0].previous, "1");
+    assert_eq!(update.changes[0].current, "2");
+}
@@ -0,0 +1,58 @@
+mod common;
+
+use holaplex_hub_webhooks::applications;
+use hub_core::uuid::Uuid;
+use serde_json::Value;
+
+use self::common::{create_webhook, TestApp};
+
+#[tokio::test]
+async fn create_webhook_creates_missing_application() {
+    let app = TestApp::start().await;
+    let user_id = Uuid::new_v4();
+    let organization_id = Uuid::new_v4();
+
+    app.add_member(organization_id, user_id).await;
+
+    let response = create_webhook(&app, user_id, organization_id, Uuid::new_v4(), &[
+        "CUSTOMER_CREATED",
+    ])
+    .await;
+
+    assert_eq!(response["errors"], Value::Null, "{response}");
+
+    let svix_app_id = app.svix_app_id(organization_id).await;
+    assert_eq!(app.svix.endpoints(&svix_app_id).len(), 1);
+}
+
+#[tokio::test]
+async fn backfill_reuses_existing_application() {
+    let app = TestApp::start().await;
+    let organization_id = Uuid::new_v4();
+    let missing_organization_id = Uuid::new_v4();
+
+    let existing_app_id = app
+        .delivery
+        .create_application("Existing".to_string(), organization_id.to_string())
+        .await
+        .unwrap();
+
+    let created = applications::backfill(&app.db, &app.delivery, &[
+        organization_id,
+        missing_organization_id,
+    ])
+    .await
+    .unwrap();
+
+    assert_eq!(created, 2);
+    assert_eq!(app.svix_app_id(organization_id).await, existing_app_id);
+
+    let created = applications::backfill(&app.db, &app.delivery, &[
+        organization_id,
+        missing_organization_id,
+    ])
+    .await
+    .unwrap();
+
+    assert_eq!(created, 0);
+}
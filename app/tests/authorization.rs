@@ -0,0 +1,59 @@
+mod common;
+
+use holaplex_hub_webhooks::members;
+use hub_core::uuid::Uuid;
+use serde_json::Value;
+
+use self::common::{create_webhook, TestApp};
+
+#[tokio::test]
+async fn create_webhook_requires_membership() {
+    let app = TestApp::start().await;
+    let project_id = Uuid::new_v4();
+
+    let organization_id = app.create_organization(Uuid::new_v4()).await;
+    let svix_app_id = app.svix_app_id(organization_id).await;
+
+    let response = create_webhook(&app, Uuid::new_v4(), organization_id, project_id, &[
+        "CUSTOMER_CREATED",
+    ])
+    .await;
+
+    assert_ne!(response["errors"], Value::Null);
+    assert!(app.svix.endpoints(&svix_app_id).is_empty());
+    assert!(app.producer.events().is_empty());
+}
+
+#[tokio::test]
+async fn backfilled_members_can_manage_webhooks() {
+    let app = TestApp::start().await;
+    let owner_id = Uuid::new_v4();
+    let user_id = Uuid::new_v4();
+    let project_id = Uuid::new_v4();
+
+    let organization_id = app.create_organization(owner_id).await;
+
+    let response = create_webhook(&app, user_id, organization_id, project_id, &[
+        "CUSTOMER_CREATED",
+    ])
+    .await;
+    assert_ne!(response["errors"], Value::Null);
+
+    let file = std::env::temp_dir().join(format!("members-{}.csv", Uuid::new_v4()));
+    let csv = format!(
+        "organization_id,user_id\n{organization_id},{owner_id}\n{organization_id},{user_id}\n"
+    );
+    std::fs::write(&file, csv).unwrap();
+
+    let added = members::backfill(&app.db, &file).await.unwrap();
+    std::fs::remove_file(&file).unwrap();
+
+    // the owner was already added by the organization creation
+    assert_eq!(added, 1);
+
+    let response = create_webhook(&app, user_id, organization_id, project_id, &[
+        "CUSTOMER_CREATED",
+    ])
+    .await;
+    assert_eq!(response["errors"], Value::Null, "{response}");
+}
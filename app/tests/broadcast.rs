@@ -0,0 +1,133 @@
+mod common;
+
+use std::time::Duration;
+
+use holaplex_hub_webhooks::{
+    event_types::ApiVersion,
+    proto::{self, nft_events, webhook_events::Event},
+    Services,
+};
+use hub_core::uuid::Uuid;
+use serde_json::{json, Value};
+
+use self::common::{create_webhook, customer_created, TestApp};
+
+#[tokio::test]
+async fn create_webhook_then_broadcast() {
+    let app = TestApp::start().await;
+    let user_id = Uuid::new_v4();
+    let project_id = Uuid::new_v4();
+
+    let organization_id = app.create_organization(user_id).await;
+    let svix_app_id = app.svix_app_id(organization_id).await;
+
+    let response = create_webhook(&app, user_id, organization_id, project_id, &[
+        "CUSTOMER_CREATED",
+    ])
+    .await;
+
+    assert_eq!(response["errors"], Value::Null, "{response}");
+
+    let endpoints = app.svix.endpoints(&svix_app_id);
+    assert_eq!(endpoints.len(), 1);
+    assert_eq!(
+        endpoints[0]["id"],
+        response["data"]["createWebhook"]["webhook"]["endpointId"]
+    );
+    assert_eq!(endpoints[0]["filterTypes"], json!(["customer.created"]));
+
+    let events = app.producer.events();
+    assert_eq!(events.len(), 1);
+    assert!(matches!(events[0].0.event, Some(Event::Created(_))));
+    assert_eq!(events[0].1.user_id, user_id.to_string());
+
+    let (customer_id, msg) = customer_created(project_id);
+    app.process(msg).await;
+
+    let messages = app.svix.wait_for_messages(&svix_app_id, 1).await;
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0]["eventType"], "customer.created");
+    assert_eq!(
+        messages[0]["channels"],
+        json!([ApiVersion::LATEST.channel(project_id)])
+    );
+    assert_eq!(
+        messages[0]["payload"]["payload"]["customer_id"],
+        customer_id.to_string()
+    );
+
+    let body = &messages[0]["payload"];
+    assert_eq!(body["id"], messages[0]["eventId"]);
+    assert_eq!(body["api_version"], ApiVersion::LATEST.number());
+    assert_eq!(body["organization_id"], organization_id.to_string());
+    assert!(body["created_at"].is_string(), "{body}");
+    assert_eq!(
+        body["source"],
+        json!({ "topic": "hub-customers", "partition": 0, "offset": 0 })
+    );
+}
+
+#[tokio::test]
+async fn redelivered_event_is_broadcast_once() {
+    let app = TestApp::start().await;
+    let user_id = Uuid::new_v4();
+    let project_id = Uuid::new_v4();
+
+    let organization_id = app.create_organization(user_id).await;
+    let svix_app_id = app.svix_app_id(organization_id).await;
+
+    create_webhook(&app, user_id, organization_id, project_id, &[
+        "CUSTOMER_CREATED",
+    ])
+    .await;
+
+    let (_, msg) = customer_created(project_id);
+    app.process(msg.clone()).await;
+    app.process(msg).await;
+
+    app.svix.wait_for_messages(&svix_app_id, 1).await;
+    tokio::time::sleep(Duration::from_secs(2)).await;
+
+    assert_eq!(app.svix.messages(&svix_app_id).len(), 1);
+}
+
+#[tokio::test]
+async fn recurring_status_is_broadcast_again() {
+    let app = TestApp::start().await;
+    let user_id = Uuid::new_v4();
+    let project_id = Uuid::new_v4();
+
+    let organization_id = app.create_organization(user_id).await;
+    let svix_app_id = app.svix_app_id(organization_id).await;
+
+    create_webhook(&app, user_id, organization_id, project_id, &[
+        "DROP_MINTED",
+    ])
+    .await;
+
+    let key = proto::NftEventKey {
+        id: Uuid::new_v4().to_string(),
+        project_id: project_id.to_string(),
+        ..Default::default()
+    };
+
+    let event = proto::NftEvents {
+        event: Some(nft_events::Event::DropMinted(proto::MintCreation {
+            drop_id: Uuid::new_v4().to_string(),
+            status: proto::CreationStatus::Failed.into(),
+        })),
+    };
+
+    let msg = Services::Nfts(key, event);
+
+    // the mint fails, the event is redelivered, then the retried mint fails again
+    app.process_at(msg.clone(), 0).await;
+    app.process_at(msg.clone(), 0).await;
+    app.process_at(msg, 1).await;
+
+    let messages = app.svix.wait_for_messages(&svix_app_id, 2).await;
+    tokio::time::sleep(Duration::from_secs(2)).await;
+
+    assert_eq!(app.svix.messages(&svix_app_id).len(), 2);
+    assert_ne!(messages[0]["eventId"], messages[1]["eventId"]);
+}
@@ -0,0 +1,695 @@
+//! An in-memory stand-in for the routes of the Svix API used by the service.
+//! Messages are recorded rather than delivered, so tests can assert on what was broadcast. Resending a message
+//! records a successful attempt, the only attempts the fake server ever makes.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use hub_core::{chrono::Utc, tokio, uuid::Uuid};
+use poem::{
+    delete, get, handler,
+    http::{HeaderMap, StatusCode},
+    listener::{Acceptor, Listener, TcpListener},
+    middleware::{AddData, NormalizePath, TrailingSlash},
+    post, put,
+    web::{Data, Json, Path, Query},
+    EndpointExt, Response, Route, Server,
+};
+use serde_json::{json, Value};
+
+/// How long [`FakeSvix::wait_for_messages`] waits before giving up.
+const WAIT_TIMEOUT: Duration = Duration::from_secs(10);
+const WAIT_INTERVAL: Duration = Duration::from_millis(50);
+
+#[derive(Default)]
+struct State {
+    applications: HashMap<String, Value>,
+    endpoints: HashMap<String, Vec<Value>>,
+    secrets: HashMap<String, String>,
+    event_types: Vec<Value>,
+    messages: HashMap<String, Vec<Value>>,
+    idempotency_keys: HashMap<String, Value>,
+    attempts: HashMap<String, Vec<Value>>,
+    recoveries: HashMap<String, Vec<Value>>,
+    failing_endpoints: HashSet<String>,
+}
+
+type SharedState = Arc<Mutex<State>>;
+
+/// A running fake Svix server.
+#[derive(Clone)]
+pub struct FakeSvix {
+    url: String,
+    state: SharedState,
+}
+
+impl FakeSvix {
+    /// Starts the server on a random local port.
+    pub async fn start() -> Self {
+        let state = SharedState::default();
+
+        let acceptor = TcpListener::bind("127.0.0.1:0")
+            .into_acceptor()
+            .await
+            .expect("failed to bind the fake svix server");
+
+        let addr = acceptor
+            .local_addr()
+            .into_iter()
+            .find_map(|addr| addr.as_socket_addr().copied())
+            .expect("fake svix server has no local address");
+
+        let app = Route::new()
+            .at("/api/v1/app", post(create_application))
+            .at(
+                "/api/v1/app/:app_id",
+                get(get_application).delete(delete_application),
+            )
+            .at(
+                "/api/v1/app/:app_id/endpoint",
+                get(list_endpoints).post(create_endpoint),
+            )
+            .at(
+                "/api/v1/app/:app_id/endpoint/:endpoint_id",
+                get(get_endpoint).put(update_endpoint).delete(delete_endpoint),
+            )
+            .at(
+                "/api/v1/app/:app_id/endpoint/:endpoint_id/secret",
+                get(get_endpoint_secret),
+            )
+            .at(
+                "/api/v1/app/:app_id/endpoint/:endpoint_id/secret/rotate",
+                post(rotate_endpoint_secret),
+            )
+            .at(
+                "/api/v1/app/:app_id/endpoint/:endpoint_id/recover",
+                post(recover_endpoint),
+            )
+            .at(
+                "/api/v1/event-type",
+                get(list_event_types).post(create_event_type),
+            )
+            .at("/api/v1/event-type/:event_type_name", put(update_event_type))
+            .at("/api/v1/app/:app_id/msg", post(create_message))
+            .at(
+                "/api/v1/app/:app_id/msg/:message_id/endpoint/:endpoint_id/resend",
+                post(resend_message),
+            )
+            .at(
+                "/api/v1/app/:app_id/attempt/endpoint/:endpoint_id",
+                get(list_endpoint_attempts),
+            )
+            .at(
+                "/api/v1/app/:app_id/attempt/msg/:message_id",
+                get(list_message_attempts),
+            )
+            .with(NormalizePath::new(TrailingSlash::Trim))
+            .with(AddData::new(state.clone()));
+
+        tokio::spawn(Server::new_with_acceptor(acceptor).run(app));
+
+        Self {
+            url: format!("http://{addr}"),
+            state,
+        }
+    }
+
+    /// The base URL of the server, to use as `svix_base_url`.
+    #[must_use]
+    pub fn url(&self) -> String {
+        self.url.clone()
+    }
+
+    /// The endpoints created in an application.
+    #[must_use]
+    pub fn endpoints(&self, app_id: &str) -> Vec<Value> {
+        let state = self.state.lock().unwrap();
+
+        state.endpoints.get(app_id).cloned().unwrap_or_default()
+    }
+
+    /// The messages sent to an application.
+    #[must_use]
+    pub fn messages(&self, app_id: &str) -> Vec<Value> {
+        let state = self.state.lock().unwrap();
+
+        state.messages.get(app_id).cloned().unwrap_or_default()
+    }
+
+    /// The attempts made in an application, in the order they were made.
+    #[must_use]
+    pub fn attempts(&self, app_id: &str) -> Vec<Value> {
+        let state = self.state.lock().unwrap();
+
+        state.attempts.get(app_id).cloned().unwrap_or_default()
+    }
+
+    /// The recoveries requested for the endpoints of an application, in the order they were requested.
+    #[must_use]
+    pub fn recoveries(&self, app_id: &str) -> Vec<Value> {
+        let state = self.state.lock().unwrap();
+
+        state.recoveries.get(app_id).cloned().unwrap_or_default()
+    }
+
+    /// Makes the updates of an endpoint fail with a server error until [`FakeSvix::restore_endpoint`] is called.
+    pub fn fail_endpoint(&self, endpoint_id: &str) {
+        let mut state = self.state.lock().unwrap();
+
+        state.failing_endpoints.insert(endpoint_id.to_string());
+    }
+
+    /// Lets the updates of an endpoint made to fail by [`FakeSvix::fail_endpoint`] succeed again.
+    pub fn restore_endpoint(&self, endpoint_id: &str) {
+        let mut state = self.state.lock().unwrap();
+
+        state.failing_endpoints.remove(endpoint_id);
+    }
+
+    /// Waits until at least `count` messages were sent to an application, returning them.
+    ///
+    /// # Panics
+    /// Panics if the messages are not sent within 10 seconds.
+    pub async fn wait_for_messages(&self, app_id: &str, count: usize) -> Vec<Value> {
+        let started = tokio::time::Instant::now();
+
+        loop {
+            let messages = self.messages(app_id);
+
+            if messages.len() >= count {
+                return messages;
+            }
+
+            assert!(
+                started.elapsed() < WAIT_TIMEOUT,
+                "expected {count} messages for application {app_id}, got {}",
+                messages.len()
+            );
+
+            tokio::time::sleep(WAIT_INTERVAL).await;
+        }
+    }
+}
+
+fn new_id(prefix: &str) -> String {
+    format!("{prefix}_{}", Uuid::new_v4().simple())
+}
+
+fn now() -> String {
+    Utc::now().to_rfc3339()
+}
+
+fn not_found(detail: &str) -> Response {
+    error(StatusCode::NOT_FOUND, "not_found", detail)
+}
+
+fn error(status: StatusCode, code: &str, detail: &str) -> Response {
+    Response::builder()
+        .status(status)
+        .content_type("application/json")
+        .body(json!({ "code": code, "detail": detail }).to_string())
+}
+
+fn ok(status: StatusCode, body: &Value) -> Response {
+    Response::builder()
+        .status(status)
+        .content_type("application/json")
+        .body(body.to_string())
+}
+
+fn list(data: Vec<Value>) -> Value {
+    json!({ "data": data, "done": true, "iterator": null, "prevIterator": null })
+}
+
+#[handler]
+fn create_application(Data(state): Data<&SharedState>, Json(body): Json<Value>) -> Response {
+    let mut state = state.lock().unwrap();
+
+    if !body["uid"].is_null()
+        && state
+            .applications
+            .values()
+            .any(|application| application["uid"] == body["uid"])
+    {
+        return error(
+            StatusCode::CONFLICT,
+            "conflict",
+            "application uid already exists",
+        );
+    }
+
+    let application = json!({
+        "id": new_id("app"),
+        "name": body["name"],
+        "uid": body["uid"],
+        "rateLimit": body["rateLimit"],
+        "metadata": {},
+        "createdAt": now(),
+        "updatedAt": now(),
+    });
+
+    let id = application["id"].as_str().unwrap_or_default().to_string();
+    state.applications.insert(id, application.clone());
+
+    ok(StatusCode::CREATED, &application)
+}
+
+/// Applications can be fetched by ID or uid, like in Svix.
+#[handler]
+fn get_application(Data(state): Data<&SharedState>, Path(app_id): Path<String>) -> Response {
+    let state = state.lock().unwrap();
+
+    let application = state.applications.get(&app_id).or_else(|| {
+        state
+            .applications
+            .values()
+            .find(|application| application["uid"] == app_id.as_str())
+    });
+
+    match application {
+        Some(application) => ok(StatusCode::OK, application),
+        None => not_found("application not found"),
+    }
+}
+
+#[handler]
+fn delete_application(Data(state): Data<&SharedState>, Path(app_id): Path<String>) -> Response {
+    let mut state = state.lock().unwrap();
+
+    if state.applications.remove(&app_id).is_none() {
+        return not_found("application not found");
+    }
+
+    state.endpoints.remove(&app_id);
+    state.messages.remove(&app_id);
+
+    Response::builder().status(StatusCode::NO_CONTENT).finish()
+}
+
+#[handler]
+fn create_endpoint(
+    Data(state): Data<&SharedState>,
+    Path(app_id): Path<String>,
+    Json(body): Json<Value>,
+) -> Response {
+    let mut state = state.lock().unwrap();
+
+    if !state.applications.contains_key(&app_id) {
+        return not_found("application not found");
+    }
+
+    let endpoint = json!({
+        "id": new_id("ep"),
+        "url": body["url"],
+        "description": body["description"],
+        "version": body["version"],
+        "disabled": body["disabled"],
+        "rateLimit": body["rateLimit"],
+        "uid": body["uid"],
+        "channels": body["channels"],
+        "filterTypes": body["filterTypes"],
+        "metadata": {},
+        "createdAt": now(),
+        "updatedAt": now(),
+    });
+
+    let secret = body["secret"]
+        .as_str()
+        .map_or_else(|| new_id("whsec"), ToString::to_string);
+    let id = endpoint["id"].as_str().unwrap_or_default().to_string();
+
+    state.secrets.insert(id, secret);
+    state
+        .endpoints
+        .entry(app_id)
+        .or_default()
+        .push(endpoint.clone());
+
+    ok(StatusCode::CREATED, &endpoint)
+}
+
+#[handler]
+fn list_endpoints(Data(state): Data<&SharedState>, Path(app_id): Path<String>) -> Response {
+    let state = state.lock().unwrap();
+
+    if !state.applications.contains_key(&app_id) {
+        return not_found("application not found");
+    }
+
+    ok(
+        StatusCode::OK,
+        &list(state.endpoints.get(&app_id).cloned().unwrap_or_default()),
+    )
+}
+
+#[handler]
+fn get_endpoint(
+    Data(state): Data<&SharedState>,
+    Path((app_id, endpoint_id)): Path<(String, String)>,
+) -> Response {
+    let state = state.lock().unwrap();
+
+    match find_endpoint(&state, &app_id, &endpoint_id) {
+        Some(endpoint) => ok(StatusCode::OK, endpoint),
+        None => not_found("endpoint not found"),
+    }
+}
+
+#[handler]
+fn update_endpoint(
+    Data(state): Data<&SharedState>,
+    Path((app_id, endpoint_id)): Path<(String, String)>,
+    Json(body): Json<Value>,
+) -> Response {
+    let mut state = state.lock().unwrap();
+
+    if state.failing_endpoints.contains(&endpoint_id) {
+        return Response::builder()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .finish();
+    }
+
+    let Some(endpoint) = state
+        .endpoints
+        .get_mut(&app_id)
+        .and_then(|endpoints| endpoints.iter_mut().find(|e| e["id"] == endpoint_id.as_str()))
+    else {
+        return not_found("endpoint not found");
+    };
+
+    for field in [
+        "url",
+        "description",
+        "version",
+        "disabled",
+        "rateLimit",
+        "uid",
+        "channels",
+        "filterTypes",
+    ] {
+        endpoint[field] = body[field].clone();
+    }
+
+    endpoint["updatedAt"] = now().into();
+
+    ok(StatusCode::OK, endpoint)
+}
+
+#[handler]
+fn delete_endpoint(
+    Data(state): Data<&SharedState>,
+    Path((app_id, endpoint_id)): Path<(String, String)>,
+) -> Response {
+    let mut state = state.lock().unwrap();
+
+    let Some(endpoints) = state.endpoints.get_mut(&app_id) else {
+        return not_found("application not found");
+    };
+
+    endpoints.retain(|endpoint| endpoint["id"] != endpoint_id.as_str());
+    state.secrets.remove(&endpoint_id);
+
+    Response::builder().status(StatusCode::NO_CONTENT).finish()
+}
+
+#[handler]
+fn get_endpoint_secret(
+    Data(state): Data<&SharedState>,
+    Path((app_id, endpoint_id)): Path<(String, String)>,
+) -> Response {
+    let state = state.lock().unwrap();
+
+    if find_endpoint(&state, &app_id, &endpoint_id).is_none() {
+        return not_found("endpoint not found");
+    }
+
+    ok(StatusCode::OK, &json!({ "key": state.secrets[&endpoint_id] }))
+}
+
+#[handler]
+fn rotate_endpoint_secret(
+    Data(state): Data<&SharedState>,
+    Path((app_id, endpoint_id)): Path<(String, String)>,
+    Json(body): Json<Value>,
+) -> Response {
+    let mut state = state.lock().unwrap();
+
+    if find_endpoint(&state, &app_id, &endpoint_id).is_none() {
+        return not_found("endpoint not found");
+    }
+
+    let secret = body["key"]
+        .as_str()
+        .map_or_else(|| new_id("whsec"), ToString::to_string);
+
+    state.secrets.insert(endpoint_id, secret);
+
+    Response::builder().status(StatusCode::NO_CONTENT).finish()
+}
+
+#[handler]
+fn recover_endpoint(
+    Data(state): Data<&SharedState>,
+    Path((app_id, endpoint_id)): Path<(String, String)>,
+    Json(body): Json<Value>,
+) -> Response {
+    let mut state = state.lock().unwrap();
+
+    if find_endpoint(&state, &app_id, &endpoint_id).is_none() {
+        return not_found("endpoint not found");
+    }
+
+    state
+        .recoveries
+        .entry(app_id)
+        .or_default()
+        .push(json!({ "endpointId": endpoint_id, "since": body["since"] }));
+
+    ok(
+        StatusCode::ACCEPTED,
+        &json!({ "id": new_id("qtask"), "status": "finished", "task": "endpoint.recover" }),
+    )
+}
+
+#[handler]
+fn create_event_type(Data(state): Data<&SharedState>, Json(body): Json<Value>) -> Response {
+    let mut state = state.lock().unwrap();
+
+    if state
+        .event_types
+        .iter()
+        .any(|event_type| event_type["name"] == body["name"])
+    {
+        return error(StatusCode::CONFLICT, "conflict", "event type already exists");
+    }
+
+    let event_type = json!({
+        "name": body["name"],
+        "description": body["description"],
+        "archived": body["archived"],
+        "schemas": body["schemas"],
+        "createdAt": now(),
+        "updatedAt": now(),
+    });
+
+    state.event_types.push(event_type.clone());
+
+    ok(StatusCode::CREATED, &event_type)
+}
+
+#[handler]
+fn update_event_type(
+    Data(state): Data<&SharedState>,
+    Path(event_type_name): Path<String>,
+    Json(body): Json<Value>,
+) -> Response {
+    let mut state = state.lock().unwrap();
+
+    let Some(event_type) = state
+        .event_types
+        .iter_mut()
+        .find(|event_type| event_type["name"] == event_type_name.as_str())
+    else {
+        return not_found("event type not found");
+    };
+
+    for field in ["description", "archived", "schemas"] {
+        event_type[field] = body[field].clone();
+    }
+
+    event_type["updatedAt"] = now().into();
+
+    ok(StatusCode::OK, event_type)
+}
+
+#[handler]
+fn list_event_types(Data(state): Data<&SharedState>) -> Response {
+    let state = state.lock().unwrap();
+
+    ok(StatusCode::OK, &list(state.event_types.clone()))
+}
+
+#[handler]
+fn create_message(
+    Data(state): Data<&SharedState>,
+    Path(app_id): Path<String>,
+    headers: &HeaderMap,
+    Json(body): Json<Value>,
+) -> Response {
+    let mut state = state.lock().unwrap();
+
+    if !state.applications.contains_key(&app_id) {
+        return not_found("application not found");
+    }
+
+    let idempotency_key = headers
+        .get("idempotency-key")
+        .and_then(|value| value.to_str().ok())
+        .map(|key| format!("{app_id}:{key}"));
+
+    if let Some(message) = idempotency_key
+        .as_ref()
+        .and_then(|key| state.idempotency_keys.get(key))
+    {
+        return ok(StatusCode::ACCEPTED, message);
+    }
+
+    let message = json!({
+        "id": new_id("msg"),
+        "eventType": body["eventType"],
+        "eventId": body["eventId"],
+        "channels": body["channels"],
+        "payload": body["payload"],
+        "timestamp": now(),
+    });
+
+    if let Some(key) = idempotency_key {
+        state.idempotency_keys.insert(key, message.clone());
+    }
+
+    state
+        .messages
+        .entry(app_id)
+        .or_default()
+        .push(message.clone());
+
+    ok(StatusCode::ACCEPTED, &message)
+}
+
+#[handler]
+fn resend_message(
+    Data(state): Data<&SharedState>,
+    Path((app_id, message_id, endpoint_id)): Path<(String, String, String)>,
+) -> Response {
+    let mut state = state.lock().unwrap();
+
+    let message = state.messages.get(&app_id).and_then(|messages| {
+        messages
+            .iter()
+            .find(|message| message["id"] == message_id.as_str())
+    });
+
+    let (Some(message), Some(endpoint)) = (message, find_endpoint(&state, &app_id, &endpoint_id))
+    else {
+        return not_found("message or endpoint not found");
+    };
+
+    let attempt = json!({
+        "id": new_id("atmpt"),
+        "msgId": message_id,
+        "msg": message,
+        "endpointId": endpoint_id,
+        "url": endpoint["url"],
+        "status": 0,
+        "responseStatusCode": 200,
+        "response": "{}",
+        "triggerType": 1,
+        "timestamp": now(),
+    });
+
+    state.attempts.entry(app_id).or_default().push(attempt);
+
+    Response::builder().status(StatusCode::ACCEPTED).finish()
+}
+
+#[handler]
+fn list_endpoint_attempts(
+    Data(state): Data<&SharedState>,
+    Path((app_id, endpoint_id)): Path<(String, String)>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Response {
+    let state = state.lock().unwrap();
+
+    ok(
+        StatusCode::OK,
+        &attempts_page(&state, &app_id, "endpointId", &endpoint_id, &params),
+    )
+}
+
+#[handler]
+fn list_message_attempts(
+    Data(state): Data<&SharedState>,
+    Path((app_id, message_id)): Path<(String, String)>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Response {
+    let state = state.lock().unwrap();
+
+    ok(
+        StatusCode::OK,
+        &attempts_page(&state, &app_id, "msgId", &message_id, &params),
+    )
+}
+
+/// A page of the attempts of an application whose `field` is `value`, most recent first, following the `limit`
+/// and `iterator` query parameters.
+fn attempts_page(
+    state: &State,
+    app_id: &str,
+    field: &str,
+    value: &str,
+    params: &HashMap<String, String>,
+) -> Value {
+    let limit = params
+        .get("limit")
+        .and_then(|limit| limit.parse().ok())
+        .unwrap_or(50);
+
+    let mut attempts: Vec<Value> = state
+        .attempts
+        .get(app_id)
+        .into_iter()
+        .flatten()
+        .rev()
+        .filter(|attempt| attempt[field] == value)
+        .cloned()
+        .collect();
+
+    if let Some(iterator) = params.get("iterator") {
+        let start = attempts
+            .iter()
+            .position(|attempt| attempt["id"] == iterator.as_str())
+            .map_or(attempts.len(), |position| position + 1);
+
+        attempts.drain(..start);
+    }
+
+    let data: Vec<Value> = attempts.iter().take(limit).cloned().collect();
+
+    json!({
+        "data": data,
+        "done": attempts.len() <= limit,
+        "iterator": data.last().map(|attempt| attempt["id"].clone()),
+        "prevIterator": null,
+    })
+}
+
+fn find_endpoint<'a>(state: &'a State, app_id: &str, endpoint_id: &str) -> Option<&'a Value> {
+    state
+        .endpoints
+        .get(app_id)?
+        .iter()
+        .find(|endpoint| endpoint["id"] == endpoint_id)
+}
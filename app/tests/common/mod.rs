@@ -0,0 +1,460 @@
+//! A harness running the service against a [`FakeSvix`] server and a throwaway Postgres database.
+//! Every [`TestApp`] creates its own database on the server at `DATABASE_URL`, so tests can run in parallel,
+//! and drops it once the test is done.
+
+// each test binary uses a different part of the harness
+#![allow(dead_code)]
+
+pub mod fake_svix;
+
+use std::sync::{Arc, Mutex};
+
+use holaplex_hub_webhooks::{
+    build_schema,
+    db::{Connection, DbArgs},
+    dead_letters::{DeadLetter, DeadLetterKey, DeadLetterPublisher},
+    delivery::{native::signature, Delivery, SvixDelivery},
+    entities::{organization_applications, organization_members},
+    events,
+    handlers::{graphql_handler, svix_operational},
+    operational::OperationalWebhooks,
+    outbox::{self, OutboxArgs},
+    proto::{self, customer_events, organization_events, WebhookEventKey, WebhookEvents},
+    svix_client,
+    validation::PayloadValidation,
+    AppState, Services, Source, WebhookEventsProducer,
+};
+use hub_core::{
+    anyhow::{Error, Result},
+    chrono::{DateTime, Utc},
+    tokio,
+    uuid::Uuid,
+};
+use migration::{Migrator, MigratorTrait};
+use poem::{
+    async_trait, http::StatusCode, middleware::AddData, post, test::TestClient, EndpointExt, Route,
+};
+use sea_orm::{prelude::*, Database, DbBackend, Set, Statement};
+use serde_json::{json, Value};
+use svix::api::{Svix, SvixOptions};
+
+use self::fake_svix::FakeSvix;
+
+/// The time of the Kafka messages of the events processed by [`TestApp::process`].
+pub const EVENT_TIMESTAMP: &str = "2023-05-09T10:00:00Z";
+
+/// Records the webhook events published by the mutations instead of sending them to Kafka.
+#[derive(Default)]
+pub struct RecordingProducer {
+    events: Mutex<Vec<(WebhookEvents, WebhookEventKey)>>,
+}
+
+impl RecordingProducer {
+    /// The events published so far, in order.
+    #[must_use]
+    pub fn events(&self) -> Vec<(WebhookEvents, WebhookEventKey)> {
+        self.events.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl WebhookEventsProducer for RecordingProducer {
+    async fn send(&self, event: &WebhookEvents, key: &WebhookEventKey) -> Result<()> {
+        self.events
+            .lock()
+            .unwrap()
+            .push((event.clone(), key.clone()));
+
+        Ok(())
+    }
+}
+
+/// Records the dead letters published by the pipeline instead of sending them to Kafka.
+#[derive(Default)]
+pub struct RecordingDeadLetterQueue {
+    dead_letters: Mutex<Vec<(DeadLetter, DeadLetterKey)>>,
+}
+
+impl RecordingDeadLetterQueue {
+    /// The dead letters published so far, in order.
+    #[must_use]
+    pub fn dead_letters(&self) -> Vec<(DeadLetter, DeadLetterKey)> {
+        self.dead_letters.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl DeadLetterPublisher for RecordingDeadLetterQueue {
+    async fn send(&self, dead_letter: &DeadLetter, key: &DeadLetterKey) -> Result<()> {
+        self.dead_letters
+            .lock()
+            .unwrap()
+            .push((dead_letter.clone(), key.clone()));
+
+        Ok(())
+    }
+}
+
+/// The service wired to a fake Svix server and its own database, with the outbox worker running.
+pub struct TestApp {
+    pub svix: FakeSvix,
+    pub db: Connection,
+    pub delivery: Delivery,
+    pub producer: Arc<RecordingProducer>,
+    /// The secret the Svix operational webhooks are signed with.
+    pub operational_secret: String,
+    client: TestClient<Route>,
+    // dropped last, once nothing else uses the database
+    database: TestDatabase,
+}
+
+impl TestApp {
+    /// Starts a fake Svix server, creates and migrates a new database and registers the event types.
+    ///
+    /// # Panics
+    /// Panics if `DATABASE_URL` is not set or the database cannot be set up.
+    pub async fn start() -> Self {
+        let svix = FakeSvix::start().await;
+        let (db, database) = create_database().await;
+
+        let delivery: Delivery = Arc::new(SvixDelivery::new(Svix::new(
+            "testsk_fake".to_string(),
+            Some(SvixOptions {
+                debug: false,
+                server_url: Some(svix.url()),
+            }),
+        )));
+
+        svix_client::create_event_types(delivery.as_ref())
+            .await
+            .expect("failed to register the event types");
+
+        tokio::spawn(outbox::run(db.clone(), delivery.clone(), OutboxArgs {
+            outbox_poll_interval: 1,
+            outbox_batch_size: 100,
+            outbox_retry_base_delay: 1,
+            outbox_retry_max_delay: 1,
+            outbox_max_attempts: 3,
+            outbox_lease: 60,
+            outbox_idempotency_retention: 1,
+        }));
+
+        let producer = Arc::new(RecordingProducer::default());
+
+        let state = AppState::new(
+            build_schema(),
+            db.clone(),
+            delivery.clone(),
+            producer.clone(),
+        );
+
+        let operational_secret = signature::generate_secret();
+        let operational =
+            OperationalWebhooks::new(db.clone(), producer.clone(), operational_secret.clone());
+
+        let client = TestClient::new(
+            Route::new()
+                .at("/graphql", post(graphql_handler).with(AddData::new(state)))
+                .at(
+                    "/svix/operational",
+                    post(svix_operational).with(AddData::new(operational)),
+                ),
+        );
+
+        Self {
+            svix,
+            db,
+            delivery,
+            producer,
+            operational_secret,
+            client,
+            database,
+        }
+    }
+
+    /// Runs a GraphQL request as `user_id`, returning the whole response body.
+    pub async fn graphql(&self, user_id: Uuid, query: &str, variables: Value) -> Value {
+        let response = self
+            .client
+            .post("/graphql")
+            .header("X-USER-ID", user_id.to_string())
+            .body_json(&json!({ "query": query, "variables": variables }))
+            .send()
+            .await;
+
+        response.assert_status_is_ok();
+
+        response
+            .0
+            .into_body()
+            .into_json()
+            .await
+            .expect("graphql response is not json")
+    }
+
+    /// Posts a Svix operational webhook signed with `secret`, returning the response status.
+    pub async fn svix_operational(&self, body: &Value, secret: &str) -> StatusCode {
+        let message_id = format!("msg_{}", Uuid::new_v4().simple());
+        let timestamp = Utc::now().timestamp();
+        let body = body.to_string();
+
+        let signature = signature::sign(secret, &message_id, timestamp, &body)
+            .expect("failed to sign the operational webhook");
+
+        self.client
+            .post("/svix/operational")
+            .header("svix-id", message_id)
+            .header("svix-timestamp", timestamp.to_string())
+            .header("svix-signature", signature)
+            .body(body)
+            .send()
+            .await
+            .0
+            .status()
+    }
+
+    /// Processes a consumed event the way the pipeline workers do, as if it was the first message of
+    /// partition 0 of its topic, produced at [`EVENT_TIMESTAMP`].
+    ///
+    /// # Panics
+    /// Panics if the event fails to be processed.
+    pub async fn process(&self, msg: Services) {
+        self.process_at(msg, 0).await;
+    }
+
+    /// Processes an event like [`TestApp::process`], as if it was read at `offset` of partition 0.
+    ///
+    /// # Panics
+    /// Panics if the event fails to be processed.
+    pub async fn process_at(&self, msg: Services, offset: i64) {
+        self.try_process_at(msg, offset)
+            .await
+            .expect("failed to process event");
+    }
+
+    /// Processes an event like [`TestApp::process`], returning the error instead of panicking.
+    pub async fn try_process(&self, msg: Services) -> Result<()> {
+        self.try_process_at(msg, 0).await
+    }
+
+    async fn try_process_at(&self, msg: Services, offset: i64) -> Result<()> {
+        let source = Source {
+            topic: msg.encode().0.to_string(),
+            partition: 0,
+            offset,
+            timestamp: DateTime::parse_from_rfc3339(EVENT_TIMESTAMP)
+                .unwrap()
+                .with_timezone(&Utc),
+        };
+
+        events::process(
+            msg,
+            &source,
+            self.db.clone(),
+            self.delivery.clone(),
+            self.producer.clone(),
+            PayloadValidation::Strict,
+        )
+        .await
+    }
+
+    /// Creates an organization owned by `user_id` along with its Svix application, returning its ID.
+    pub async fn create_organization(&self, user_id: Uuid) -> Uuid {
+        let organization_id = Uuid::new_v4();
+
+        let key = proto::OrganizationEventKey {
+            id: organization_id.to_string(),
+            user_id: user_id.to_string(),
+        };
+
+        let event = proto::OrganizationEvents {
+            event: Some(organization_events::Event::OrganizationCreated(
+                proto::Organization {
+                    id: organization_id.to_string(),
+                    name: "Test Organization".to_string(),
+                    ..Default::default()
+                },
+            )),
+        };
+
+        self.process(Services::Organizations(key, event)).await;
+
+        organization_id
+    }
+
+    /// Adds `user_id` to an organization without creating its Svix application, like organizations
+    /// created before the consumer existed.
+    pub async fn add_member(&self, organization_id: Uuid, user_id: Uuid) {
+        let member = organization_members::ActiveModel {
+            organization_id: Set(organization_id),
+            user_id: Set(user_id),
+            deactivated_at: Set(None),
+            ..Default::default()
+        };
+
+        member
+            .insert(self.db.get())
+            .await
+            .expect("failed to add organization member");
+    }
+
+    /// The ID of the Svix application of an organization.
+    ///
+    /// # Panics
+    /// Panics if the organization has no application.
+    pub async fn svix_app_id(&self, organization_id: Uuid) -> String {
+        organization_applications::Entity::find()
+            .filter(organization_applications::Column::OrganizationId.eq(organization_id))
+            .one(self.db.get())
+            .await
+            .expect("failed to query organization applications")
+            .expect("organization has no application")
+            .svix_app_id
+    }
+}
+
+/// Creates a webhook through the GraphQL API, returning the webhook's ID and endpoint ID and the secret.
+pub const CREATE_WEBHOOK: &str = r#"
+    mutation CreateWebhook($input: CreateWebhookInput!) {
+        createWebhook(input: $input) {
+            webhook { id endpointId }
+            secret
+        }
+    }
+"#;
+
+/// Edits a webhook through the GraphQL API.
+pub const EDIT_WEBHOOK: &str = r#"
+    mutation EditWebhook($input: EditWebhookInput!) {
+        editWebhook(input: $input) {
+            webhook { id }
+        }
+    }
+"#;
+
+/// Creates a webhook on `project_id` as `user_id` for the given event types, returning the whole response body.
+pub async fn create_webhook(
+    app: &TestApp,
+    user_id: Uuid,
+    organization_id: Uuid,
+    project_id: Uuid,
+    filter_types: &[&str],
+) -> Value {
+    app.graphql(
+        user_id,
+        CREATE_WEBHOOK,
+        json!({
+            "input": {
+                "url": "https://example.com/webhook",
+                "organization": organization_id,
+                "description": "Test webhook",
+                "projects": [project_id],
+                "filterTypes": filter_types,
+            }
+        }),
+    )
+    .await
+}
+
+/// A `customer.created` event of a new customer of `project_id`, returning the customer's ID along with it.
+#[must_use]
+pub fn customer_created(project_id: Uuid) -> (Uuid, Services) {
+    let customer_id = Uuid::new_v4();
+
+    let key = proto::CustomerEventKey {
+        id: customer_id.to_string(),
+        ..Default::default()
+    };
+
+    let event = proto::CustomerEvents {
+        event: Some(customer_events::Event::Created(proto::Customer {
+            project_id: project_id.to_string(),
+            ..Default::default()
+        })),
+    };
+
+    (customer_id, Services::Customers(key, event))
+}
+
+/// A database created for a single [`TestApp`], dropped along with it so test runs do not leave databases behind.
+struct TestDatabase {
+    database_url: String,
+    name: String,
+}
+
+impl Drop for TestDatabase {
+    fn drop(&mut self) {
+        let database_url = self.database_url.clone();
+        let name = self.name.clone();
+
+        // the runtime of the test may already be shutting down, so the database is dropped from a runtime of its own
+        let dropped = std::thread::spawn(move || -> Result<()> {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()?;
+
+            runtime.block_on(async {
+                let server = Database::connect(database_url.as_str()).await?;
+
+                server
+                    .execute(Statement::from_string(
+                        DbBackend::Postgres,
+                        format!("DROP DATABASE IF EXISTS {name} WITH (FORCE)"),
+                    ))
+                    .await?;
+
+                Ok::<_, Error>(())
+            })
+        })
+        .join();
+
+        if !matches!(dropped, Ok(Ok(()))) {
+            eprintln!("failed to drop the test database {}", self.name);
+        }
+    }
+}
+
+/// Creates a database named after a random ID on the server at `DATABASE_URL` and runs the migrations.
+async fn create_database() -> (Connection, TestDatabase) {
+    let database_url = std::env::var("DATABASE_URL")
+        .expect("DATABASE_URL must be set to run the integration tests");
+
+    let (server_url, _) = database_url
+        .rsplit_once('/')
+        .expect("DATABASE_URL has no database name");
+
+    let name = format!("hub_webhooks_test_{}", Uuid::new_v4().simple());
+
+    let server = Database::connect(database_url.as_str())
+        .await
+        .expect("failed to connect to the database server");
+
+    server
+        .execute(Statement::from_string(
+            DbBackend::Postgres,
+            format!("CREATE DATABASE {name}"),
+        ))
+        .await
+        .expect("failed to create the test database");
+
+    let database = TestDatabase {
+        database_url: database_url.clone(),
+        name: name.clone(),
+    };
+
+    let db = Connection::new(DbArgs {
+        max_connections: 10,
+        connection_timeout: 10,
+        acquire_timeout: 10,
+        idle_timeout: 10,
+        database_url: format!("{server_url}/{name}"),
+    })
+    .await
+    .expect("failed to connect to the test database");
+
+    Migrator::up(db.get(), None)
+        .await
+        .expect("failed to run the migrations");
+
+    (db, database)
+}
@@ -0,0 +1,197 @@
+mod common;
+
+use std::sync::Arc;
+
+use holaplex_hub_webhooks::{
+    dead_letters::{self, DeadLetterQueue},
+    entities,
+    proto::{self, nft_events},
+    validation::PayloadValidation,
+    EventsProducer, Services, Source,
+};
+use hub_core::{
+    anyhow::anyhow,
+    chrono::{DateTime, Utc},
+    uuid::Uuid,
+};
+use sea_orm::prelude::*;
+
+use self::common::{create_webhook, customer_created, RecordingDeadLetterQueue, TestApp};
+
+#[tokio::test]
+async fn failed_events_are_published_to_the_dead_letter_topic() {
+    let app = TestApp::start().await;
+    let queue = Arc::new(RecordingDeadLetterQueue::default());
+    let dead_letter_queue: DeadLetterQueue = queue.clone();
+
+    let key = proto::NftEventKey {
+        id: Uuid::new_v4().to_string(),
+        project_id: Uuid::new_v4().to_string(),
+        ..Default::default()
+    };
+
+    let event = proto::NftEvents {
+        event: Some(nft_events::Event::DropMinted(proto::MintCreation {
+            drop_id: Uuid::new_v4().to_string(),
+            status: proto::CreationStatus::Created.into(),
+        })),
+    };
+
+    let msg = Services::Nfts(key, event);
+    let source = Source {
+        topic: "hub-nfts".to_string(),
+        partition: 2,
+        offset: 42,
+        timestamp: DateTime::parse_from_rfc3339("2023-05-09T10:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc),
+    };
+
+    let id = dead_letters::store(
+        &app.db,
+        &dead_letter_queue,
+        &msg,
+        &source,
+        &anyhow!("svix unavailable"),
+        4,
+    )
+    .await
+    .unwrap();
+
+    let published = queue.dead_letters();
+    assert_eq!(published.len(), 1);
+
+    let (dead_letter, key) = &published[0];
+    assert_eq!(key.id, id.to_string());
+    assert_eq!(dead_letter.id, id.to_string());
+    assert_eq!(dead_letter.topic, "hub-nfts");
+    assert_eq!(dead_letter.partition, 2);
+    assert_eq!(dead_letter.offset, 42);
+    assert_eq!(dead_letter.attempts, 4);
+    assert_eq!(dead_letter.timestamp, "2023-05-09T10:00:00+00:00");
+    assert!(dead_letter.error.contains("svix unavailable"));
+    assert_eq!(
+        (dead_letter.key.clone(), dead_letter.payload.clone()),
+        (msg.encode().1, msg.encode().2)
+    );
+
+    let producer: EventsProducer = app.producer.clone();
+    let redriven = dead_letters::redrive(
+        &app.db,
+        &app.delivery,
+        &producer,
+        PayloadValidation::Strict,
+        &[id],
+    )
+    .await
+    .unwrap();
+    assert_eq!(redriven, 1);
+}
+
+#[tokio::test]
+async fn a_message_read_again_keeps_its_dead_letter() {
+    let app = TestApp::start().await;
+    let queue = Arc::new(RecordingDeadLetterQueue::default());
+    let dead_letter_queue: DeadLetterQueue = queue.clone();
+
+    let (_, msg) = customer_created(Uuid::new_v4());
+    let source = Source {
+        topic: "hub-customers".to_string(),
+        partition: 0,
+        offset: 7,
+        timestamp: Utc::now(),
+    };
+
+    let mut ids = Vec::new();
+
+    for _ in 0..2 {
+        let id = dead_letters::store(
+            &app.db,
+            &dead_letter_queue,
+            &msg,
+            &source,
+            &anyhow!("svix unavailable"),
+            1,
+        )
+        .await
+        .unwrap();
+
+        ids.push(id);
+    }
+
+    assert_eq!(ids[0], ids[1]);
+
+    let stored = entities::dead_letters::Entity::find()
+        .count(app.db.get())
+        .await
+        .unwrap();
+    assert_eq!(stored, 1);
+    assert_eq!(queue.dead_letters().len(), 2);
+}
+
+#[tokio::test]
+async fn dead_letters_are_redriven_in_order() {
+    let app = TestApp::start().await;
+    let user_id = Uuid::new_v4();
+    let project_id = Uuid::new_v4();
+
+    let organization_id = app.create_organization(user_id).await;
+    let svix_app_id = app.svix_app_id(organization_id).await;
+
+    create_webhook(&app, user_id, organization_id, project_id, &[
+        "CUSTOMER_CREATED",
+    ])
+    .await;
+
+    let dead_letter_queue: DeadLetterQueue = Arc::new(RecordingDeadLetterQueue::default());
+    let mut customer_ids = Vec::new();
+
+    for offset in 0..5 {
+        let (customer_id, msg) = customer_created(project_id);
+        let source = Source {
+            topic: "hub-customers".to_string(),
+            partition: 0,
+            offset,
+            timestamp: Utc::now(),
+        };
+
+        dead_letters::store(
+            &app.db,
+            &dead_letter_queue,
+            &msg,
+            &source,
+            &anyhow!("svix unavailable"),
+            1,
+        )
+        .await
+        .unwrap();
+
+        customer_ids.push(customer_id.to_string());
+    }
+
+    let producer: EventsProducer = app.producer.clone();
+    let redriven = dead_letters::redrive(
+        &app.db,
+        &app.delivery,
+        &producer,
+        PayloadValidation::Strict,
+        &[],
+    )
+    .await
+    .unwrap();
+    assert_eq!(redriven, 5);
+
+    let received: Vec<String> = app
+        .svix
+        .wait_for_messages(&svix_app_id, 5)
+        .await
+        .iter()
+        .map(|message| {
+            message["payload"]["payload"]["customer_id"]
+                .as_str()
+                .unwrap()
+                .to_string()
+        })
+        .collect();
+    assert_eq!(received, customer_ids);
+}
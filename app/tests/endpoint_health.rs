@@ -0,0 +1,91 @@
+mod common;
+
+use holaplex_hub_webhooks::proto::webhook_events::Event;
+use hub_core::uuid::Uuid;
+use poem::http::StatusCode;
+use serde_json::json;
+
+use self::common::{create_webhook, TestApp};
+
+const WEBHOOK_HEALTH: &str = r#"
+    query WebhookHealth($id: UUID!) {
+        _entities(representations: [{ __typename: "Webhook", id: $id }]) {
+            ... on Webhook { health disabled }
+        }
+    }
+"#;
+
+#[tokio::test]
+async fn operational_webhooks_track_endpoint_health() {
+    let app = TestApp::start().await;
+    let user_id = Uuid::new_v4();
+    let project_id = Uuid::new_v4();
+
+    let organization_id = app.create_organization(user_id).await;
+
+    let response = create_webhook(&app, user_id, organization_id, project_id, &[
+        "CUSTOMER_CREATED",
+    ])
+    .await;
+    let webhook = &response["data"]["createWebhook"]["webhook"];
+    let variables = json!({ "id": webhook["id"] });
+
+    let exhausted = json!({
+        "type": "message.attempt.exhausted",
+        "data": {
+            "appId": "app_1",
+            "endpointId": webhook["endpointId"],
+            "msgId": "msg_1",
+            "lastAttempt": { "id": "atmpt_1", "responseStatusCode": 500 },
+        },
+    });
+
+    let status = app
+        .svix_operational(&exhausted, "whsec_d3Jvbmcgc2VjcmV0")
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let status = app
+        .svix_operational(&exhausted, &app.operational_secret)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let response = app
+        .graphql(user_id, WEBHOOK_HEALTH, variables.clone())
+        .await;
+    assert_eq!(
+        response["data"]["_entities"][0],
+        json!({ "health": "FAILING", "disabled": false })
+    );
+
+    let disabled = json!({
+        "type": "endpoint.disabled",
+        "data": { "appId": "app_1", "endpointId": webhook["endpointId"] },
+    });
+
+    // a redelivered notification is not published twice
+    for _ in 0..2 {
+        let status = app
+            .svix_operational(&disabled, &app.operational_secret)
+            .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    let response = app.graphql(user_id, WEBHOOK_HEALTH, variables).await;
+    assert_eq!(
+        response["data"]["_entities"][0],
+        json!({ "health": "DISABLED", "disabled": true })
+    );
+
+    let events = app.producer.events();
+    assert_eq!(events.len(), 3);
+
+    let Some(Event::EndpointFailing(failure)) = &events[1].0.event else {
+        panic!("expected a failure, got {:?}", events[1].0);
+    };
+    assert_eq!(failure.message_id, "msg_1");
+    assert_eq!(failure.response_status_code, 500);
+
+    assert!(matches!(events[2].0.event, Some(Event::Disabled(_))));
+    assert!(events[2].1.user_id.is_empty());
+}
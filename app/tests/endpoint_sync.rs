@@ -0,0 +1,70 @@
+mod common;
+
+use holaplex_hub_webhooks::{delivery::EndpointUpdate, endpoint_cache};
+use hub_core::uuid::Uuid;
+use serde_json::json;
+
+use self::common::{create_webhook, TestApp};
+
+const WEBHOOK: &str = r#"
+    query Webhook($id: UUID!) {
+        _entities(representations: [{ __typename: "Webhook", id: $id }]) {
+            ... on Webhook { url description disabled events }
+        }
+    }
+"#;
+
+#[tokio::test]
+async fn endpoint_changes_are_synced_from_svix() {
+    let app = TestApp::start().await;
+    let user_id = Uuid::new_v4();
+    let project_id = Uuid::new_v4();
+
+    let organization_id = app.create_organization(user_id).await;
+    let svix_app_id = app.svix_app_id(organization_id).await;
+
+    let response = create_webhook(&app, user_id, organization_id, project_id, &[
+        "CUSTOMER_CREATED",
+    ])
+    .await;
+    let webhook = &response["data"]["createWebhook"]["webhook"];
+    let endpoint_id = webhook["endpointId"].as_str().unwrap().to_string();
+
+    app.delivery
+        .update_endpoint(svix_app_id, endpoint_id, EndpointUpdate {
+            channels: Some(vec![organization_id.to_string(), project_id.to_string()]),
+            filter_types: Some(vec!["drop.minted".to_string()]),
+            version: 2,
+            description: Some("Changed in Svix".to_string()),
+            disabled: Some(true),
+            rate_limit: None,
+            url: "https://example.com/changed".to_string(),
+            uid: None,
+        })
+        .await
+        .unwrap();
+
+    let variables = json!({ "id": webhook["id"] });
+
+    let before = app.graphql(user_id, WEBHOOK, variables.clone()).await;
+    assert_eq!(
+        before["data"]["_entities"][0]["url"],
+        "https://example.com/webhook"
+    );
+
+    let synced = endpoint_cache::sync(&app.db, &app.delivery, 10)
+        .await
+        .unwrap();
+    assert_eq!(synced, 1);
+
+    let after = app.graphql(user_id, WEBHOOK, variables).await;
+    assert_eq!(
+        after["data"]["_entities"][0],
+        json!({
+            "url": "https://example.com/changed",
+            "description": "Changed in Svix",
+            "disabled": true,
+            "events": ["DROP_MINTED"],
+        })
+    );
+}
@@ -0,0 +1,248 @@
+mod common;
+
+use holaplex_hub_webhooks::{
+    event_types::{ApiVersion, FilterType},
+    events, svix_client,
+    validation::{self, InvalidPayload, PayloadValidation},
+};
+use std::{env, fs, path::Path};
+
+use hub_core::uuid::Uuid;
+use serde_json::{json, Map, Value};
+
+use self::common::TestApp;
+
+#[tokio::test]
+async fn event_types_are_registered_from_the_registry() {
+    let app = TestApp::start().await;
+
+    let event_types = app.delivery.list_event_types().await.unwrap();
+    assert_eq!(event_types.len(), FilterType::ALL.len());
+
+    for filter_type in FilterType::ALL {
+        assert_eq!(filter_type.name().parse::<FilterType>(), Ok(*filter_type));
+
+        let event_type = event_types
+            .iter()
+            .find(|event_type| event_type.name == filter_type.name())
+            .unwrap_or_else(|| panic!("{} is not registered", filter_type.name()));
+
+        let schemas = serde_json::to_value(&event_type.schemas).unwrap();
+        assert_eq!(schemas.as_object().unwrap().len(), ApiVersion::ALL.len());
+
+        for api_version in ApiVersion::ALL {
+            let name = format!("{} v{}", filter_type.name(), api_version.number());
+            let body = events::example_payload(*filter_type, *api_version, Uuid::nil()).unwrap();
+            let example = &body["payload"];
+
+            let schema = &schemas[api_version.number().to_string()];
+            let envelope = schema["properties"].as_object().unwrap();
+
+            let mut registered: Vec<&String> = envelope.keys().collect();
+            let mut sent: Vec<&String> = body.as_object().unwrap().keys().collect();
+            registered.sort();
+            sent.sort();
+
+            assert_eq!(registered, sent, "{name}");
+            assert_eq!(body["api_version"], envelope["api_version"]["enum"][0]);
+            assert_eq!(body["source"], Value::Null, "{name}");
+
+            let payload_schema = &envelope["payload"];
+            let properties = payload_schema["properties"].as_object().unwrap();
+
+            let mut registered: Vec<&String> = properties.keys().collect();
+            let mut sent: Vec<&String> = example.as_object().unwrap().keys().collect();
+            registered.sort();
+            sent.sort();
+
+            assert_eq!(registered, sent, "{name}");
+            assert_eq!(
+                payload_schema["required"].as_array().unwrap().len(),
+                properties.len(),
+                "{name}"
+            );
+
+            for (field, schema) in properties {
+                assert_eq!(schema["type"], "string", "{name}.{field}");
+                assert!(schema["description"].is_string(), "{name}.{field}");
+                assert!(example[field].is_string(), "{name}.{field}");
+
+                if let Some(values) = schema["enum"].as_array() {
+                    assert!(values.contains(&example[field]), "{name}.{field}");
+                }
+            }
+        }
+    }
+
+    let drop_created = event_types
+        .iter()
+        .find(|event_type| event_type.name == FilterType::DropCreated.name())
+        .unwrap();
+    let schemas = serde_json::to_value(&drop_created.schemas).unwrap();
+
+    let creation_status = &schemas["1"]["properties"]["payload"]["properties"]["creation_status"];
+    let statuses = creation_status["enum"].as_array().unwrap();
+
+    assert!(statuses.contains(&json!("CREATED")));
+    assert_eq!(
+        schemas["2"]["properties"]["payload"]["properties"]["status"],
+        *creation_status
+    );
+}
+
+#[tokio::test]
+async fn registering_event_types_again_updates_them() {
+    let app = TestApp::start().await;
+
+    let mut stale = FilterType::DropCreated.event_type_in();
+    stale.description = "stale description".to_string();
+    stale.schemas = None;
+    app.delivery.register_event_type(stale).await.unwrap();
+
+    let event_types = app.delivery.list_event_types().await.unwrap();
+    let drop_created = event_types
+        .iter()
+        .find(|event_type| event_type.name == FilterType::DropCreated.name())
+        .unwrap();
+    assert_eq!(drop_created.description, "stale description");
+
+    svix_client::create_event_types(app.delivery.as_ref())
+        .await
+        .unwrap();
+
+    let event_types = app.delivery.list_event_types().await.unwrap();
+    assert_eq!(event_types.len(), FilterType::ALL.len());
+
+    let drop_created = event_types
+        .iter()
+        .find(|event_type| event_type.name == FilterType::DropCreated.name())
+        .unwrap();
+    let schemas = serde_json::to_value(&drop_created.schemas).unwrap();
+
+    let drop_created_type = FilterType::DropCreated;
+
+    assert_eq!(drop_created.description, drop_created_type.description());
+    for api_version in ApiVersion::ALL {
+        assert_eq!(
+            Some(&schemas[api_version.number().to_string()]),
+            drop_created_type.registered_schema(*api_version)
+        );
+    }
+}
+
+#[test]
+fn payloads_are_validated_against_their_schema() {
+    for filter_type in FilterType::ALL {
+        for api_version in ApiVersion::ALL {
+            let example = events::example_payload(*filter_type, *api_version, Uuid::nil()).unwrap();
+
+            let schema = filter_type.registered_schema(*api_version).unwrap();
+
+            assert_eq!(
+                validation::violations(schema, &example),
+                Vec::<String>::new(),
+                "{} v{}",
+                filter_type.name(),
+                api_version.number()
+            );
+        }
+    }
+
+    let mut invalid =
+        events::example_payload(FilterType::DropCreated, ApiVersion::V1, Uuid::nil()).unwrap();
+    invalid["source"] = json!({ "topic": "hub-nfts", "partition": 0, "offset": "0" });
+    invalid["payload"] = json!({
+        "project_id": Uuid::nil().to_string(),
+        "creation_status": "UNKNOWN",
+        "supply": 10,
+    });
+
+    let schema = FilterType::DropCreated
+        .registered_schema(ApiVersion::V1)
+        .unwrap();
+    let mut violations = validation::violations(schema, &invalid);
+    violations.sort();
+
+    assert_eq!(violations.len(), 4, "{violations:?}");
+    assert!(violations[0].starts_with("$.payload.creation_status is not one of"));
+    assert_eq!(violations[1], "$.payload.drop_id is missing");
+    assert_eq!(violations[2], "$.payload.supply is not declared");
+    assert_eq!(violations[3], "$.source.offset is not of type integer");
+
+    let mut wrong_source = invalid.clone();
+    wrong_source["source"] = "hub-nfts".into();
+    assert!(validation::violations(schema, &wrong_source)
+        .contains(&"$.source is not of type object or null".to_string()));
+
+    let err = PayloadValidation::Strict
+        .check(FilterType::DropCreated, ApiVersion::V1, &invalid)
+        .unwrap_err();
+    assert!(err.is::<InvalidPayload>());
+
+    PayloadValidation::Lenient
+        .check(FilterType::DropCreated, ApiVersion::V1, &invalid)
+        .unwrap();
+}
+
+/// Fails when a payload struct or upstream proto changes the schema of an event type, since receivers code against
+/// the published one. Review the change, then run the test with `UPDATE_SCHEMA_SNAPSHOT=1` to publish it.
+#[test]
+fn derived_schemas_match_the_published_snapshot() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("schemas/event_types.json");
+
+    let derived: Map<String, Value> = FilterType::ALL
+        .iter()
+        .map(|filter_type| {
+            let schemas: Map<String, Value> = ApiVersion::ALL
+                .iter()
+                .map(|api_version| {
+                    (
+                        api_version.number().to_string(),
+                        filter_type.schema(*api_version),
+                    )
+                })
+                .collect();
+
+            (filter_type.name().to_string(), schemas.into())
+        })
+        .collect();
+    let derived = Value::from(derived);
+
+    if env::var_os("UPDATE_SCHEMA_SNAPSHOT").is_some() {
+        let snapshot = serde_json::to_string_pretty(&derived).unwrap();
+        fs::write(&path, snapshot + "\n").unwrap();
+
+        return;
+    }
+
+    let snapshot: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+
+    for filter_type in FilterType::ALL {
+        for api_version in ApiVersion::ALL {
+            let name = format!("{} v{}", filter_type.name(), api_version.number());
+
+            assert_eq!(
+                filter_type.registered_schema(*api_version),
+                Some(&derived[filter_type.name()][api_version.number().to_string()]),
+                "the schema of {name} changed, review it and run with UPDATE_SCHEMA_SNAPSHOT=1"
+            );
+        }
+    }
+
+    assert_eq!(snapshot, derived, "the snapshot has unknown event types");
+}
+
+#[test]
+fn payloads_drifting_from_the_published_schema_are_rejected() {
+    let mut drifted =
+        events::example_payload(FilterType::MintTransfered, ApiVersion::V2, Uuid::nil()).unwrap();
+    // a field added upstream and passed through, which the published schema does not declare
+    drifted["payload"]["amount"] = 1.into();
+
+    let err = PayloadValidation::Strict
+        .check(FilterType::MintTransfered, ApiVersion::V2, &drifted)
+        .unwrap_err();
+    let err = err.downcast::<InvalidPayload>().unwrap();
+
+    assert_eq!(err.violations, ["$.payload.amount is not declared"]);
+}
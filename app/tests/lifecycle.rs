@@ -0,0 +1,166 @@
+mod common;
+
+use holaplex_hub_webhooks::{
+    entities::{organization_applications, webhook_projects, webhooks},
+    event_types::ApiVersion,
+    proto::{self, organization_events, webhook_events::Event},
+    Services,
+};
+use hub_core::uuid::Uuid;
+use sea_orm::prelude::*;
+use serde_json::Value;
+
+use self::common::{create_webhook, TestApp};
+
+fn project_deactivated(organization_id: Uuid, project_id: Uuid) -> Services {
+    let key = proto::OrganizationEventKey {
+        id: organization_id.to_string(),
+        user_id: Uuid::new_v4().to_string(),
+    };
+
+    let event = proto::OrganizationEvents {
+        event: Some(organization_events::Event::ProjectDeactivated(
+            proto::Project {
+                id: project_id.to_string(),
+                organization_id: organization_id.to_string(),
+                ..Default::default()
+            },
+        )),
+    };
+
+    Services::Organizations(key, event)
+}
+
+fn organization_deactivated(organization_id: Uuid, user_id: Uuid) -> Services {
+    let key = proto::OrganizationEventKey {
+        id: organization_id.to_string(),
+        user_id: user_id.to_string(),
+    };
+
+    let event = proto::OrganizationEvents {
+        event: Some(organization_events::Event::OrganizationDeactivated(
+            proto::Organization {
+                id: organization_id.to_string(),
+                ..Default::default()
+            },
+        )),
+    };
+
+    Services::Organizations(key, event)
+}
+
+#[tokio::test]
+async fn project_deactivation_retries_endpoints_that_failed_to_update() {
+    let app = TestApp::start().await;
+    let user_id = Uuid::new_v4();
+    let project_id = Uuid::new_v4();
+
+    let organization_id = app.create_organization(user_id).await;
+    let svix_app_id = app.svix_app_id(organization_id).await;
+
+    let response = create_webhook(&app, user_id, organization_id, project_id, &[
+        "CUSTOMER_CREATED",
+    ])
+    .await;
+    let webhook = &response["data"]["createWebhook"]["webhook"];
+    let webhook_id: Uuid = webhook["id"].as_str().unwrap().parse().unwrap();
+    let endpoint_id = webhook["endpointId"].as_str().unwrap().to_string();
+
+    let project_channel = Value::from(ApiVersion::LATEST.channel(project_id));
+    let webhook_projects = || async {
+        webhook_projects::Entity::find()
+            .filter(webhook_projects::Column::WebhookId.eq(webhook_id))
+            .filter(webhook_projects::Column::ProjectId.eq(project_id))
+            .count(app.db.get())
+            .await
+            .unwrap()
+    };
+
+    app.svix.fail_endpoint(&endpoint_id);
+
+    let result = app
+        .try_process(project_deactivated(organization_id, project_id))
+        .await;
+    assert!(result.is_err());
+
+    // the webhook keeps its project until its endpoint is unsubscribed from it
+    assert_eq!(webhook_projects().await, 1);
+
+    let endpoints = app.svix.endpoints(&svix_app_id);
+    assert!(endpoints[0]["channels"]
+        .as_array()
+        .unwrap()
+        .contains(&project_channel));
+
+    app.svix.restore_endpoint(&endpoint_id);
+    app.process(project_deactivated(organization_id, project_id))
+        .await;
+
+    assert_eq!(webhook_projects().await, 0);
+
+    let endpoints = app.svix.endpoints(&svix_app_id);
+    assert!(!endpoints[0]["channels"]
+        .as_array()
+        .unwrap()
+        .contains(&project_channel));
+    assert_eq!(endpoints[0]["disabled"], true);
+}
+
+#[tokio::test]
+async fn organization_deactivation_publishes_webhook_deletions() {
+    let app = TestApp::start().await;
+    let user_id = Uuid::new_v4();
+    let deactivated_by = Uuid::new_v4();
+    let project_id = Uuid::new_v4();
+
+    let organization_id = app.create_organization(user_id).await;
+    let svix_app_id = app.svix_app_id(organization_id).await;
+
+    let mut webhook_ids = Vec::new();
+
+    for filter_type in ["CUSTOMER_CREATED", "DROP_MINTED"] {
+        let response =
+            create_webhook(&app, user_id, organization_id, project_id, &[filter_type]).await;
+
+        webhook_ids.push(
+            response["data"]["createWebhook"]["webhook"]["id"]
+                .as_str()
+                .unwrap()
+                .to_string(),
+        );
+    }
+
+    app.process(organization_deactivated(organization_id, deactivated_by))
+        .await;
+
+    let mut deleted: Vec<String> = app
+        .producer
+        .events()
+        .into_iter()
+        .filter(|(event, _)| matches!(event.event, Some(Event::Deleted(_))))
+        .map(|(_, key)| {
+            assert_eq!(key.user_id, deactivated_by.to_string());
+
+            key.id
+        })
+        .collect();
+    deleted.sort();
+    webhook_ids.sort();
+    assert_eq!(deleted, webhook_ids);
+
+    assert!(app.svix.endpoints(&svix_app_id).is_empty());
+
+    let applications = organization_applications::Entity::find()
+        .filter(organization_applications::Column::OrganizationId.eq(organization_id))
+        .count(app.db.get())
+        .await
+        .unwrap();
+    assert_eq!(applications, 0);
+
+    let webhooks = webhooks::Entity::find()
+        .filter(webhooks::Column::OrganizationId.eq(organization_id))
+        .count(app.db.get())
+        .await
+        .unwrap();
+    assert_eq!(webhooks, 0);
+}
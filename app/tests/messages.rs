@@ -0,0 +1,237 @@
+mod common;
+
+use holaplex_hub_webhooks::{delivery::MessageIn, proto::webhook_events::Event};
+use hub_core::uuid::Uuid;
+use serde_json::{json, Value};
+
+use self::common::{create_webhook, TestApp};
+
+const RESEND_WEBHOOK_MESSAGE: &str = r#"
+    mutation ResendWebhookMessage($input: ResendWebhookMessageInput!) {
+        resendWebhookMessage(input: $input) { webhook messageId }
+    }
+"#;
+
+const RECOVER_WEBHOOK: &str = r#"
+    mutation RecoverWebhook($input: RecoverWebhookInput!) {
+        recoverWebhook(input: $input) { webhook }
+    }
+"#;
+
+#[tokio::test]
+async fn resend_webhook_message() {
+    let app = TestApp::start().await;
+    let user_id = Uuid::new_v4();
+    let other_user_id = Uuid::new_v4();
+    let project_id = Uuid::new_v4();
+
+    let organization_id = app.create_organization(user_id).await;
+    let other_organization_id = app.create_organization(other_user_id).await;
+    let svix_app_id = app.svix_app_id(organization_id).await;
+
+    let response = create_webhook(&app, user_id, organization_id, project_id, &[
+        "CUSTOMER_CREATED",
+    ])
+    .await;
+    let webhook = &response["data"]["createWebhook"]["webhook"];
+
+    let message = app
+        .delivery
+        .create_message(
+            svix_app_id.clone(),
+            MessageIn {
+                channels: Some(vec![project_id.to_string()]),
+                event_id: None,
+                event_type: "customer.created".to_string(),
+                payload: json!({}),
+            },
+            None,
+        )
+        .await
+        .unwrap();
+
+    let resend = |user_id: Uuid, organization_id: Uuid| {
+        app.graphql(
+            user_id,
+            RESEND_WEBHOOK_MESSAGE,
+            json!({
+                "input": {
+                    "organization": organization_id,
+                    "webhook": webhook["id"],
+                    "messageId": message.id,
+                }
+            }),
+        )
+    };
+
+    // neither a non-member nor a member of another organization can resend to the webhook
+    let response = resend(other_user_id, organization_id).await;
+    assert_ne!(response["errors"], Value::Null, "{response}");
+
+    let response = resend(other_user_id, other_organization_id).await;
+    assert_ne!(response["errors"], Value::Null, "{response}");
+
+    assert!(app.svix.attempts(&svix_app_id).is_empty());
+
+    let response = resend(user_id, organization_id).await;
+    assert_eq!(response["errors"], Value::Null, "{response}");
+    assert_eq!(
+        response["data"]["resendWebhookMessage"],
+        json!({ "webhook": webhook["id"], "messageId": message.id })
+    );
+
+    let attempts = app.svix.attempts(&svix_app_id);
+    assert_eq!(attempts.len(), 1);
+    assert_eq!(attempts[0]["msgId"], message.id);
+    assert_eq!(attempts[0]["endpointId"], webhook["endpointId"]);
+
+    let events = app.producer.events();
+    assert_eq!(events.len(), 2);
+
+    let Some(Event::MessageResent(resent)) = &events[1].0.event else {
+        panic!("expected a resend, got {:?}", events[1].0);
+    };
+    assert_eq!(resent.message_id, message.id);
+    assert_eq!(events[1].1.user_id, user_id.to_string());
+}
+
+#[tokio::test]
+async fn recover_webhook() {
+    let app = TestApp::start().await;
+    let user_id = Uuid::new_v4();
+    let project_id = Uuid::new_v4();
+    let since = "2023-05-01T00:00:00+00:00";
+
+    let organization_id = app.create_organization(user_id).await;
+    let svix_app_id = app.svix_app_id(organization_id).await;
+
+    let response = create_webhook(&app, user_id, organization_id, project_id, &[
+        "CUSTOMER_CREATED",
+    ])
+    .await;
+    let webhook = &response["data"]["createWebhook"]["webhook"];
+
+    let recover = |user_id: Uuid| {
+        app.graphql(
+            user_id,
+            RECOVER_WEBHOOK,
+            json!({
+                "input": {
+                    "organization": organization_id,
+                    "webhook": webhook["id"],
+                    "since": since,
+                }
+            }),
+        )
+    };
+
+    let response = recover(Uuid::new_v4()).await;
+    assert_ne!(response["errors"], Value::Null, "{response}");
+    assert!(app.svix.recoveries(&svix_app_id).is_empty());
+
+    let response = recover(user_id).await;
+    assert_eq!(response["errors"], Value::Null, "{response}");
+
+    assert_eq!(
+        app.svix.recoveries(&svix_app_id),
+        vec![json!({ "endpointId": webhook["endpointId"], "since": since })]
+    );
+
+    let events = app.producer.events();
+    assert_eq!(events.len(), 2);
+
+    let Some(Event::Recovered(recovery)) = &events[1].0.event else {
+        panic!("expected a recovery, got {:?}", events[1].0);
+    };
+    assert_eq!(recovery.since, since);
+}
+
+const SEND_TEST_EVENT: &str = r#"
+    mutation SendTestEvent($input: SendTestEventInput!) {
+        sendTestEvent(input: $input) { webhook messageId }
+    }
+"#;
+
+const WEBHOOK_MESSAGE_ATTEMPTS: &str = r#"
+    query WebhookMessageAttempts($id: UUID!) {
+        _entities(representations: [{ __typename: "Webhook", id: $id }]) {
+            ... on Webhook {
+                messageAttempts { edges { node { messageId eventType status } } }
+            }
+        }
+    }
+"#;
+
+#[tokio::test]
+async fn test_events_reach_endpoints_not_subscribed_to_them() {
+    let app = TestApp::start().await;
+    let user_id = Uuid::new_v4();
+    let project_id = Uuid::new_v4();
+
+    let organization_id = app.create_organization(user_id).await;
+    let svix_app_id = app.svix_app_id(organization_id).await;
+
+    let response = create_webhook(&app, user_id, organization_id, project_id, &[
+        "CUSTOMER_CREATED",
+    ])
+    .await;
+    let webhook = &response["data"]["createWebhook"]["webhook"];
+
+    let send = |user_id: Uuid| {
+        app.graphql(
+            user_id,
+            SEND_TEST_EVENT,
+            json!({
+                "input": {
+                    "organization": organization_id,
+                    "webhook": webhook["id"],
+                    "eventType": "DROP_MINTED",
+                }
+            }),
+        )
+    };
+
+    let response = send(Uuid::new_v4()).await;
+    assert_ne!(response["errors"], Value::Null, "{response}");
+    assert!(app.svix.messages(&svix_app_id).is_empty());
+
+    let response = send(user_id).await;
+    assert_eq!(response["errors"], Value::Null, "{response}");
+
+    let message_id = &response["data"]["sendTestEvent"]["messageId"];
+
+    // the message is only sent on a channel of its own, and resent to the endpoint despite its filters
+    let messages = app.svix.messages(&svix_app_id);
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0]["id"], *message_id);
+    assert_eq!(messages[0]["eventType"], "drop.minted");
+    assert_eq!(
+        messages[0]["channels"],
+        json!([format!("test-{}", webhook["id"].as_str().unwrap())])
+    );
+    assert_eq!(
+        messages[0]["payload"]["organization_id"],
+        organization_id.to_string()
+    );
+
+    let attempts = app.svix.attempts(&svix_app_id);
+    assert_eq!(attempts.len(), 1);
+    assert_eq!(attempts[0]["msgId"], *message_id);
+    assert_eq!(attempts[0]["endpointId"], webhook["endpointId"]);
+
+    let response = app
+        .graphql(
+            user_id,
+            WEBHOOK_MESSAGE_ATTEMPTS,
+            json!({ "id": webhook["id"] }),
+        )
+        .await;
+
+    assert_eq!(
+        response["data"]["_entities"][0]["messageAttempts"]["edges"],
+        json!([{
+            "node": { "messageId": message_id, "eventType": "DROP_MINTED", "status": "SUCCESS" },
+        }]),
+        "{response}"
+    );
+}
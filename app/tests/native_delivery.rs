@@ -0,0 +1,339 @@
+mod common;
+
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use holaplex_hub_webhooks::{
+    delivery::{
+        native::{self, signature, NativeArgs, NativeDelivery},
+        EndpointIn, EndpointOut, MessageAttemptListOptions, MessageAttemptOut, MessageIn,
+        MessageStatus, WebhookDelivery,
+    },
+    entities::native_message_attempts,
+};
+use hub_core::{
+    chrono::{TimeZone, Utc},
+    tokio,
+};
+use poem::{
+    handler,
+    http::{HeaderMap, StatusCode},
+    listener::{Acceptor, Listener, TcpListener},
+    middleware::AddData,
+    web::Data,
+    EndpointExt, Response, Route, Server,
+};
+use sea_orm::{prelude::*, Set};
+use serde_json::json;
+
+use self::common::TestApp;
+
+const WAIT_TIMEOUT: Duration = Duration::from_secs(10);
+const WAIT_INTERVAL: Duration = Duration::from_millis(50);
+
+/// A request received by a [`Receiver`].
+#[derive(Debug, Clone)]
+struct Received {
+    message_id: String,
+    timestamp: i64,
+    signatures: String,
+    body: String,
+}
+
+#[derive(Default)]
+struct ReceiverState {
+    received: Vec<Received>,
+    /// The number of requests to fail before responding successfully
+    failures: usize,
+}
+
+/// A customer's endpoint, recording the requests it receives.
+struct Receiver {
+    url: String,
+    state: Arc<Mutex<ReceiverState>>,
+}
+
+#[handler]
+fn receive(
+    Data(state): Data<&Arc<Mutex<ReceiverState>>>,
+    headers: &HeaderMap,
+    body: String,
+) -> Response {
+    let header = |name| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+            .to_string()
+    };
+
+    let mut state = state.lock().unwrap();
+
+    state.received.push(Received {
+        message_id: header("webhook-id"),
+        timestamp: header("webhook-timestamp").parse().unwrap_or_default(),
+        signatures: header("webhook-signature"),
+        body,
+    });
+
+    let status = if state.failures > 0 {
+        state.failures -= 1;
+
+        StatusCode::INTERNAL_SERVER_ERROR
+    } else {
+        StatusCode::OK
+    };
+
+    Response::builder().status(status).body("x".repeat(4096))
+}
+
+impl Receiver {
+    async fn start(failures: usize) -> Self {
+        let state = Arc::new(Mutex::new(ReceiverState {
+            received: Vec::new(),
+            failures,
+        }));
+
+        let acceptor = TcpListener::bind("127.0.0.1:0")
+            .into_acceptor()
+            .await
+            .expect("failed to bind the receiver");
+
+        let addr = acceptor
+            .local_addr()
+            .into_iter()
+            .find_map(|addr| addr.as_socket_addr().copied())
+            .expect("receiver has no local address");
+
+        let app = Route::new()
+            .at("/webhooks", receive)
+            .with(AddData::new(state.clone()));
+
+        tokio::spawn(Server::new_with_acceptor(acceptor).run(app));
+
+        Self {
+            url: format!("http://{addr}/webhooks"),
+            state,
+        }
+    }
+
+    fn received(&self) -> Vec<Received> {
+        self.state.lock().unwrap().received.clone()
+    }
+}
+
+fn native_args(allow_private_addresses: bool, max_attempts: i32) -> NativeArgs {
+    NativeArgs {
+        native_poll_interval: 1,
+        native_batch_size: 10,
+        native_request_timeout: 5,
+        native_max_attempts: max_attempts,
+        native_retry_base_delay: 1,
+        native_retry_max_delay: 1,
+        native_disable_after_failures: 50,
+        native_allow_private_addresses: allow_private_addresses,
+    }
+}
+
+/// Creates an application with one endpoint sending to `url`.
+async fn create_endpoint(delivery: &NativeDelivery, url: &str) -> (String, EndpointOut) {
+    let app_id = delivery
+        .create_application("test".to_string(), "test".to_string())
+        .await
+        .unwrap();
+
+    let endpoint = delivery
+        .create_endpoint(app_id.clone(), EndpointIn {
+            url: url.to_string(),
+            version: 1,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    (app_id, endpoint)
+}
+
+async fn send_message(delivery: &NativeDelivery, app_id: &str) -> String {
+    delivery
+        .create_message(
+            app_id.to_string(),
+            MessageIn {
+                event_type: "CUSTOMER_CREATED".to_string(),
+                payload: json!({ "customer_id": "1" }),
+                ..Default::default()
+            },
+            None,
+        )
+        .await
+        .unwrap()
+        .id
+}
+
+/// Waits until `count` attempts were made to an endpoint, returning them most recent first.
+async fn wait_for_attempts(
+    delivery: &NativeDelivery,
+    app_id: &str,
+    endpoint_id: &str,
+    count: usize,
+) -> Vec<MessageAttemptOut> {
+    let started = tokio::time::Instant::now();
+
+    loop {
+        let attempts = delivery
+            .list_endpoint_attempts(
+                app_id.to_string(),
+                endpoint_id.to_string(),
+                MessageAttemptListOptions::default(),
+            )
+            .await
+            .unwrap()
+            .data;
+
+        if attempts.len() >= count {
+            return attempts;
+        }
+
+        assert!(
+            started.elapsed() < WAIT_TIMEOUT,
+            "expected {count} attempts, got {}",
+            attempts.len()
+        );
+
+        tokio::time::sleep(WAIT_INTERVAL).await;
+    }
+}
+
+#[tokio::test]
+async fn failed_messages_are_signed_and_retried_until_delivered() {
+    let app = TestApp::start().await;
+    let delivery = NativeDelivery::new(app.db.clone());
+    let receiver = Receiver::start(1).await;
+
+    let (app_id, endpoint) = create_endpoint(&delivery, &receiver.url).await;
+    let secret = delivery
+        .get_endpoint_secret(app_id.clone(), endpoint.id.clone())
+        .await
+        .unwrap();
+    let message_id = send_message(&delivery, &app_id).await;
+
+    tokio::spawn(native::run(
+        app.db.clone(),
+        native_args(true, 3),
+        app.producer.clone(),
+    ));
+
+    let attempts = wait_for_attempts(&delivery, &app_id, &endpoint.id, 2).await;
+
+    let statuses: Vec<MessageStatus> = attempts.iter().map(|attempt| attempt.status).collect();
+    assert_eq!(statuses, [MessageStatus::Success, MessageStatus::Fail]);
+    assert_eq!(attempts[1].response_status_code, 500);
+
+    // only a short excerpt of each response is kept
+    for attempt in &attempts {
+        assert!(attempt.response.len() <= 256);
+    }
+
+    let received = receiver.received();
+    assert_eq!(received.len(), 2);
+
+    for request in received {
+        assert_eq!(request.message_id, message_id);
+        assert!(signature::verify(
+            &secret,
+            &request.message_id,
+            request.timestamp,
+            &request.body,
+            &request.signatures,
+        )
+        .is_ok());
+    }
+}
+
+#[tokio::test]
+async fn endpoints_on_private_addresses_are_not_sent_to() {
+    let app = TestApp::start().await;
+    let delivery = NativeDelivery::new(app.db.clone());
+    let receiver = Receiver::start(0).await;
+
+    let (app_id, endpoint) = create_endpoint(&delivery, &receiver.url).await;
+    send_message(&delivery, &app_id).await;
+
+    tokio::spawn(native::run(
+        app.db.clone(),
+        native_args(false, 1),
+        app.producer.clone(),
+    ));
+
+    let attempts = wait_for_attempts(&delivery, &app_id, &endpoint.id, 1).await;
+
+    assert_eq!(attempts[0].status, MessageStatus::Fail);
+    assert_eq!(attempts[0].response_status_code, 0);
+    assert!(receiver.received().is_empty());
+}
+
+#[tokio::test]
+async fn attempts_made_at_the_same_time_are_all_paged() {
+    let app = TestApp::start().await;
+    let delivery = NativeDelivery::new(app.db.clone());
+
+    let (app_id, endpoint) = create_endpoint(&delivery, "https://example.com/webhooks").await;
+    let (_, other_endpoint) = create_endpoint(&delivery, "https://example.org/webhooks").await;
+    let message_id = send_message(&delivery, &app_id).await;
+
+    let created_at = Utc.with_ymd_and_hms(2023, 5, 9, 10, 0, 0).unwrap();
+    let mut attempt_ids = HashSet::new();
+
+    for i in 0..5 {
+        let attempt = native_message_attempts::ActiveModel {
+            id: Set(format!("atmpt_{i}")),
+            message_id: Set(message_id.clone()),
+            endpoint_id: Set(endpoint.id.clone()),
+            url: Set(endpoint.url.clone()),
+            status: Set("fail".to_string()),
+            response_status_code: Set(500),
+            response: Set(String::new()),
+            created_at: Set(created_at.naive_utc()),
+        };
+
+        attempt_ids.insert(attempt.insert(app.db.get()).await.unwrap().id);
+    }
+
+    let mut paged = HashSet::new();
+    let mut iterator = None;
+
+    loop {
+        let page = delivery
+            .list_endpoint_attempts(app_id.clone(), endpoint.id.clone(), MessageAttemptListOptions {
+                iterator: iterator.clone(),
+                limit: Some(2),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        for attempt in page.data {
+            assert!(paged.insert(attempt.id), "attempt listed twice");
+        }
+
+        if page.done {
+            break;
+        }
+
+        iterator = page.iterator;
+    }
+
+    assert_eq!(paged, attempt_ids);
+
+    // the cursor of an endpoint cannot be used to page another one
+    let result = delivery
+        .list_endpoint_attempts(app_id, other_endpoint.id, MessageAttemptListOptions {
+            iterator: Some("atmpt_0".to_string()),
+            ..Default::default()
+        })
+        .await;
+    assert!(result.is_err());
+}
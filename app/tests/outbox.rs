@@ -0,0 +1,46 @@
+mod common;
+
+use holaplex_hub_webhooks::{delivery::MessageIn, entities::outbox_messages, outbox};
+use hub_core::uuid::Uuid;
+use sea_orm::EntityTrait;
+use serde_json::json;
+
+use self::common::TestApp;
+
+#[tokio::test]
+async fn outbox_holds_back_a_channel_until_its_failed_message_is_dead_lettered() {
+    let app = TestApp::start().await;
+    let user_id = Uuid::new_v4();
+
+    let organization_id = app.create_organization(user_id).await;
+    let svix_app_id = app.svix_app_id(organization_id).await;
+    let channel = Uuid::new_v4().to_string();
+
+    let message = |event_id: &str| MessageIn {
+        channels: Some(vec![channel.clone()]),
+        event_id: Some(event_id.to_string()),
+        event_type: "project.created".to_string(),
+        payload: json!({}),
+    };
+
+    // the application does not exist, so every attempt to publish the first message fails
+    outbox::enqueue(app.db.get(), "app_missing".to_string(), &message("first"))
+        .await
+        .unwrap();
+    outbox::enqueue(app.db.get(), svix_app_id.clone(), &message("second"))
+        .await
+        .unwrap();
+
+    let messages = app.svix.wait_for_messages(&svix_app_id, 1).await;
+    assert_eq!(messages[0]["eventId"], "second");
+
+    let dead_letters = outbox_messages::Entity::find()
+        .all(app.db.get())
+        .await
+        .unwrap();
+
+    assert_eq!(dead_letters.len(), 1);
+    assert_eq!(dead_letters[0].svix_app_id, "app_missing");
+    assert_eq!(dead_letters[0].attempts, 3);
+    assert!(dead_letters[0].dead_lettered_at.is_some());
+}
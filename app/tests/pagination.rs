@@ -0,0 +1,197 @@
+mod common;
+
+use holaplex_hub_webhooks::delivery::MessageIn;
+use hub_core::uuid::Uuid;
+use serde_json::{json, Value};
+
+use self::common::{create_webhook, TestApp};
+
+const ORGANIZATION_WEBHOOKS: &str = r#"
+    query OrganizationWebhooks($id: UUID!, $first: Int, $after: String, $filter: WebhookFilter) {
+        _entities(representations: [{ __typename: "Organization", id: $id }]) {
+            ... on Organization {
+                webhooks(first: $first, after: $after, filter: $filter) {
+                    edges { cursor node { id } }
+                    pageInfo { hasNextPage }
+                }
+            }
+        }
+    }
+"#;
+
+#[tokio::test]
+async fn organization_webhooks_are_paginated_and_filtered() {
+    let app = TestApp::start().await;
+    let user_id = Uuid::new_v4();
+    let project_id = Uuid::new_v4();
+
+    let organization_id = app.create_organization(user_id).await;
+
+    let mut created = Vec::new();
+
+    for filter_types in [["CUSTOMER_CREATED"], ["DROP_MINTED"], ["CUSTOMER_CREATED"]] {
+        let response =
+            create_webhook(&app, user_id, organization_id, project_id, &filter_types).await;
+
+        created.push(response["data"]["createWebhook"]["webhook"]["id"].clone());
+    }
+
+    let first_page = app
+        .graphql(
+            user_id,
+            ORGANIZATION_WEBHOOKS,
+            json!({ "id": organization_id, "first": 2 }),
+        )
+        .await;
+    let webhooks = &first_page["data"]["_entities"][0]["webhooks"];
+
+    assert_eq!(webhooks["pageInfo"]["hasNextPage"], true, "{first_page}");
+    assert_eq!(webhooks["edges"][0]["node"]["id"], created[2]);
+    assert_eq!(webhooks["edges"][1]["node"]["id"], created[1]);
+
+    let second_page = app
+        .graphql(
+            user_id,
+            ORGANIZATION_WEBHOOKS,
+            json!({
+                "id": organization_id,
+                "first": 2,
+                "after": webhooks["edges"][1]["cursor"],
+            }),
+        )
+        .await;
+    let webhooks = &second_page["data"]["_entities"][0]["webhooks"];
+
+    assert_eq!(webhooks["pageInfo"]["hasNextPage"], false, "{second_page}");
+    assert_eq!(
+        webhooks["edges"],
+        json!([{ "cursor": created[0], "node": { "id": created[0] } }])
+    );
+
+    let filtered = app
+        .graphql(
+            user_id,
+            ORGANIZATION_WEBHOOKS,
+            json!({
+                "id": organization_id,
+                "filter": { "eventTypes": ["DROP_MINTED"], "url": "example.com" },
+            }),
+        )
+        .await;
+    let webhooks = &filtered["data"]["_entities"][0]["webhooks"];
+
+    assert_eq!(
+        webhooks["edges"],
+        json!([{ "cursor": created[1], "node": { "id": created[1] } }])
+    );
+
+    // wildcards in the text are matched literally
+    let filtered = app
+        .graphql(
+            user_id,
+            ORGANIZATION_WEBHOOKS,
+            json!({ "id": organization_id, "filter": { "url": "example_com" } }),
+        )
+        .await;
+    let webhooks = &filtered["data"]["_entities"][0]["webhooks"];
+
+    assert_eq!(webhooks["edges"], json!([]), "{filtered}");
+}
+
+const WEBHOOK_MESSAGE_ATTEMPTS: &str = r#"
+    query WebhookMessageAttempts($id: UUID!, $first: Int, $after: String) {
+        _entities(representations: [{ __typename: "Webhook", id: $id }]) {
+            ... on Webhook {
+                messageAttempts(first: $first, after: $after) {
+                    edges { node { id messageId status attemptNumber } }
+                    pageInfo { hasNextPage }
+                }
+            }
+        }
+    }
+"#;
+
+#[tokio::test]
+async fn message_attempts_are_paginated_and_numbered() {
+    let app = TestApp::start().await;
+    let user_id = Uuid::new_v4();
+    let project_id = Uuid::new_v4();
+
+    let organization_id = app.create_organization(user_id).await;
+    let svix_app_id = app.svix_app_id(organization_id).await;
+
+    let response = create_webhook(&app, user_id, organization_id, project_id, &[
+        "CUSTOMER_CREATED",
+    ])
+    .await;
+    let webhook = &response["data"]["createWebhook"]["webhook"];
+    let endpoint_id = webhook["endpointId"].as_str().unwrap().to_string();
+
+    let message = app
+        .delivery
+        .create_message(
+            svix_app_id.clone(),
+            MessageIn {
+                channels: Some(vec![project_id.to_string()]),
+                event_id: None,
+                event_type: "customer.created".to_string(),
+                payload: json!({}),
+            },
+            None,
+        )
+        .await
+        .unwrap();
+
+    for _ in 0..3 {
+        app.delivery
+            .resend_message(svix_app_id.clone(), message.id.clone(), endpoint_id.clone())
+            .await
+            .unwrap();
+    }
+
+    let first_page = app
+        .graphql(
+            user_id,
+            WEBHOOK_MESSAGE_ATTEMPTS,
+            json!({ "id": webhook["id"], "first": 2 }),
+        )
+        .await;
+    let attempts = &first_page["data"]["_entities"][0]["messageAttempts"];
+
+    assert_eq!(attempts["pageInfo"]["hasNextPage"], true, "{first_page}");
+    assert_eq!(attempts["edges"][0]["node"]["messageId"], message.id);
+    assert_eq!(attempts["edges"][0]["node"]["status"], "SUCCESS");
+    assert_eq!(attempts["edges"][0]["node"]["attemptNumber"], 3);
+    assert_eq!(attempts["edges"][1]["node"]["attemptNumber"], 2);
+
+    let second_page = app
+        .graphql(
+            user_id,
+            WEBHOOK_MESSAGE_ATTEMPTS,
+            json!({
+                "id": webhook["id"],
+                "first": 2,
+                "after": attempts["edges"][1]["node"]["id"],
+            }),
+        )
+        .await;
+    let attempts = &second_page["data"]["_entities"][0]["messageAttempts"];
+
+    assert_eq!(attempts["pageInfo"]["hasNextPage"], false, "{second_page}");
+    assert_eq!(attempts["edges"].as_array().unwrap().len(), 1);
+    assert_eq!(attempts["edges"][0]["node"]["attemptNumber"], 1);
+
+    let response = app
+        .graphql(
+            Uuid::new_v4(),
+            WEBHOOK_MESSAGE_ATTEMPTS,
+            json!({ "id": webhook["id"] }),
+        )
+        .await;
+
+    assert_ne!(response["errors"], Value::Null, "{response}");
+    assert_eq!(
+        response["data"]["_entities"][0]["messageAttempts"],
+        Value::Null
+    );
+}
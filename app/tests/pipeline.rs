@@ -0,0 +1,200 @@
+mod common;
+
+use std::{sync::Arc, time::Duration};
+
+use holaplex_hub_webhooks::{
+    dead_letters::{DeadLetter, DeadLetterKey, DeadLetterPublisher, DeadLetterQueue},
+    entities::dead_letters,
+    pipeline::{Pipeline, PipelineArgs},
+    proto::{self, customer_events},
+    validation::PayloadValidation,
+    Consumed, Services, Source,
+};
+use hub_core::{
+    anyhow::{anyhow, Result},
+    chrono::Utc,
+    tokio,
+    uuid::Uuid,
+};
+use poem::async_trait;
+use sea_orm::prelude::*;
+
+use self::common::{create_webhook, customer_created, RecordingDeadLetterQueue, TestApp};
+
+/// Fails to publish every dead letter, like a Kafka cluster that is down.
+struct UnavailableDeadLetterQueue;
+
+#[async_trait]
+impl DeadLetterPublisher for UnavailableDeadLetterQueue {
+    async fn send(&self, _dead_letter: &DeadLetter, _key: &DeadLetterKey) -> Result<()> {
+        Err(anyhow!("kafka unavailable"))
+    }
+}
+
+fn pipeline(app: &TestApp, dead_letter_queue: DeadLetterQueue) -> Pipeline {
+    Pipeline::new(
+        PipelineArgs {
+            consumer_workers: 4,
+            consumer_queue_size: 8,
+            consumer_max_retries: 0,
+            consumer_retry_delay: 1,
+        },
+        app.db.clone(),
+        app.delivery.clone(),
+        app.producer.clone(),
+        dead_letter_queue,
+        PayloadValidation::Strict,
+    )
+}
+
+fn consumed(msg: Services, offset: i64) -> Consumed {
+    Consumed {
+        msg,
+        source: Source {
+            topic: "hub-customers".to_string(),
+            partition: 0,
+            offset,
+            timestamp: Utc::now(),
+        },
+    }
+}
+
+/// Waits until the pipeline reports `offset` as the offset to commit for partition 0.
+async fn wait_for_offset(pipeline: &Pipeline, offset: i64) {
+    for _ in 0..200 {
+        if pipeline
+            .committable()
+            .iter()
+            .any(|committable| committable.offset == offset)
+        {
+            return;
+        }
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+
+    panic!("offset {offset} was never committable");
+}
+
+#[tokio::test]
+async fn events_of_a_project_are_processed_in_order() {
+    let app = TestApp::start().await;
+    let user_id = Uuid::new_v4();
+    let project_ids = [Uuid::new_v4(), Uuid::new_v4()];
+
+    let organization_id = app.create_organization(user_id).await;
+    let svix_app_id = app.svix_app_id(organization_id).await;
+
+    for project_id in project_ids {
+        create_webhook(&app, user_id, organization_id, project_id, &[
+            "CUSTOMER_CREATED",
+        ])
+        .await;
+    }
+
+    let pipeline = pipeline(&app, Arc::new(RecordingDeadLetterQueue::default()));
+    let mut customer_ids = vec![Vec::new(), Vec::new()];
+
+    // the events of both projects are interleaved on one partition
+    for offset in 0..20 {
+        let project = usize::try_from(offset % 2).unwrap();
+        let (customer_id, msg) = customer_created(project_ids[project]);
+
+        customer_ids[project].push(customer_id.to_string());
+        pipeline.dispatch(consumed(msg, offset)).await.unwrap();
+    }
+
+    let messages = app.svix.wait_for_messages(&svix_app_id, 20).await;
+
+    for (project, project_id) in project_ids.iter().enumerate() {
+        let channel = project_id.to_string();
+        let received: Vec<String> = messages
+            .iter()
+            .filter(|message| {
+                message["channels"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .any(|c| c.as_str().map_or(false, |c| c.starts_with(&channel)))
+            })
+            .map(|message| {
+                message["payload"]["payload"]["customer_id"]
+                    .as_str()
+                    .unwrap()
+                    .to_string()
+            })
+            .collect();
+
+        assert_eq!(received, customer_ids[project]);
+    }
+
+    wait_for_offset(&pipeline, 20).await;
+}
+
+#[tokio::test]
+async fn offset_is_held_by_an_event_that_cannot_be_dead_lettered() {
+    let app = TestApp::start().await;
+    let user_id = Uuid::new_v4();
+    let project_id = Uuid::new_v4();
+
+    let organization_id = app.create_organization(user_id).await;
+    let svix_app_id = app.svix_app_id(organization_id).await;
+
+    create_webhook(&app, user_id, organization_id, project_id, &[
+        "CUSTOMER_CREATED",
+    ])
+    .await;
+
+    let pipeline = pipeline(&app, Arc::new(UnavailableDeadLetterQueue));
+
+    // fails to be processed, since its project ID is not a UUID
+    let invalid = Services::Customers(
+        proto::CustomerEventKey {
+            id: Uuid::new_v4().to_string(),
+            ..Default::default()
+        },
+        proto::CustomerEvents {
+            event: Some(customer_events::Event::Created(proto::Customer {
+                project_id: "not-a-project".to_string(),
+                ..Default::default()
+            })),
+        },
+    );
+    let (_, valid) = customer_created(project_id);
+
+    pipeline.dispatch(consumed(invalid, 0)).await.unwrap();
+    pipeline.dispatch(consumed(valid, 1)).await.unwrap();
+
+    app.svix.wait_for_messages(&svix_app_id, 1).await;
+
+    let mut stored = 0;
+
+    for _ in 0..200 {
+        stored = dead_letters::Entity::find()
+            .count(app.db.get())
+            .await
+            .unwrap();
+
+        if stored > 0 {
+            break;
+        }
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+
+    assert_eq!(stored, 1);
+
+    // the event after it was processed, but the failed event is read again after a restart
+    wait_for_offset(&pipeline, 0).await;
+    assert!(pipeline.committable().is_empty());
+
+    // once the partition is revoked and read again from its committed offset, it is tracked anew
+    pipeline.revoke("hub-customers", 0);
+
+    let (_, msg) = customer_created(project_id);
+    pipeline.dispatch(consumed(msg, 0)).await.unwrap();
+
+    app.svix.wait_for_messages(&svix_app_id, 2).await;
+
+    wait_for_offset(&pipeline, 1).await;
+}
@@ -0,0 +1,181 @@
+mod common;
+
+use std::time::Duration;
+
+use holaplex_hub_webhooks::{
+    delivery::{EndpointIn, EndpointUpdate},
+    event_types::ApiVersion,
+    reconcile::{self, Drift},
+};
+use hub_core::uuid::Uuid;
+
+use self::common::{create_webhook, TestApp};
+
+#[tokio::test]
+async fn reconcile_reports_and_repairs_drift() {
+    let app = TestApp::start().await;
+    let user_id = Uuid::new_v4();
+    let project_id = Uuid::new_v4();
+
+    let organization_id = app.create_organization(user_id).await;
+    let svix_app_id = app.svix_app_id(organization_id).await;
+
+    let mut webhooks = Vec::new();
+
+    for _ in 0..2 {
+        let response = create_webhook(&app, user_id, organization_id, project_id, &[
+            "CUSTOMER_CREATED",
+        ])
+        .await;
+        let webhook = &response["data"]["createWebhook"]["webhook"];
+
+        webhooks.push((
+            Uuid::parse_str(webhook["id"].as_str().unwrap()).unwrap(),
+            webhook["endpointId"].as_str().unwrap().to_string(),
+        ));
+    }
+
+    let (mismatched_webhook, mismatched_endpoint) = webhooks[0].clone();
+    let (dangling_webhook, dangling_endpoint) = webhooks[1].clone();
+
+    let endpoint = app
+        .delivery
+        .get_endpoint(svix_app_id.clone(), mismatched_endpoint.clone())
+        .await
+        .unwrap();
+
+    app.delivery
+        .update_endpoint(svix_app_id.clone(), mismatched_endpoint, EndpointUpdate {
+            channels: Some(vec![organization_id.to_string()]),
+            filter_types: endpoint.filter_types,
+            version: endpoint.version + 1,
+            description: endpoint.description,
+            disabled: endpoint.disabled,
+            rate_limit: endpoint.rate_limit,
+            url: endpoint.url,
+            uid: endpoint.uid,
+        })
+        .await
+        .unwrap();
+
+    app.delivery
+        .delete_endpoint(svix_app_id.clone(), dangling_endpoint.clone())
+        .await
+        .unwrap();
+
+    let orphan = app
+        .delivery
+        .create_endpoint(svix_app_id.clone(), EndpointIn {
+            channels: None,
+            filter_types: None,
+            version: 1,
+            description: None,
+            disabled: Some(false),
+            rate_limit: None,
+            secret: None,
+            url: "https://example.com/orphan".to_string(),
+            uid: None,
+        })
+        .await
+        .unwrap();
+
+    let mut expected_channels = vec![
+        ApiVersion::LATEST.channel(project_id),
+        ApiVersion::LATEST.channel(organization_id),
+    ];
+    expected_channels.sort();
+
+    let drifts = reconcile::reconcile(&app.db, &app.delivery, Duration::ZERO, false)
+        .await
+        .unwrap();
+
+    assert_eq!(drifts.len(), 3, "{drifts:?}");
+    assert!(drifts.contains(&Drift::ChannelsMismatch {
+        webhook_id: mismatched_webhook,
+        expected: expected_channels,
+        actual: vec![organization_id.to_string()],
+    }));
+    assert!(drifts.contains(&Drift::DanglingWebhook {
+        webhook_id: dangling_webhook,
+        endpoint_id: dangling_endpoint,
+    }));
+    assert!(drifts.contains(&Drift::OrphanEndpoint {
+        svix_app_id: svix_app_id.clone(),
+        endpoint_id: orphan.id,
+    }));
+
+    reconcile::reconcile(&app.db, &app.delivery, Duration::ZERO, true)
+        .await
+        .unwrap();
+
+    let drifts = reconcile::reconcile(&app.db, &app.delivery, Duration::ZERO, false)
+        .await
+        .unwrap();
+
+    assert!(drifts.is_empty(), "{drifts:?}");
+    assert_eq!(app.svix.endpoints(&svix_app_id).len(), 1);
+}
+
+#[tokio::test]
+async fn backfill_subscribes_endpoints_to_their_organization_channel() {
+    let app = TestApp::start().await;
+    let user_id = Uuid::new_v4();
+    let project_id = Uuid::new_v4();
+
+    let organization_id = app.create_organization(user_id).await;
+    let svix_app_id = app.svix_app_id(organization_id).await;
+
+    let response = create_webhook(&app, user_id, organization_id, project_id, &[
+        "PROJECT_CREATED",
+    ])
+    .await;
+    let endpoint_id = response["data"]["createWebhook"]["webhook"]["endpointId"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    // endpoints created before organization-wide events only listened on their projects
+    let project_channel = ApiVersion::LATEST.channel(project_id);
+    let endpoint = app
+        .delivery
+        .get_endpoint(svix_app_id.clone(), endpoint_id.clone())
+        .await
+        .unwrap();
+
+    app.delivery
+        .update_endpoint(svix_app_id.clone(), endpoint_id.clone(), EndpointUpdate {
+            channels: Some(vec![project_channel.clone()]),
+            filter_types: endpoint.filter_types,
+            version: endpoint.version + 1,
+            description: endpoint.description,
+            disabled: endpoint.disabled,
+            rate_limit: endpoint.rate_limit,
+            url: endpoint.url,
+            uid: endpoint.uid,
+        })
+        .await
+        .unwrap();
+
+    let subscribed = reconcile::backfill_organization_channels(&app.db, &app.delivery)
+        .await
+        .unwrap();
+    assert_eq!(subscribed, 1);
+
+    let endpoint = app
+        .delivery
+        .get_endpoint(svix_app_id, endpoint_id)
+        .await
+        .unwrap();
+    assert_eq!(
+        endpoint.channels,
+        Some(vec![
+            project_channel,
+            ApiVersion::LATEST.channel(organization_id),
+        ])
+    );
+
+    let subscribed = reconcile::backfill_organization_channels(&app.db, &app.delivery)
+        .await
+        .unwrap();
+    assert_eq!(subscribed, 0);
+}
@@ -0,0 +1,75 @@
+mod common;
+
+use holaplex_hub_webhooks::proto::webhook_events::Event;
+use hub_core::{
+    chrono::{self, DateTime, Utc},
+    uuid::Uuid,
+};
+use serde_json::{json, Value};
+
+use self::common::{create_webhook, TestApp};
+
+const ROTATE_WEBHOOK_SECRET: &str = r#"
+    mutation RotateWebhookSecret($input: RotateWebhookSecretInput!) {
+        rotateWebhookSecret(input: $input) {
+            secret
+            previousSecretExpiresAt
+        }
+    }
+"#;
+
+#[tokio::test]
+async fn rotate_webhook_secret_with_a_grace_period() {
+    let app = TestApp::start().await;
+    let user_id = Uuid::new_v4();
+    let project_id = Uuid::new_v4();
+
+    let organization_id = app.create_organization(user_id).await;
+
+    let response = create_webhook(&app, user_id, organization_id, project_id, &[
+        "CUSTOMER_CREATED",
+    ])
+    .await;
+    let webhook_id = &response["data"]["createWebhook"]["webhook"]["id"];
+
+    let rotate = |grace_period_hours: Value| {
+        app.graphql(
+            user_id,
+            ROTATE_WEBHOOK_SECRET,
+            json!({
+                "input": {
+                    "organization": organization_id,
+                    "webhook": webhook_id,
+                    "gracePeriodHours": grace_period_hours,
+                }
+            }),
+        )
+    };
+
+    let response = rotate(json!(1000)).await;
+    assert_ne!(response["errors"], Value::Null, "{response}");
+
+    // Svix only keeps the previous secret for the default grace period
+    let response = rotate(json!(48)).await;
+    assert_ne!(response["errors"], Value::Null, "{response}");
+    assert_eq!(app.producer.events().len(), 1);
+
+    let response = rotate(Value::Null).await;
+    assert_eq!(response["errors"], Value::Null, "{response}");
+
+    let rotation = &response["data"]["rotateWebhookSecret"];
+    assert!(rotation["secret"].as_str().unwrap().starts_with("whsec_"));
+
+    let expires_at: DateTime<Utc> = rotation["previousSecretExpiresAt"]
+        .as_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    let grace_period = expires_at - Utc::now();
+    assert!(grace_period > chrono::Duration::hours(23));
+    assert!(grace_period <= chrono::Duration::hours(24));
+
+    let events = app.producer.events();
+    assert_eq!(events.len(), 2);
+    assert!(matches!(events[1].0.event, Some(Event::SecretRotated(_))));
+}
@@ -0,0 +1,83 @@
+mod common;
+
+use holaplex_hub_webhooks::proto::webhook_events::Event;
+use hub_core::uuid::Uuid;
+use serde_json::{json, Value};
+
+use self::common::{create_webhook, TestApp, EDIT_WEBHOOK};
+
+#[tokio::test]
+async fn edit_webhook_publishes_changes() {
+    let app = TestApp::start().await;
+    let user_id = Uuid::new_v4();
+    let project_id = Uuid::new_v4();
+
+    let organization_id = app.create_organization(user_id).await;
+
+    let response = create_webhook(&app, user_id, organization_id, project_id, &[
+        "CUSTOMER_CREATED",
+    ])
+    .await;
+    let webhook_id = &response["data"]["createWebhook"]["webhook"]["id"];
+
+    let response = app
+        .graphql(
+            user_id,
+            EDIT_WEBHOOK,
+            json!({
+                "input": {
+                    "webhook": webhook_id,
+                    "url": "https://example.com/edited",
+                    "description": "Test webhook",
+                    "projects": [project_id],
+                    "filterTypes": ["CUSTOMER_CREATED"],
+                    "disabled": true,
+                }
+            }),
+        )
+        .await;
+
+    assert_eq!(response["errors"], Value::Null, "{response}");
+
+    let events = app.producer.events();
+    assert_eq!(events.len(), 3);
+
+    let Some(Event::Updated(update)) = &events[1].0.event else {
+        panic!("expected an update, got {:?}", events[1].0);
+    };
+    assert_eq!(update.changes.len(), 1);
+    assert_eq!(update.changes[0].field, "url");
+    assert_eq!(update.changes[0].previous, r#""https://example.com/webhook""#);
+    assert_eq!(update.changes[0].current, r#""https://example.com/edited""#);
+
+    assert!(matches!(events[2].0.event, Some(Event::Disabled(_))));
+    assert_eq!(events[2].1.user_id, user_id.to_string());
+
+    let response = app
+        .graphql(
+            user_id,
+            EDIT_WEBHOOK,
+            json!({
+                "input": {
+                    "webhook": webhook_id,
+                    "url": "https://example.com/edited",
+                    "description": "Edited webhook",
+                    "projects": [project_id],
+                    "filterTypes": ["CUSTOMER_CREATED"],
+                }
+            }),
+        )
+        .await;
+
+    assert_eq!(response["errors"], Value::Null, "{response}");
+
+    // leaving out `disabled` keeps the webhook disabled
+    let events = app.producer.events();
+    assert_eq!(events.len(), 4);
+
+    let Some(Event::Updated(update)) = &events[3].0.event else {
+        panic!("expected an update, got {:?}", events[3].0);
+    };
+    assert_eq!(update.changes.len(), 1);
+    assert_eq!(update.changes[0].field, "description");
+}
@@ -3,6 +3,17 @@ pub use sea_orm_migration::prelude::*;
 mod m20230227_235925_organization_applications_table;
 mod m20230227_235932_webhooks_table;
 mod m20230227_235936_webhook_projects_table;
+mod m20230410_143512_organization_members_table;
+mod m20230412_091544_outbox_messages_table;
+mod m20230413_160221_message_idempotency_keys_table;
+mod m20230414_110318_add_filter_types_to_webhooks;
+mod m20230418_135007_dead_letters_table;
+mod m20230420_102647_native_delivery_tables;
+mod m20230424_093512_add_url_and_disabled_to_webhooks;
+mod m20230425_141208_add_endpoint_metadata_to_webhooks;
+mod m20230427_101533_add_health_to_webhooks;
+mod m20230502_143027_add_api_version_to_webhooks;
+mod m20230509_094517_add_kafka_timestamp_to_dead_letters;
 
 pub struct Migrator;
 
@@ -13,6 +24,17 @@ impl MigratorTrait for Migrator {
             Box::new(m20230227_235925_organization_applications_table::Migration),
             Box::new(m20230227_235932_webhooks_table::Migration),
             Box::new(m20230227_235936_webhook_projects_table::Migration),
+            Box::new(m20230410_143512_organization_members_table::Migration),
+            Box::new(m20230412_091544_outbox_messages_table::Migration),
+            Box::new(m20230413_160221_message_idempotency_keys_table::Migration),
+            Box::new(m20230414_110318_add_filter_types_to_webhooks::Migration),
+            Box::new(m20230418_135007_dead_letters_table::Migration),
+            Box::new(m20230420_102647_native_delivery_tables::Migration),
+            Box::new(m20230424_093512_add_url_and_disabled_to_webhooks::Migration),
+            Box::new(m20230425_141208_add_endpoint_metadata_to_webhooks::Migration),
+            Box::new(m20230427_101533_add_health_to_webhooks::Migration),
+            Box::new(m20230502_143027_add_api_version_to_webhooks::Migration),
+            Box::new(m20230509_094517_add_kafka_timestamp_to_dead_letters::Migration),
         ]
     }
 }
@@ -0,0 +1,66 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(OrganizationMembers::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(OrganizationMembers::OrganizationId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(OrganizationMembers::UserId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(OrganizationMembers::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("default now()".to_string()),
+                    )
+                    .col(ColumnDef::new(OrganizationMembers::DeactivatedAt).timestamp())
+                    .primary_key(
+                        Index::create()
+                            .col(OrganizationMembers::OrganizationId)
+                            .col(OrganizationMembers::UserId),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                IndexCreateStatement::new()
+                    .name("organization-members_user_id_idx")
+                    .table(OrganizationMembers::Table)
+                    .col(OrganizationMembers::UserId)
+                    .index_type(IndexType::Hash)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(OrganizationMembers::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum OrganizationMembers {
+    Table,
+    OrganizationId,
+    UserId,
+    CreatedAt,
+    DeactivatedAt,
+}
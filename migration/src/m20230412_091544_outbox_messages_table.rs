@@ -0,0 +1,113 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(OutboxMessages::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(OutboxMessages::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("default gen_random_uuid()".to_string()),
+                    )
+                    .col(
+                        ColumnDef::new(OutboxMessages::SvixAppId)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(OutboxMessages::Message)
+                            .json_binary()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(OutboxMessages::Attempts)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(ColumnDef::new(OutboxMessages::LastError).text())
+                    .col(
+                        ColumnDef::new(OutboxMessages::NextAttemptAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("default now()".to_string()),
+                    )
+                    .col(
+                        ColumnDef::new(OutboxMessages::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("default now()".to_string()),
+                    )
+                    .col(
+                        ColumnDef::new(OutboxMessages::Channel)
+                            .string()
+                            .not_null()
+                            .default(""),
+                    )
+                    .col(
+                        ColumnDef::new(OutboxMessages::Position)
+                            .big_integer()
+                            .not_null()
+                            .extra("generated by default as identity".to_string()),
+                    )
+                    .col(ColumnDef::new(OutboxMessages::LockedUntil).timestamp())
+                    .col(ColumnDef::new(OutboxMessages::DeadLetteredAt).timestamp())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                IndexCreateStatement::new()
+                    .name("outbox-messages_next_attempt_at_idx")
+                    .table(OutboxMessages::Table)
+                    .col(OutboxMessages::NextAttemptAt)
+                    .index_type(IndexType::BTree)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                IndexCreateStatement::new()
+                    .name("outbox-messages_channel_position_idx")
+                    .table(OutboxMessages::Table)
+                    .col(OutboxMessages::Channel)
+                    .col(OutboxMessages::Position)
+                    .index_type(IndexType::BTree)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(OutboxMessages::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum OutboxMessages {
+    Table,
+    Id,
+    SvixAppId,
+    Message,
+    Attempts,
+    LastError,
+    NextAttemptAt,
+    CreatedAt,
+    Channel,
+    Position,
+    LockedUntil,
+    DeadLetteredAt,
+}
@@ -0,0 +1,94 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(DeadLetters::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(DeadLetters::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("default gen_random_uuid()".to_string()),
+                    )
+                    .col(ColumnDef::new(DeadLetters::Topic).string().not_null())
+                    .col(ColumnDef::new(DeadLetters::Key).binary().not_null())
+                    .col(ColumnDef::new(DeadLetters::Payload).binary().not_null())
+                    .col(
+                        ColumnDef::new(DeadLetters::KafkaPartition)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(DeadLetters::KafkaOffset)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(DeadLetters::Error).text().not_null())
+                    .col(ColumnDef::new(DeadLetters::Attempts).integer().not_null())
+                    .col(
+                        ColumnDef::new(DeadLetters::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("default now()".to_string()),
+                    )
+                    .col(ColumnDef::new(DeadLetters::RedrivenAt).timestamp())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                IndexCreateStatement::new()
+                    .name("dead-letters_created_at_idx")
+                    .table(DeadLetters::Table)
+                    .col(DeadLetters::CreatedAt)
+                    .index_type(IndexType::BTree)
+                    .to_owned(),
+            )
+            .await?;
+
+        // a Kafka message read again after its dead letter failed to be published keeps the dead
+        // letter stored the first time
+        manager
+            .create_index(
+                IndexCreateStatement::new()
+                    .name("dead-letters_source_idx")
+                    .table(DeadLetters::Table)
+                    .col(DeadLetters::Topic)
+                    .col(DeadLetters::KafkaPartition)
+                    .col(DeadLetters::KafkaOffset)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(DeadLetters::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum DeadLetters {
+    Table,
+    Id,
+    Topic,
+    Key,
+    Payload,
+    KafkaPartition,
+    KafkaOffset,
+    Error,
+    Attempts,
+    CreatedAt,
+    RedrivenAt,
+}
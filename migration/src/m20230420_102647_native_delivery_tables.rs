@@ -0,0 +1,498 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(NativeApplications::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(NativeApplications::Id)
+                            .string()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(NativeApplications::Name).string().not_null())
+                    .col(
+                        ColumnDef::new(NativeApplications::Uid)
+                            .string()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(
+                        ColumnDef::new(NativeApplications::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("default now()".to_string()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(NativeEndpoints::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(NativeEndpoints::Id)
+                            .string()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(NativeEndpoints::ApplicationId)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(NativeEndpoints::Url).text().not_null())
+                    .col(ColumnDef::new(NativeEndpoints::Description).text())
+                    .col(
+                        ColumnDef::new(NativeEndpoints::Version)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(NativeEndpoints::Disabled)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(ColumnDef::new(NativeEndpoints::RateLimit).integer())
+                    .col(ColumnDef::new(NativeEndpoints::Uid).string())
+                    .col(ColumnDef::new(NativeEndpoints::Channels).json_binary())
+                    .col(ColumnDef::new(NativeEndpoints::FilterTypes).json_binary())
+                    .col(ColumnDef::new(NativeEndpoints::Secret).string().not_null())
+                    .col(ColumnDef::new(NativeEndpoints::PreviousSecret).string())
+                    .col(ColumnDef::new(NativeEndpoints::PreviousSecretExpiresAt).timestamp())
+                    .col(
+                        ColumnDef::new(NativeEndpoints::ConsecutiveFailures)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(NativeEndpoints::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("default now()".to_string()),
+                    )
+                    .col(
+                        ColumnDef::new(NativeEndpoints::UpdatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("default now()".to_string()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-native-endpoints_application_id")
+                            .from(NativeEndpoints::Table, NativeEndpoints::ApplicationId)
+                            .to(NativeApplications::Table, NativeApplications::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                IndexCreateStatement::new()
+                    .name("native-endpoints_application_id_idx")
+                    .table(NativeEndpoints::Table)
+                    .col(NativeEndpoints::ApplicationId)
+                    .index_type(IndexType::Hash)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(NativeEventTypes::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(NativeEventTypes::Name)
+                            .string()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(NativeEventTypes::Description)
+                            .text()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(NativeEventTypes::Schemas).json_binary())
+                    .col(
+                        ColumnDef::new(NativeEventTypes::Archived)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(
+                        ColumnDef::new(NativeEventTypes::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("default now()".to_string()),
+                    )
+                    .col(
+                        ColumnDef::new(NativeEventTypes::UpdatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("default now()".to_string()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(NativeMessages::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(NativeMessages::Id)
+                            .string()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(NativeMessages::ApplicationId)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(NativeMessages::EventType)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(NativeMessages::EventId).string())
+                    .col(ColumnDef::new(NativeMessages::Channels).json_binary())
+                    .col(
+                        ColumnDef::new(NativeMessages::Payload)
+                            .json_binary()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(NativeMessages::IdempotencyKey).string())
+                    .col(
+                        ColumnDef::new(NativeMessages::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("default now()".to_string()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-native-messages_application_id")
+                            .from(NativeMessages::Table, NativeMessages::ApplicationId)
+                            .to(NativeApplications::Table, NativeApplications::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                IndexCreateStatement::new()
+                    .name("native-messages_application_id_idempotency_key_idx")
+                    .table(NativeMessages::Table)
+                    .col(NativeMessages::ApplicationId)
+                    .col(NativeMessages::IdempotencyKey)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(NativeMessageDestinations::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(NativeMessageDestinations::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("default gen_random_uuid()".to_string()),
+                    )
+                    .col(
+                        ColumnDef::new(NativeMessageDestinations::MessageId)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(NativeMessageDestinations::EndpointId)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(NativeMessageDestinations::Status)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(NativeMessageDestinations::Attempts)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(NativeMessageDestinations::NextAttemptAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("default now()".to_string()),
+                    )
+                    .col(
+                        ColumnDef::new(NativeMessageDestinations::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("default now()".to_string()),
+                    )
+                    .col(ColumnDef::new(NativeMessageDestinations::LockedUntil).timestamp())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-native-message-destinations_message_id")
+                            .from(
+                                NativeMessageDestinations::Table,
+                                NativeMessageDestinations::MessageId,
+                            )
+                            .to(NativeMessages::Table, NativeMessages::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-native-message-destinations_endpoint_id")
+                            .from(
+                                NativeMessageDestinations::Table,
+                                NativeMessageDestinations::EndpointId,
+                            )
+                            .to(NativeEndpoints::Table, NativeEndpoints::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                IndexCreateStatement::new()
+                    .name("native-message-destinations_status_next_attempt_at_idx")
+                    .table(NativeMessageDestinations::Table)
+                    .col(NativeMessageDestinations::Status)
+                    .col(NativeMessageDestinations::NextAttemptAt)
+                    .index_type(IndexType::BTree)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(NativeMessageAttempts::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(NativeMessageAttempts::Id)
+                            .string()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(NativeMessageAttempts::MessageId)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(NativeMessageAttempts::EndpointId)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(NativeMessageAttempts::Url).text().not_null())
+                    .col(
+                        ColumnDef::new(NativeMessageAttempts::Status)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(NativeMessageAttempts::ResponseStatusCode)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(NativeMessageAttempts::Response)
+                            .text()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(NativeMessageAttempts::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("default now()".to_string()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-native-message-attempts_message_id")
+                            .from(
+                                NativeMessageAttempts::Table,
+                                NativeMessageAttempts::MessageId,
+                            )
+                            .to(NativeMessages::Table, NativeMessages::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-native-message-attempts_endpoint_id")
+                            .from(
+                                NativeMessageAttempts::Table,
+                                NativeMessageAttempts::EndpointId,
+                            )
+                            .to(NativeEndpoints::Table, NativeEndpoints::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                IndexCreateStatement::new()
+                    .name("native-message-attempts_endpoint_id_created_at_idx")
+                    .table(NativeMessageAttempts::Table)
+                    .col(NativeMessageAttempts::EndpointId)
+                    .col(NativeMessageAttempts::CreatedAt)
+                    .index_type(IndexType::BTree)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                IndexCreateStatement::new()
+                    .name("native-message-attempts_message_id_idx")
+                    .table(NativeMessageAttempts::Table)
+                    .col(NativeMessageAttempts::MessageId)
+                    .index_type(IndexType::Hash)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(NativeMessageAttempts::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(NativeMessageDestinations::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(NativeMessages::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(NativeEventTypes::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(NativeEndpoints::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(NativeApplications::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum NativeApplications {
+    Table,
+    Id,
+    Name,
+    Uid,
+    CreatedAt,
+}
+
+#[derive(Iden)]
+enum NativeEndpoints {
+    Table,
+    Id,
+    ApplicationId,
+    Url,
+    Description,
+    Version,
+    Disabled,
+    RateLimit,
+    Uid,
+    Channels,
+    FilterTypes,
+    Secret,
+    PreviousSecret,
+    PreviousSecretExpiresAt,
+    ConsecutiveFailures,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(Iden)]
+enum NativeEventTypes {
+    Table,
+    Name,
+    Description,
+    Schemas,
+    Archived,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(Iden)]
+enum NativeMessages {
+    Table,
+    Id,
+    ApplicationId,
+    EventType,
+    EventId,
+    Channels,
+    Payload,
+    IdempotencyKey,
+    CreatedAt,
+}
+
+#[derive(Iden)]
+enum NativeMessageDestinations {
+    Table,
+    Id,
+    MessageId,
+    EndpointId,
+    Status,
+    Attempts,
+    NextAttemptAt,
+    CreatedAt,
+    LockedUntil,
+}
+
+#[derive(Iden)]
+enum NativeMessageAttempts {
+    Table,
+    Id,
+    MessageId,
+    EndpointId,
+    Url,
+    Status,
+    ResponseStatusCode,
+    Response,
+    CreatedAt,
+}
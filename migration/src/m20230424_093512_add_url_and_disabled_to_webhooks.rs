@@ -0,0 +1,68 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Webhooks::Table)
+                    .add_column(ColumnDef::new(Webhooks::Url).text())
+                    .add_column(
+                        ColumnDef::new(Webhooks::Disabled)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                IndexCreateStatement::new()
+                    .name("webhooks_organization_id_created_at_idx")
+                    .table(Webhooks::Table)
+                    .col(Webhooks::OrganizationId)
+                    .col(Webhooks::CreatedAt)
+                    .col(Webhooks::Id)
+                    .index_type(IndexType::BTree)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("webhooks_organization_id_created_at_idx")
+                    .table(Webhooks::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Webhooks::Table)
+                    .drop_column(Webhooks::Url)
+                    .drop_column(Webhooks::Disabled)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum Webhooks {
+    Table,
+    Id,
+    OrganizationId,
+    CreatedAt,
+    Url,
+    Disabled,
+}
@@ -0,0 +1,41 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Webhooks::Table)
+                    .add_column(ColumnDef::new(Webhooks::Description).text())
+                    .add_column(ColumnDef::new(Webhooks::Channels).json_binary())
+                    .add_column(ColumnDef::new(Webhooks::SyncedAt).timestamp())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Webhooks::Table)
+                    .drop_column(Webhooks::Description)
+                    .drop_column(Webhooks::Channels)
+                    .drop_column(Webhooks::SyncedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum Webhooks {
+    Table,
+    Description,
+    Channels,
+    SyncedAt,
+}